@@ -0,0 +1,31 @@
+//! Fuzz `parse_binary` and the mapped-layout parsers
+//!
+//! Run with `cargo fuzz run parse_binary` from the crate directory; seeds live in
+//! `fuzz/corpus/parse_binary`.
+
+#![no_main]
+
+use endfield_binary_parser::{parse_binary, parse_binary_with_layout, BinaryFile, ImageLayout};
+use libfuzzer_sys::fuzz_target;
+
+/// Touch the accessors that index into the data after parsing
+fn exercise(binary: &dyn BinaryFile) {
+    for section in binary.sections() {
+        let _ = binary.section_data(section);
+        let _ = binary.read_va(section.virtual_address, 16);
+        let _ = binary.read_string_va(section.virtual_address, 64);
+    }
+    for symbol in binary.symbols().iter().take(64) {
+        let _ = binary.va_to_offset(symbol.address);
+    }
+    let _ = binary.function_at(binary.entry_point());
+}
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(binary) = parse_binary(data) {
+        exercise(binary.as_ref());
+    }
+    if let Ok(binary) = parse_binary_with_layout(data, Some(ImageLayout::Mapped)) {
+        exercise(binary.as_ref());
+    }
+});
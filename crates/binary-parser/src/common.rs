@@ -1,7 +1,11 @@
 //! Common traits and types for binary parsing
 
 use endfield_core::{Address, Architecture, BinaryFormat, Platform};
+use crate::disasm::{Disassembler, Instruction};
+use crate::scanner::{Pattern, Scanner};
+use crate::strings::{NativeString, StringOptions};
 use crate::ParseResult;
+use std::collections::HashMap;
 
 /// Trait for parsed binary files
 pub trait BinaryFile: Send + Sync {
@@ -56,6 +60,11 @@ pub trait BinaryFile: Send + Sync {
     /// Get the raw binary data
     fn data(&self) -> &[u8];
 
+    /// Get how the raw data is laid out
+    fn layout(&self) -> ImageLayout {
+        ImageLayout::File
+    }
+
     /// Get executable sections
     fn executable_sections(&self) -> Vec<&Section> {
         self.sections()
@@ -77,54 +86,114 @@ pub trait BinaryFile: Send + Sync {
 
     /// Search for a byte pattern in executable sections
     fn search_pattern(&self, pattern: &[u8]) -> Vec<Address> {
-        let mut results = Vec::new();
-        for section in self.executable_sections() {
-            if let Some(data) = self.section_data(section) {
-                for (offset, window) in data.windows(pattern.len()).enumerate() {
-                    if window == pattern {
-                        let va = section.virtual_address.offset(offset as i64);
-                        results.push(va);
-                    }
-                }
-            }
-        }
-        results
+        self.search_pattern_masked(pattern, &vec![1; pattern.len()])
     }
 
-    /// Search for a byte pattern with wildcards (0xFF = wildcard)
+    /// Search for a byte pattern with wildcards (mask 0 = wildcard)
+    ///
+    /// Returns no matches if the lengths differ or every byte is a wildcard.
     fn search_pattern_masked(&self, pattern: &[u8], mask: &[u8]) -> Vec<Address> {
-        assert_eq!(pattern.len(), mask.len());
-        let mut results = Vec::new();
+        match Pattern::from_masked(pattern, mask) {
+            Ok(pattern) => self.search(&pattern),
+            Err(_) => Vec::new(),
+        }
+    }
 
+    /// Search for an IDA style signature such as `48 8B ?? ?? 05`
+    fn search_signature(&self, signature: &str) -> ParseResult<Vec<Address>> {
+        Ok(self.search(&Pattern::parse(signature)?))
+    }
+
+    /// Search for a compiled pattern in executable sections
+    fn search(&self, pattern: &Pattern) -> Vec<Address> {
+        let mut scanner = Scanner::new();
+        scanner.add("", pattern.clone());
+
+        let mut results = Vec::new();
         for section in self.executable_sections() {
             if let Some(data) = self.section_data(section) {
-                'outer: for (offset, window) in data.windows(pattern.len()).enumerate() {
-                    for (i, &byte) in window.iter().enumerate() {
-                        if mask[i] != 0 && byte != pattern[i] {
-                            continue 'outer;
-                        }
-                    }
-                    let va = section.virtual_address.offset(offset as i64);
-                    results.push(va);
-                }
+                let offsets = scanner.scan_bytes(data).swap_remove(0);
+                results.extend(offsets.into_iter().map(|offset| section.virtual_address.offset(offset as i64)));
             }
         }
         results
     }
 
+    /// Get function extents from unwind or function-start tables
+    fn functions(&self) -> &[Function] {
+        &[]
+    }
+
+    /// Merge extra symbols, such as those read from debug information, into
+    /// [`symbols`](Self::symbols), returning how many were new
+    ///
+    /// Binaries without a symbol table of their own keep none of them.
+    fn add_symbols(&mut self, _symbols: Vec<Symbol>) -> usize {
+        0
+    }
+
+    /// Get the names of the shared libraries the binary links against
+    fn libraries(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Get the names of the functions the binary imports by name
+    fn imported_functions(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Find the function containing `va`
+    fn function_at(&self, va: Address) -> Option<&Function> {
+        let functions = self.functions();
+        let index = functions.partition_point(|f| f.start.as_u64() <= va.as_u64()).checked_sub(1)?;
+        Some(&functions[index]).filter(|f| va.as_u64() < f.end.as_u64())
+    }
+
+    /// Disassemble up to `max_instructions` at `va`, stopping after a return
+    fn disassemble(&self, va: Address, max_instructions: usize) -> ParseResult<Vec<Instruction>> {
+        Disassembler::new(self).disassemble(va, max_instructions)
+    }
+
+    /// Extract ASCII/UTF-8 and UTF-16LE strings, sorted by address
+    fn strings(&self, options: &StringOptions) -> Vec<NativeString> {
+        crate::strings::extract(self, options)
+    }
+
     /// Get raw data for a section
     fn section_data(&self, section: &Section) -> Option<&[u8]> {
-        let start = section.raw_offset as usize;
-        let end = start + section.raw_size as usize;
-        let data = self.data();
-        if end <= data.len() {
-            Some(&data[start..end])
-        } else {
-            None
-        }
+        checked_slice(self.data(), section.raw_offset, section.raw_size).ok()
     }
 }
 
+/// Bounds-checked `data[offset..offset + len]`
+pub fn checked_slice(data: &[u8], offset: u64, len: u64) -> ParseResult<&[u8]> {
+    offset
+        .checked_add(len)
+        .filter(|&end| end <= data.len() as u64)
+        .map(|end| &data[offset as usize..end as usize])
+        .ok_or_else(|| crate::ParseError::out_of_range(offset, len, data.len()))
+}
+
+/// Unwrap an optional table, logging and dropping it when malformed
+///
+/// Protected and memory-dumped images often carry garbage in tables the
+/// loader never reads, which shouldn't fail the whole parse.
+pub(crate) fn or_empty<T: Default>(table: &str, result: ParseResult<T>) -> T {
+    result.unwrap_or_else(|e| {
+        tracing::warn!("Ignoring malformed {}: {}", table, e);
+        T::default()
+    })
+}
+
+/// Layout of the bytes handed to a parser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageLayout {
+    /// On-disk file, sections at their raw offsets
+    File,
+    /// Memory dump, sections at their virtual address relative to the image base
+    Mapped,
+}
+
 /// Binary section information
 #[derive(Debug, Clone)]
 pub struct Section {
@@ -142,6 +211,19 @@ pub struct Section {
     pub characteristics: SectionFlags,
 }
 
+impl Section {
+    /// Check whether `va` lies inside the section's virtual range
+    pub fn contains_va(&self, va: u64) -> bool {
+        let start = self.virtual_address.as_u64();
+        va >= start && va - start < self.virtual_size
+    }
+
+    /// Check whether `offset` lies inside the section's raw data
+    pub fn contains_offset(&self, offset: u64) -> bool {
+        offset >= self.raw_offset && offset - self.raw_offset < self.raw_size
+    }
+}
+
 bitflags::bitflags! {
     /// Section flags
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -154,6 +236,84 @@ bitflags::bitflags! {
     }
 }
 
+/// Function extent recovered from unwind or function-start tables
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Function {
+    /// Start address
+    pub start: Address,
+    /// End address (exclusive)
+    pub end: Address,
+}
+
+impl Function {
+    /// Size in bytes
+    pub fn size(&self) -> u64 {
+        self.end.as_u64() - self.start.as_u64()
+    }
+}
+
+/// Sort and deduplicate function starts into [`Function`]s
+///
+/// Entries without an end run to the next start or the end of their
+/// executable section; entries outside executable sections need an end.
+pub(crate) fn build_functions(mut entries: Vec<(u64, Option<u64>)>, sections: &[Section]) -> Vec<Function> {
+    entries.retain(|&(start, _)| start != 0);
+    entries.sort_by_key(|&(start, end)| (start, end.is_none()));
+    entries.dedup_by_key(|&mut (start, _)| start);
+
+    let section_end = |va: u64| {
+        sections
+            .iter()
+            .filter(|s| s.characteristics.contains(SectionFlags::EXECUTE))
+            .find(|s| s.contains_va(va))
+            .map(|s| s.virtual_address.as_u64().saturating_add(s.virtual_size))
+    };
+
+    let mut functions = Vec::with_capacity(entries.len());
+    for (i, &(start, end)) in entries.iter().enumerate() {
+        let next = entries.get(i + 1).map_or(u64::MAX, |&(next, _)| next);
+        let end = match end {
+            Some(end) => end,
+            None => match section_end(start) {
+                Some(section_end) => section_end.min(next),
+                None => continue,
+            },
+        };
+        if end > start {
+            functions.push(Function { start: Address::new(start), end: Address::new(end) });
+        }
+    }
+    functions
+}
+
+/// Merge `extra` into `symbols`, filling in sizes, kinds and types of entries
+/// already present under the same name and address; returns how many were new
+pub(crate) fn merge_symbols(symbols: &mut Vec<Symbol>, extra: Vec<Symbol>) -> usize {
+    let mut known: HashMap<(String, u64), usize> = symbols
+        .iter()
+        .enumerate()
+        .map(|(index, symbol)| ((symbol.name.clone(), symbol.address.as_u64()), index))
+        .collect();
+    let before = symbols.len();
+    for symbol in extra {
+        match known.get(&(symbol.name.clone(), symbol.address.as_u64())) {
+            Some(&index) => {
+                let existing = &mut symbols[index];
+                existing.size = existing.size.or(symbol.size);
+                existing.type_name = existing.type_name.take().or(symbol.type_name);
+                if existing.symbol_type == SymbolType::Unknown {
+                    existing.symbol_type = symbol.symbol_type;
+                }
+            }
+            None => {
+                known.insert((symbol.name.clone(), symbol.address.as_u64()), symbols.len());
+                symbols.push(symbol);
+            }
+        }
+    }
+    symbols.len() - before
+}
+
 /// Symbol information
 #[derive(Debug, Clone)]
 pub struct Symbol {
@@ -165,6 +325,8 @@ pub struct Symbol {
     pub size: Option<u64>,
     /// Symbol type
     pub symbol_type: SymbolType,
+    /// Declared type from debug info, spelled like C (`int (char*, int)` for functions)
+    pub type_name: Option<String>,
 }
 
 /// Symbol type
@@ -213,21 +375,29 @@ impl<'a> BinaryReader<'a> {
         self.data.len().saturating_sub(self.offset)
     }
 
+    /// Take the next `count` bytes, advancing the offset
+    fn take(&mut self, count: usize) -> ParseResult<&'a [u8]> {
+        let bytes = self
+            .offset
+            .checked_add(count)
+            .and_then(|end| self.data.get(self.offset..end))
+            .ok_or_else(|| crate::ParseError::truncated(count, self.remaining()))?;
+        self.offset += count;
+        Ok(bytes)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> ParseResult<[u8; N]> {
+        let mut bytes = [0u8; N];
+        bytes.copy_from_slice(self.take(N)?);
+        Ok(bytes)
+    }
+
     pub fn read_u8(&mut self) -> ParseResult<u8> {
-        if self.offset >= self.data.len() {
-            return Err(crate::ParseError::truncated(1, 0));
-        }
-        let value = self.data[self.offset];
-        self.offset += 1;
-        Ok(value)
+        Ok(self.take(1)?[0])
     }
 
     pub fn read_u16(&mut self) -> ParseResult<u16> {
-        if self.offset + 2 > self.data.len() {
-            return Err(crate::ParseError::truncated(2, self.remaining()));
-        }
-        let bytes = [self.data[self.offset], self.data[self.offset + 1]];
-        self.offset += 2;
+        let bytes = self.take_array()?;
         Ok(if self.little_endian {
             u16::from_le_bytes(bytes)
         } else {
@@ -236,16 +406,7 @@ impl<'a> BinaryReader<'a> {
     }
 
     pub fn read_u32(&mut self) -> ParseResult<u32> {
-        if self.offset + 4 > self.data.len() {
-            return Err(crate::ParseError::truncated(4, self.remaining()));
-        }
-        let bytes = [
-            self.data[self.offset],
-            self.data[self.offset + 1],
-            self.data[self.offset + 2],
-            self.data[self.offset + 3],
-        ];
-        self.offset += 4;
+        let bytes = self.take_array()?;
         Ok(if self.little_endian {
             u32::from_le_bytes(bytes)
         } else {
@@ -254,12 +415,7 @@ impl<'a> BinaryReader<'a> {
     }
 
     pub fn read_u64(&mut self) -> ParseResult<u64> {
-        if self.offset + 8 > self.data.len() {
-            return Err(crate::ParseError::truncated(8, self.remaining()));
-        }
-        let mut bytes = [0u8; 8];
-        bytes.copy_from_slice(&self.data[self.offset..self.offset + 8]);
-        self.offset += 8;
+        let bytes = self.take_array()?;
         Ok(if self.little_endian {
             u64::from_le_bytes(bytes)
         } else {
@@ -276,51 +432,69 @@ impl<'a> BinaryReader<'a> {
     }
 
     pub fn read_bytes(&mut self, count: usize) -> ParseResult<&'a [u8]> {
-        if self.offset + count > self.data.len() {
-            return Err(crate::ParseError::truncated(count, self.remaining()));
-        }
-        let bytes = &self.data[self.offset..self.offset + count];
-        self.offset += count;
-        Ok(bytes)
+        self.take(count)
     }
 
     pub fn read_cstring(&mut self, max_len: usize) -> ParseResult<String> {
-        let start = self.offset;
-        let end = (start + max_len).min(self.data.len());
-
-        for i in start..end {
-            if self.data[i] == 0 {
-                let s = String::from_utf8_lossy(&self.data[start..i]).to_string();
-                self.offset = i + 1;
-                return Ok(s);
+        let window = &self.data[self.offset.min(self.data.len())..];
+        let window = &window[..max_len.min(window.len())];
+        let len = memchr::memchr(0, window).ok_or_else(|| crate::ParseError::parse("Unterminated string"))?;
+        let s = String::from_utf8_lossy(&window[..len]).to_string();
+        self.offset += len + 1;
+        Ok(s)
+    }
+
+    /// Bytes at an absolute offset, independent of the current position
+    pub fn bytes_at(&self, offset: u64, len: u64) -> ParseResult<&'a [u8]> {
+        checked_slice(self.data, offset, len)
+    }
+
+    /// String at an absolute offset, ending at a NUL, `max_len` or the end of the data
+    pub fn string_at(&self, offset: u64, max_len: usize) -> ParseResult<String> {
+        let available = (self.data.len() as u64).saturating_sub(offset);
+        let window = checked_slice(self.data, offset, available.min(max_len as u64))?;
+        let len = memchr::memchr(0, window).unwrap_or(window.len());
+        Ok(String::from_utf8_lossy(&window[..len]).to_string())
+    }
+
+    pub fn read_uleb128(&mut self) -> ParseResult<u64> {
+        let mut result = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = self.read_u8()?;
+            if shift < 64 {
+                result |= ((byte & 0x7F) as u64) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Ok(result);
             }
         }
+    }
 
-        Err(crate::ParseError::parse("Unterminated string"))
+    pub fn read_sleb128(&mut self) -> ParseResult<i64> {
+        let mut result = 0i64;
+        let mut shift = 0;
+        loop {
+            let byte = self.read_u8()?;
+            if shift < 64 {
+                result |= ((byte & 0x7F) as i64) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                if shift < 64 && byte & 0x40 != 0 {
+                    result |= -1i64 << shift;
+                }
+                return Ok(result);
+            }
+        }
     }
 
     pub fn skip(&mut self, count: usize) -> ParseResult<()> {
-        if self.offset + count > self.data.len() {
-            return Err(crate::ParseError::truncated(count, self.remaining()));
-        }
-        self.offset += count;
-        Ok(())
+        self.take(count).map(|_| ())
     }
 
     pub fn peek_u32(&self) -> ParseResult<u32> {
-        if self.offset + 4 > self.data.len() {
-            return Err(crate::ParseError::truncated(4, self.remaining()));
-        }
-        let bytes = [
-            self.data[self.offset],
-            self.data[self.offset + 1],
-            self.data[self.offset + 2],
-            self.data[self.offset + 3],
-        ];
-        Ok(if self.little_endian {
-            u32::from_le_bytes(bytes)
-        } else {
-            u32::from_be_bytes(bytes)
-        })
+        BinaryReader { offset: self.offset, ..*self }.read_u32()
     }
 }
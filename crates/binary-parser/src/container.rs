@@ -0,0 +1,279 @@
+//! APK / XAPK / IPA package loader
+//!
+//! Opens zip-based application packages in memory and locates the IL2CPP
+//! binary and `global-metadata.dat` inside them, including split APKs nested
+//! in an XAPK.
+
+use crate::common::BinaryFile;
+use crate::error::{ParseError, ParseResult};
+use crate::macho::MachOFile;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use zip::ZipArchive;
+
+/// Android ABIs in order of preference
+const ANDROID_ABIS: [&str; 4] = ["arm64-v8a", "armeabi-v7a", "x86_64", "x86"];
+
+/// Metadata path inside an APK
+const APK_METADATA: &str = "assets/bin/Data/Managed/Metadata/global-metadata.dat";
+
+/// Metadata path inside an iOS app bundle
+const IPA_METADATA: &str = "Data/Managed/Metadata/global-metadata.dat";
+
+/// Kind of application package
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageKind {
+    /// Single Android APK
+    Apk,
+    /// Android package bundle holding split APKs (XAPK, APKS, APKM)
+    Xapk,
+    /// iOS application archive
+    Ipa,
+}
+
+/// Application package opened in memory
+pub struct Package {
+    kind: PackageKind,
+    /// Outer archive first, then any nested split APKs
+    archives: Vec<ZipArchive<Cursor<Vec<u8>>>>,
+}
+
+impl Package {
+    /// Open a package file
+    pub fn open(path: &Path) -> ParseResult<Self> {
+        Self::from_bytes(std::fs::read(path)?)
+    }
+
+    /// Open a package from its bytes
+    pub fn from_bytes(data: Vec<u8>) -> ParseResult<Self> {
+        let mut outer = open_zip(data)?;
+        let names: Vec<String> = outer.file_names().map(str::to_string).collect();
+
+        let kind = if names.iter().any(|n| n.starts_with("Payload/") && n.contains(".app/")) {
+            PackageKind::Ipa
+        } else if names.iter().any(|n| n.ends_with(".apk") && !n.contains('/')) {
+            PackageKind::Xapk
+        } else if names.iter().any(|n| n == "AndroidManifest.xml" || n.starts_with("lib/")) {
+            PackageKind::Apk
+        } else {
+            return Err(ParseError::UnknownFormat);
+        };
+
+        let mut archives = Vec::new();
+        if kind == PackageKind::Xapk {
+            for name in names.iter().filter(|n| n.ends_with(".apk") && !n.contains('/')) {
+                archives.push(open_zip(read_entry(&mut outer, name)?)?);
+            }
+        }
+        archives.insert(0, outer);
+
+        Ok(Self { kind, archives })
+    }
+
+    /// Get the package kind
+    pub fn kind(&self) -> PackageKind {
+        self.kind
+    }
+
+    /// List the architectures the IL2CPP binary is available for
+    ///
+    /// Android ABIs such as `arm64-v8a`, or Mach-O slice names such as `arm64`.
+    pub fn abis(&mut self) -> ParseResult<Vec<String>> {
+        if self.kind == PackageKind::Ipa {
+            let binary = self.extract_binary(None)?;
+            return Ok(MachOFile::slices(&binary)?.iter().map(|slice| slice.name()).collect());
+        }
+
+        let mut abis: Vec<String> = self
+            .entry_names()
+            .filter_map(|name| {
+                let abi = name.strip_prefix("lib/")?.strip_suffix("/libil2cpp.so")?;
+                (!abi.contains('/')).then(|| abi.to_string())
+            })
+            .collect();
+        abis.sort_by_key(|abi| ANDROID_ABIS.iter().position(|a| a == abi).unwrap_or(ANDROID_ABIS.len()));
+        abis.dedup();
+        Ok(abis)
+    }
+
+    /// Extract the IL2CPP binary
+    ///
+    /// For Android `abi` picks the `lib/<abi>` directory, defaulting to the
+    /// most preferred ABI present. iOS binaries are returned whole; pass the
+    /// slice name to [`Package::binary`] to select an architecture.
+    pub fn extract_binary(&mut self, abi: Option<&str>) -> ParseResult<Vec<u8>> {
+        let path = match self.kind {
+            PackageKind::Ipa => self.ipa_binary_path()?,
+            PackageKind::Apk | PackageKind::Xapk => {
+                let abi = match abi {
+                    Some(abi) => abi.to_string(),
+                    None => self
+                        .abis()?
+                        .into_iter()
+                        .next()
+                        .ok_or_else(|| ParseError::EntryNotFound("lib/*/libil2cpp.so".to_string()))?,
+                };
+                format!("lib/{}/libil2cpp.so", abi)
+            }
+        };
+        self.extract(&path)
+    }
+
+    /// Extract `global-metadata.dat`
+    pub fn extract_metadata(&mut self) -> ParseResult<Vec<u8>> {
+        let path = match self.kind {
+            PackageKind::Ipa => format!("{}{}", self.ipa_app_dir()?, IPA_METADATA),
+            PackageKind::Apk | PackageKind::Xapk => APK_METADATA.to_string(),
+        };
+        self.extract(&path)
+    }
+
+    /// Extract and parse the IL2CPP binary for `abi`
+    pub fn binary(&mut self, abi: Option<&str>) -> ParseResult<Box<dyn BinaryFile>> {
+        let data = self.extract_binary(abi)?;
+        match (self.kind, abi) {
+            (PackageKind::Ipa, Some(arch)) => crate::parse_image_arch(data.into(), arch),
+            _ => crate::parse_image(data.into(), None),
+        }
+    }
+
+    /// Extract an entry by path from whichever archive holds it
+    pub fn extract(&mut self, path: &str) -> ParseResult<Vec<u8>> {
+        for archive in &mut self.archives {
+            if archive.file_names().any(|name| name == path) {
+                return read_entry(archive, path);
+            }
+        }
+        Err(ParseError::EntryNotFound(path.to_string()))
+    }
+
+    fn entry_names(&self) -> impl Iterator<Item = &str> {
+        self.archives.iter().flat_map(|archive| archive.file_names())
+    }
+
+    /// `Payload/<Name>.app/`
+    fn ipa_app_dir(&self) -> ParseResult<String> {
+        self.entry_names()
+            .filter_map(|name| {
+                let rest = name.strip_prefix("Payload/")?;
+                let end = rest.find(".app/")?;
+                Some(format!("Payload/{}", &rest[..end + 5]))
+            })
+            .min()
+            .ok_or_else(|| ParseError::EntryNotFound("Payload/*.app".to_string()))
+    }
+
+    /// `UnityFramework` for Unity 2019.3+, the main executable before that
+    fn ipa_binary_path(&self) -> ParseResult<String> {
+        let app_dir = self.ipa_app_dir()?;
+        let framework = format!("{}Frameworks/UnityFramework.framework/UnityFramework", app_dir);
+        if self.entry_names().any(|name| name == framework) {
+            return Ok(framework);
+        }
+
+        let app_name = app_dir
+            .trim_end_matches(".app/")
+            .trim_start_matches("Payload/");
+        Ok(format!("{}{}", app_dir, app_name))
+    }
+}
+
+fn open_zip(data: Vec<u8>) -> ParseResult<ZipArchive<Cursor<Vec<u8>>>> {
+    ZipArchive::new(Cursor::new(data)).map_err(|e| ParseError::parse(format!("Invalid zip archive: {}", e)))
+}
+
+fn read_entry(archive: &mut ZipArchive<Cursor<Vec<u8>>>, path: &str) -> ParseResult<Vec<u8>> {
+    let entry = archive.by_name(path).map_err(|e| match e {
+        zip::result::ZipError::FileNotFound => ParseError::EntryNotFound(path.to_string()),
+        e => ParseError::parse(format!("{}: {}", path, e)),
+    })?;
+    // The declared size is untrusted: grow the buffer as data arrives instead
+    // of reserving it up front, and never read past it
+    let size = entry.size();
+    let mut data = Vec::new();
+    entry.take(size).read_to_end(&mut data)?;
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::FileOptions;
+    use zip::ZipWriter;
+
+    fn zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in entries {
+            writer
+                .start_file(*name, FileOptions::default().compression_method(zip::CompressionMethod::Deflated))
+                .unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_apk() {
+        let apk = zip(&[
+            ("AndroidManifest.xml", b""),
+            ("lib/armeabi-v7a/libil2cpp.so", b"v7"),
+            ("lib/arm64-v8a/libil2cpp.so", b"v8"),
+            (APK_METADATA, b"metadata"),
+        ]);
+
+        let mut package = Package::from_bytes(apk).unwrap();
+        assert_eq!(package.kind(), PackageKind::Apk);
+        assert_eq!(package.abis().unwrap(), ["arm64-v8a", "armeabi-v7a"]);
+        assert_eq!(package.extract_binary(None).unwrap(), b"v8");
+        assert_eq!(package.extract_binary(Some("armeabi-v7a")).unwrap(), b"v7");
+        assert_eq!(package.extract_metadata().unwrap(), b"metadata");
+        assert!(matches!(package.extract_binary(Some("x86")), Err(ParseError::EntryNotFound(_))));
+    }
+
+    #[test]
+    fn test_xapk_split_apks() {
+        let base = zip(&[("AndroidManifest.xml", b""), (APK_METADATA, b"metadata")]);
+        let config = zip(&[("AndroidManifest.xml", b""), ("lib/arm64-v8a/libil2cpp.so", b"v8")]);
+        let xapk = zip(&[("manifest.json", b"{}"), ("base.apk", &base), ("config.arm64_v8a.apk", &config)]);
+
+        let mut package = Package::from_bytes(xapk).unwrap();
+        assert_eq!(package.kind(), PackageKind::Xapk);
+        assert_eq!(package.abis().unwrap(), ["arm64-v8a"]);
+        assert_eq!(package.extract_binary(None).unwrap(), b"v8");
+        assert_eq!(package.extract_metadata().unwrap(), b"metadata");
+    }
+
+    #[test]
+    fn test_oversized_entry() {
+        // Claim a 4 GiB uncompressed size in the local and central headers
+        let mut apk = zip(&[("AndroidManifest.xml", b""), (APK_METADATA, b"metadata")]);
+        for signature in [&b"PK\x03\x04"[..], &b"PK\x01\x02"[..]] {
+            let header = apk.windows(4).rposition(|w| w == signature).unwrap();
+            let size = header + if signature[2] == 3 { 22 } else { 24 };
+            apk[size..size + 4].copy_from_slice(&0xFFFF_FFF0u32.to_le_bytes());
+        }
+
+        let mut package = Package::from_bytes(apk).unwrap();
+        assert_eq!(package.extract_metadata().unwrap(), b"metadata");
+    }
+
+    #[test]
+    fn test_ipa() {
+        // Thin arm64 Mach-O header is enough to list the architecture
+        let mut macho = vec![0u8; 32];
+        macho[..4].copy_from_slice(&0xFEEDFACFu32.to_le_bytes());
+        macho[4..8].copy_from_slice(&0x0100000Cu32.to_le_bytes());
+        let ipa = zip(&[
+            ("Payload/Game.app/Info.plist", b""),
+            ("Payload/Game.app/Frameworks/UnityFramework.framework/UnityFramework", &macho),
+            ("Payload/Game.app/Data/Managed/Metadata/global-metadata.dat", b"metadata"),
+        ]);
+
+        let mut package = Package::from_bytes(ipa).unwrap();
+        assert_eq!(package.kind(), PackageKind::Ipa);
+        assert_eq!(package.abis().unwrap(), ["arm64"]);
+        assert_eq!(package.extract_binary(None).unwrap(), macho);
+        assert_eq!(package.extract_metadata().unwrap(), b"metadata");
+    }
+}
@@ -0,0 +1,607 @@
+//! Debug information ingestion
+//!
+//! Reads function and global variable symbols from PDB files and from DWARF
+//! sections (unstripped ELF/Mach-O binaries or separate debug files), so leaked
+//! debug builds can fill in names, sizes and kinds the stripped image lacks.
+
+use crate::common::{BinaryFile, Symbol, SymbolType};
+use crate::error::{ParseError, ParseResult};
+use crate::image::ImageData;
+use endfield_core::{Address, BinaryFormat};
+use gimli::{AttributeValue, EndianSlice, LittleEndian, Operation};
+use pdb::FallibleIterator;
+use std::io::Cursor;
+use std::path::Path;
+use tracing::{debug, warn};
+
+/// MSF 7.0 superblock magic starting every PDB
+const PDB_MAGIC: &[u8] = b"Microsoft C/C++ MSF 7.00\r\n\x1aDS";
+
+/// Deepest chain of pointer, modifier and typedef hops followed when spelling a type
+const MAX_TYPE_DEPTH: usize = 16;
+
+type DwarfReader<'a> = EndianSlice<'a, LittleEndian>;
+
+/// Read symbols from a PDB file or a binary carrying DWARF and merge them
+/// into `binary`, returning how many were new
+pub fn load_debug_symbols(binary: &mut dyn BinaryFile, path: &Path) -> ParseResult<usize> {
+    let data = ImageData::map(path)?;
+    let symbols = if data.starts_with(PDB_MAGIC) {
+        pdb_symbols(&data, binary.image_base())?
+    } else {
+        dwarf_symbols(crate::parse_image(data, None)?.as_ref())?
+    };
+    Ok(binary.add_symbols(symbols))
+}
+
+/// Public symbols, procedures and global data from a PDB, rebased onto `image_base`
+pub fn pdb_symbols(data: &[u8], image_base: Address) -> ParseResult<Vec<Symbol>> {
+    let mut pdb = pdb::PDB::open(Cursor::new(data)).map_err(pdb_error)?;
+    let address_map = pdb.address_map().map_err(pdb_error)?;
+    let type_information = pdb.type_information().map_err(pdb_error)?;
+    let mut finder = type_information.finder();
+    let mut types = type_information.iter();
+    while types.next().map_err(pdb_error)?.is_some() {
+        finder.update(&types);
+    }
+    let mut symbols = Vec::new();
+
+    let mut push = |symbol: pdb::Symbol<'_>| {
+        let (name, offset, size, symbol_type, type_index) = match symbol.parse() {
+            Ok(pdb::SymbolData::Public(public)) => {
+                let symbol_type = if public.function { SymbolType::Function } else { SymbolType::Object };
+                (public.name, public.offset, None, symbol_type, None)
+            }
+            Ok(pdb::SymbolData::Procedure(procedure)) => (
+                procedure.name,
+                procedure.offset,
+                Some(procedure.len as u64),
+                SymbolType::Function,
+                Some(procedure.type_index),
+            ),
+            Ok(pdb::SymbolData::Data(data)) => (data.name, data.offset, None, SymbolType::Object, Some(data.type_index)),
+            _ => return,
+        };
+        if let Some(rva) = offset.to_rva(&address_map) {
+            symbols.push(Symbol {
+                name: name.to_string().into_owned(),
+                address: Address::new(image_base.as_u64().wrapping_add(rva.0 as u64)),
+                size,
+                symbol_type,
+                type_name: type_index.and_then(|index| pdb_type_name(&finder, index, 0)),
+            });
+        }
+    };
+
+    let globals = pdb.global_symbols().map_err(pdb_error)?;
+    let mut iter = globals.iter();
+    while let Some(symbol) = iter.next().map_err(pdb_error)? {
+        push(symbol);
+    }
+
+    // Procedures with their sizes live in the per-module streams
+    let debug_information = pdb.debug_information().map_err(pdb_error)?;
+    let mut modules = debug_information.modules().map_err(pdb_error)?;
+    while let Some(module) = modules.next().map_err(pdb_error)? {
+        let Some(info) = pdb.module_info(&module).map_err(pdb_error)? else {
+            continue;
+        };
+        let mut iter = info.symbols().map_err(pdb_error)?;
+        while let Some(symbol) = iter.next().map_err(pdb_error)? {
+            push(symbol);
+        }
+    }
+
+    Ok(symbols)
+}
+
+/// C spelling of a PDB type record, `None` for records it can't name
+fn pdb_type_name(finder: &pdb::TypeFinder<'_>, index: pdb::TypeIndex, depth: usize) -> Option<String> {
+    if depth > MAX_TYPE_DEPTH {
+        return None;
+    }
+    let inner = |index| pdb_type_name(finder, index, depth + 1);
+    match finder.find(index).ok()?.parse().ok()? {
+        pdb::TypeData::Primitive(primitive) => {
+            let name = primitive_name(primitive.kind)?;
+            Some(if primitive.indirection.is_some() { format!("{}*", name) } else { name.to_string() })
+        }
+        pdb::TypeData::Class(class) => Some(class.name.to_string().into_owned()),
+        pdb::TypeData::Union(union) => Some(union.name.to_string().into_owned()),
+        pdb::TypeData::Enumeration(enumeration) => Some(enumeration.name.to_string().into_owned()),
+        pdb::TypeData::Pointer(pointer) => {
+            let suffix = if pointer.attributes.is_reference() { "&" } else { "*" };
+            inner(pointer.underlying_type).map(|name| format!("{}{}", name, suffix))
+        }
+        pdb::TypeData::Modifier(modifier) => {
+            let name = inner(modifier.underlying_type)?;
+            Some(if modifier.constant { format!("const {}", name) } else { name })
+        }
+        pdb::TypeData::Array(array) => inner(array.element_type).map(|name| format!("{}[]", name)),
+        pdb::TypeData::Procedure(procedure) => {
+            let return_type = match procedure.return_type {
+                Some(index) => inner(index)?,
+                None => "void".to_string(),
+            };
+            pdb_function_name(finder, return_type, procedure.argument_list, depth)
+        }
+        pdb::TypeData::MemberFunction(function) => {
+            pdb_function_name(finder, inner(function.return_type)?, function.argument_list, depth)
+        }
+        _ => None,
+    }
+}
+
+fn pdb_function_name(
+    finder: &pdb::TypeFinder<'_>,
+    return_type: String,
+    argument_list: pdb::TypeIndex,
+    depth: usize,
+) -> Option<String> {
+    let pdb::TypeData::ArgumentList(list) = finder.find(argument_list).ok()?.parse().ok()? else {
+        return None;
+    };
+    let arguments = list
+        .arguments
+        .iter()
+        .map(|&index| match index.0 {
+            // T_NOTYPE closes a variadic list
+            0 => Some("...".to_string()),
+            _ => pdb_type_name(finder, index, depth + 1),
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(format!("{} ({})", return_type, arguments.join(", ")))
+}
+
+fn primitive_name(kind: pdb::PrimitiveKind) -> Option<&'static str> {
+    use pdb::PrimitiveKind::*;
+    Some(match kind {
+        Void => "void",
+        Char | RChar | I8 => "char",
+        UChar | U8 => "unsigned char",
+        WChar => "wchar_t",
+        RChar16 => "char16_t",
+        RChar32 => "char32_t",
+        Short | I16 => "short",
+        UShort | U16 => "unsigned short",
+        Long => "long",
+        ULong => "unsigned long",
+        I32 => "int",
+        U32 => "unsigned int",
+        Quad | I64 => "long long",
+        UQuad | U64 => "unsigned long long",
+        F32 => "float",
+        F64 => "double",
+        Bool8 => "bool",
+        HRESULT => "HRESULT",
+        _ => return None,
+    })
+}
+
+fn pdb_error(e: pdb::Error) -> ParseError {
+    ParseError::parse(format!("Invalid PDB: {}", e))
+}
+
+/// Functions and global variables described by a binary's DWARF sections
+pub fn dwarf_symbols<B: BinaryFile + ?Sized>(binary: &B) -> ParseResult<Vec<Symbol>> {
+    let macho = binary.format() == BinaryFormat::MachO;
+    dwarf_symbols_from(|name| {
+        // Mach-O keeps `.debug_info` as `__debug_info` in the `__DWARF` segment,
+        // cut to the 16 bytes a section name can hold
+        let name = if macho {
+            let section: String = format!("__{}", &name[1..]).chars().take(16).collect();
+            format!("__DWARF,{}", section)
+        } else {
+            name.to_string()
+        };
+        binary.find_section(&name).and_then(|section| binary.section_data(section)).unwrap_or(&[])
+    })
+}
+
+/// Walk every DIE, keeping subprograms with code and variables at fixed addresses
+///
+/// A malformed entry is skipped and a malformed unit abandoned, so one bad
+/// record doesn't cost the symbols around it.
+fn dwarf_symbols_from<'a>(section: impl Fn(&str) -> &'a [u8]) -> ParseResult<Vec<Symbol>> {
+    let dwarf = gimli::Dwarf::load(|id| {
+        Ok::<_, gimli::Error>(EndianSlice::new(section(id.name()), LittleEndian))
+    })
+    .map_err(dwarf_error)?;
+
+    let mut symbols = Vec::new();
+    let mut units = dwarf.units();
+    loop {
+        // Without a readable header there is no way to find the next unit
+        let header = match units.next() {
+            Ok(Some(header)) => header,
+            Ok(None) => break,
+            Err(e) => {
+                warn!("Stopping at malformed DWARF unit header: {}", e);
+                break;
+            }
+        };
+        let unit = match dwarf.unit(header) {
+            Ok(unit) => unit,
+            Err(e) => {
+                warn!("Skipping malformed DWARF unit: {}", e);
+                continue;
+            }
+        };
+        let mut entries = unit.entries();
+        loop {
+            let entry = match entries.next_dfs() {
+                Ok(Some((_, entry))) => entry,
+                Ok(None) => break,
+                Err(e) => {
+                    warn!("Skipping the rest of a malformed DWARF unit: {}", e);
+                    break;
+                }
+            };
+            let symbol = match entry.tag() {
+                gimli::DW_TAG_subprogram => subprogram(&dwarf, &unit, entry),
+                gimli::DW_TAG_variable => variable(&dwarf, &unit, entry),
+                _ => Ok(None),
+            };
+            match symbol {
+                Ok(symbol) => symbols.extend(symbol),
+                Err(e) => debug!("Skipping DWARF entry at {:?}: {}", entry.offset(), e),
+            }
+        }
+    }
+    Ok(symbols)
+}
+
+fn dwarf_error(e: gimli::Error) -> ParseError {
+    ParseError::parse(format!("Invalid DWARF: {}", e))
+}
+
+type Entry<'abbrev, 'unit, 'a> = gimli::DebuggingInformationEntry<'abbrev, 'unit, DwarfReader<'a>>;
+
+fn subprogram(
+    dwarf: &gimli::Dwarf<DwarfReader<'_>>,
+    unit: &gimli::Unit<DwarfReader<'_>>,
+    entry: &Entry<'_, '_, '_>,
+) -> ParseResult<Option<Symbol>> {
+    let Some(low_pc) = entry.attr_value(gimli::DW_AT_low_pc).map_err(dwarf_error)? else {
+        return Ok(None);
+    };
+    let Some(start) = dwarf.attr_address(unit, low_pc).map_err(dwarf_error)? else {
+        return Ok(None);
+    };
+    // Functions discarded by the linker keep a zero start
+    if start == 0 {
+        return Ok(None);
+    }
+    let end = match entry.attr_value(gimli::DW_AT_high_pc).map_err(dwarf_error)? {
+        Some(AttributeValue::Udata(size)) => start.checked_add(size),
+        Some(value) => dwarf.attr_address(unit, value).map_err(dwarf_error)?,
+        None => None,
+    };
+    let Some(name) = entry_name(dwarf, unit, entry)? else {
+        return Ok(None);
+    };
+    Ok(Some(Symbol {
+        name,
+        address: Address::new(start),
+        size: end.filter(|&end| end > start).map(|end| end - start),
+        symbol_type: SymbolType::Function,
+        type_name: function_type_name(dwarf, unit, entry, 0)?,
+    }))
+}
+
+fn variable(
+    dwarf: &gimli::Dwarf<DwarfReader<'_>>,
+    unit: &gimli::Unit<DwarfReader<'_>>,
+    entry: &Entry<'_, '_, '_>,
+) -> ParseResult<Option<Symbol>> {
+    // Only statically allocated variables have a lone DW_OP_addr location
+    let Some(AttributeValue::Exprloc(expression)) = entry.attr_value(gimli::DW_AT_location).map_err(dwarf_error)?
+    else {
+        return Ok(None);
+    };
+    let mut operations = expression.operations(unit.encoding());
+    let address = match operations.next().map_err(dwarf_error)? {
+        Some(Operation::Address { address }) => address,
+        Some(Operation::AddressIndex { index }) => dwarf.address(unit, index).map_err(dwarf_error)?,
+        _ => return Ok(None),
+    };
+    if address == 0 || operations.next().map_err(dwarf_error)?.is_some() {
+        return Ok(None);
+    }
+    let Some(name) = entry_name(dwarf, unit, entry)? else {
+        return Ok(None);
+    };
+    let declaration = declaration(unit, entry)?;
+    Ok(Some(Symbol {
+        name,
+        address: Address::new(address),
+        size: None,
+        symbol_type: SymbolType::Object,
+        type_name: referenced_type_name(dwarf, unit, declaration.as_ref().unwrap_or(entry), 0)?,
+    }))
+}
+
+/// Linkage name, falling back to the plain name, following one
+/// `DW_AT_specification`/`DW_AT_abstract_origin` hop to the declaration
+///
+/// Linkage names match the ELF/Mach-O symbol tables, so merged entries dedupe.
+fn entry_name(
+    dwarf: &gimli::Dwarf<DwarfReader<'_>>,
+    unit: &gimli::Unit<DwarfReader<'_>>,
+    entry: &Entry<'_, '_, '_>,
+) -> ParseResult<Option<String>> {
+    if let Some(name) = own_name(dwarf, unit, entry)? {
+        return Ok(Some(name));
+    }
+    match declaration(unit, entry)? {
+        Some(declaration) => own_name(dwarf, unit, &declaration),
+        None => Ok(None),
+    }
+}
+
+/// Entry a definition points back at through `DW_AT_specification`/`DW_AT_abstract_origin`
+fn declaration<'abbrev, 'unit, 'a>(
+    unit: &'unit gimli::Unit<DwarfReader<'a>>,
+    entry: &Entry<'_, '_, '_>,
+) -> ParseResult<Option<Entry<'abbrev, 'unit, 'a>>>
+where
+    'unit: 'abbrev,
+{
+    for origin in [gimli::DW_AT_specification, gimli::DW_AT_abstract_origin] {
+        if let Some(AttributeValue::UnitRef(offset)) = entry.attr_value(origin).map_err(dwarf_error)? {
+            return unit.entry(offset).map(Some).map_err(dwarf_error);
+        }
+    }
+    Ok(None)
+}
+
+fn own_name(
+    dwarf: &gimli::Dwarf<DwarfReader<'_>>,
+    unit: &gimli::Unit<DwarfReader<'_>>,
+    entry: &Entry<'_, '_, '_>,
+) -> ParseResult<Option<String>> {
+    for attribute in [gimli::DW_AT_linkage_name, gimli::DW_AT_MIPS_linkage_name, gimli::DW_AT_name] {
+        if let Some(value) = entry.attr_value(attribute).map_err(dwarf_error)? {
+            return string(dwarf, unit, value).map(Some);
+        }
+    }
+    Ok(None)
+}
+
+fn string(
+    dwarf: &gimli::Dwarf<DwarfReader<'_>>,
+    unit: &gimli::Unit<DwarfReader<'_>>,
+    value: AttributeValue<DwarfReader<'_>>,
+) -> ParseResult<String> {
+    let name = dwarf.attr_string(unit, value).map_err(dwarf_error)?;
+    Ok(String::from_utf8_lossy(name.slice()).into_owned())
+}
+
+/// C spelling of the type `entry` refers to through `DW_AT_type`, `void` when it has none
+///
+/// References into other units aren't followed and come back as `None`.
+fn referenced_type_name(
+    dwarf: &gimli::Dwarf<DwarfReader<'_>>,
+    unit: &gimli::Unit<DwarfReader<'_>>,
+    entry: &Entry<'_, '_, '_>,
+    depth: usize,
+) -> ParseResult<Option<String>> {
+    match entry.attr_value(gimli::DW_AT_type).map_err(dwarf_error)? {
+        Some(AttributeValue::UnitRef(offset)) => {
+            let target = unit.entry(offset).map_err(dwarf_error)?;
+            type_name(dwarf, unit, &target, depth + 1)
+        }
+        Some(_) => Ok(None),
+        None => Ok(Some("void".to_string())),
+    }
+}
+
+/// C spelling of a type DIE, `None` for anonymous or unmodelled types
+fn type_name(
+    dwarf: &gimli::Dwarf<DwarfReader<'_>>,
+    unit: &gimli::Unit<DwarfReader<'_>>,
+    entry: &Entry<'_, '_, '_>,
+    depth: usize,
+) -> ParseResult<Option<String>> {
+    if depth > MAX_TYPE_DEPTH {
+        return Ok(None);
+    }
+    let inner = || referenced_type_name(dwarf, unit, entry, depth);
+    Ok(match entry.tag() {
+        gimli::DW_TAG_pointer_type => inner()?.map(|name| format!("{}*", name)),
+        gimli::DW_TAG_reference_type | gimli::DW_TAG_rvalue_reference_type => {
+            inner()?.map(|name| format!("{}&", name))
+        }
+        gimli::DW_TAG_const_type => inner()?.map(|name| format!("const {}", name)),
+        gimli::DW_TAG_volatile_type | gimli::DW_TAG_restrict_type | gimli::DW_TAG_atomic_type => inner()?,
+        gimli::DW_TAG_array_type => inner()?.map(|name| format!("{}[]", name)),
+        gimli::DW_TAG_subroutine_type => function_type_name(dwarf, unit, entry, depth)?,
+        _ => match entry.attr_value(gimli::DW_AT_name).map_err(dwarf_error)? {
+            Some(value) => Some(string(dwarf, unit, value)?),
+            None => None,
+        },
+    })
+}
+
+/// `return (parameters)` for a subprogram or subroutine type
+///
+/// Out-of-line definitions carry neither their return type nor, sometimes,
+/// their parameters, so both fall back to the declaration.
+fn function_type_name(
+    dwarf: &gimli::Dwarf<DwarfReader<'_>>,
+    unit: &gimli::Unit<DwarfReader<'_>>,
+    entry: &Entry<'_, '_, '_>,
+    depth: usize,
+) -> ParseResult<Option<String>> {
+    let declaration = declaration(unit, entry)?;
+    let return_source = match &declaration {
+        Some(declaration) if entry.attr_value(gimli::DW_AT_type).map_err(dwarf_error)?.is_none() => declaration,
+        _ => entry,
+    };
+    let Some(return_type) = referenced_type_name(dwarf, unit, return_source, depth)? else {
+        return Ok(None);
+    };
+
+    let mut parameters = parameter_types(dwarf, unit, entry, depth)?;
+    if let (Some(declaration), Some(found)) = (&declaration, &parameters) {
+        if found.is_empty() {
+            parameters = parameter_types(dwarf, unit, declaration, depth)?;
+        }
+    }
+    Ok(parameters.map(|parameters| format!("{} ({})", return_type, parameters.join(", "))))
+}
+
+/// Types of `entry`'s `DW_TAG_formal_parameter` children, `None` if any can't be named
+fn parameter_types(
+    dwarf: &gimli::Dwarf<DwarfReader<'_>>,
+    unit: &gimli::Unit<DwarfReader<'_>>,
+    entry: &Entry<'_, '_, '_>,
+    depth: usize,
+) -> ParseResult<Option<Vec<String>>> {
+    let mut tree = unit.entries_tree(Some(entry.offset())).map_err(dwarf_error)?;
+    let mut children = tree.root().map_err(dwarf_error)?.children();
+    let mut parameters = Vec::new();
+    while let Some(child) = children.next().map_err(dwarf_error)? {
+        let child = child.entry();
+        let parameter = match child.tag() {
+            gimli::DW_TAG_formal_parameter => {
+                // Concrete inlined/out-of-line parameters keep their type on the origin
+                let origin = declaration(unit, child)?;
+                referenced_type_name(dwarf, unit, origin.as_ref().unwrap_or(child), depth)?
+            }
+            gimli::DW_TAG_unspecified_parameters => Some("...".to_string()),
+            _ => continue,
+        };
+        let Some(parameter) = parameter else {
+            return Ok(None);
+        };
+        parameters.push(parameter);
+    }
+    Ok(Some(parameters))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{merge_symbols, SectionFlags};
+    use crate::test_util::TestBinary;
+
+    #[rustfmt::skip]
+    const ABBREV: [u8; 51] = [
+        1, 0x11, 1, 0, 0, // compile_unit, children
+        2, 0x2E, 1, 0x03, 0x08, 0x11, 0x01, 0x12, 0x06, 0x49, 0x13, 0, 0, // subprogram: name, low_pc, high_pc data4, type
+        3, 0x34, 0, 0x03, 0x08, 0x02, 0x18, 0x49, 0x13, 0, 0, // variable: name, location exprloc, type
+        4, 0x24, 0, 0x03, 0x08, 0, 0, // base_type: name
+        5, 0x0F, 0, 0x49, 0x13, 0, 0, // pointer_type: type
+        6, 0x05, 0, 0x49, 0x13, 0, 0, // formal_parameter: type
+        0,
+    ];
+
+    /// DWARF 4 unit header for 64-bit addresses around `dies`
+    fn unit(dies: &[u8]) -> Vec<u8> {
+        let mut info = Vec::new();
+        info.extend((7 + dies.len() as u32).to_le_bytes());
+        info.extend(4u16.to_le_bytes());
+        info.extend(0u32.to_le_bytes());
+        info.push(8);
+        info.extend(dies);
+        info
+    }
+
+    /// `int* il2cpp_init(int, int*)` at 0x1000 and `int g_CodeRegistration` at 0x5000
+    fn sample_unit() -> Vec<u8> {
+        // Unit-relative offsets count the 11-byte header
+        let offset = |dies: &Vec<u8>| (11 + dies.len() as u32).to_le_bytes();
+        let mut dies = vec![1];
+        let int = offset(&dies);
+        dies.push(4);
+        dies.extend(b"int\0");
+        let pointer = offset(&dies);
+        dies.push(5);
+        dies.extend(int);
+        dies.push(2);
+        dies.extend(b"il2cpp_init\0");
+        dies.extend(0x1000u64.to_le_bytes());
+        dies.extend(0x40u32.to_le_bytes());
+        dies.extend(pointer);
+        dies.push(6);
+        dies.extend(int);
+        dies.push(6);
+        dies.extend(pointer);
+        dies.push(0);
+        dies.push(3);
+        dies.extend(b"g_CodeRegistration\0");
+        dies.extend([9, 0x03]); // DW_OP_addr
+        dies.extend(0x5000u64.to_le_bytes());
+        dies.extend(int);
+        dies.push(0);
+        unit(&dies)
+    }
+
+    #[test]
+    fn test_dwarf_symbols() {
+        let info = sample_unit();
+        let found = dwarf_symbols_from(|name| match name {
+            ".debug_info" => &info,
+            ".debug_abbrev" => &ABBREV,
+            _ => &[],
+        })
+        .unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].name, "il2cpp_init");
+        assert_eq!((found[0].address, found[0].size), (Address::new(0x1000), Some(0x40)));
+        assert_eq!(found[0].type_name.as_deref(), Some("int* (int, int*)"));
+        assert_eq!((found[1].name.as_str(), found[1].symbol_type), ("g_CodeRegistration", SymbolType::Object));
+        assert_eq!(found[1].type_name.as_deref(), Some("int"));
+
+        // The export already known keeps its entry and gains the size and type
+        let mut symbols = vec![Symbol {
+            name: "il2cpp_init".to_string(),
+            address: Address::new(0x1000),
+            size: None,
+            symbol_type: SymbolType::Function,
+            type_name: None,
+        }];
+        assert_eq!(merge_symbols(&mut symbols, found), 1);
+        assert_eq!(symbols[0].size, Some(0x40));
+        assert_eq!(symbols[0].type_name.as_deref(), Some("int* (int, int*)"));
+        assert_eq!(symbols[1].address, Address::new(0x5000));
+
+        assert!(pdb_symbols(b"not a pdb", Address::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_dwarf_symbols_macho() {
+        let info = sample_unit();
+        let binary = TestBinary::new(BinaryFormat::MachO)
+            .section("__DWARF,__debug_info", 0, &info, SectionFlags::READ)
+            .section("__DWARF,__debug_abbrev", 0, &ABBREV, SectionFlags::READ);
+        let found = dwarf_symbols(&binary).unwrap();
+        let names: Vec<_> = found.iter().map(|symbol| symbol.name.as_str()).collect();
+        assert_eq!(names, ["il2cpp_init", "g_CodeRegistration"]);
+    }
+
+    #[test]
+    fn test_dwarf_symbols_skip_malformed() {
+        let mut abbrev = ABBREV[..ABBREV.len() - 1].to_vec();
+        abbrev.extend([7, 0x2E, 0, 0x03, 0x0E, 0x11, 0x01, 0, 0, 0]); // subprogram: name strp, low_pc
+
+        // A name outside the empty .debug_str, then an undefined abbreviation
+        let mut bad_entry = vec![1, 7];
+        bad_entry.extend(0u32.to_le_bytes());
+        bad_entry.extend(0x2000u64.to_le_bytes());
+        bad_entry.push(0);
+        let mut info = unit(&bad_entry);
+        info.extend(unit(&[1, 99]));
+        info.extend(sample_unit());
+
+        let found = dwarf_symbols_from(|name| match name {
+            ".debug_info" => &info,
+            ".debug_abbrev" => &abbrev,
+            _ => &[],
+        })
+        .unwrap();
+        let names: Vec<_> = found.iter().map(|symbol| symbol.name.as_str()).collect();
+        assert_eq!(names, ["il2cpp_init", "g_CodeRegistration"]);
+    }
+}
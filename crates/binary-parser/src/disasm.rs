@@ -0,0 +1,575 @@
+//! Disassembly of x86, x86-64 and AArch64 code
+//!
+//! x86 decoding is done by iced-x86; AArch64 uses a small built-in decoder
+//! covering branches, PC-relative addressing, moves, arithmetic immediates
+//! and loads/stores, which is what method prologues and call sites are made
+//! of. Anything else is shown as `.word`.
+
+use crate::common::BinaryFile;
+use crate::error::{ParseError, ParseResult};
+use endfield_core::{Address, Architecture};
+use iced_x86::{Decoder, DecoderOptions, FlowControl, Formatter, IntelFormatter, OpKind};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// How an instruction affects control flow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowKind {
+    /// Falls through to the next instruction
+    Next,
+    /// Direct call
+    Call,
+    /// Call through a register or memory
+    IndirectCall,
+    /// Unconditional direct jump
+    Jump,
+    /// Conditional direct jump
+    ConditionalJump,
+    /// Jump through a register or memory
+    IndirectJump,
+    /// Return from the function
+    Return,
+    /// Trap, breakpoint or undefined instruction
+    Interrupt,
+}
+
+impl FlowKind {
+    /// Check whether the instruction ends a basic block
+    pub fn ends_block(self) -> bool {
+        !matches!(self, FlowKind::Next | FlowKind::Call | FlowKind::IndirectCall)
+    }
+}
+
+/// Decoded instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    /// Instruction address
+    pub address: Address,
+    /// Encoded bytes
+    pub bytes: Vec<u8>,
+    /// Mnemonic, e.g. `mov`
+    pub mnemonic: String,
+    /// Formatted operands
+    pub operands: String,
+    /// Control flow kind
+    pub flow: FlowKind,
+    /// Branch target or PC-relative memory reference
+    pub target: Option<Address>,
+    /// Symbol or method name for `target`
+    pub target_name: Option<String>,
+}
+
+impl Instruction {
+    /// Instruction length in bytes
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Check whether the instruction has no bytes
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Address of the following instruction
+    pub fn next_address(&self) -> Address {
+        self.address.offset(self.bytes.len() as i64)
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.mnemonic)?;
+        if !self.operands.is_empty() {
+            write!(f, " {}", self.operands)?;
+        }
+        if let Some(name) = &self.target_name {
+            write!(f, " ; {}", name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Format instructions as a plain-text listing (`address  bytes  text`)
+pub fn listing(instructions: &[Instruction]) -> String {
+    let mut out = String::new();
+    for instruction in instructions {
+        let bytes: Vec<String> = instruction.bytes.iter().map(|b| format!("{:02X}", b)).collect();
+        out.push_str(&format!("{:016X}  {:<30} {}\n", instruction.address.as_u64(), bytes.join(" "), instruction));
+    }
+    out
+}
+
+/// Disassembler over a binary with name resolution for branch targets
+pub struct Disassembler<'a, B: BinaryFile + ?Sized> {
+    binary: &'a B,
+    /// start -> (name, size)
+    names: BTreeMap<u64, (String, Option<u64>)>,
+}
+
+impl<'a, B: BinaryFile + ?Sized> Disassembler<'a, B> {
+    /// Create a disassembler resolving targets against the binary's symbols
+    pub fn new(binary: &'a B) -> Self {
+        let names = binary
+            .symbols()
+            .iter()
+            .filter(|symbol| !symbol.name.is_empty() && symbol.address.as_u64() != 0)
+            .map(|symbol| (symbol.address.as_u64(), (symbol.name.clone(), symbol.size)))
+            .collect();
+        Self { binary, names }
+    }
+
+    /// Add names such as IL2CPP methods from a dump, overriding symbols
+    pub fn with_names(mut self, names: impl IntoIterator<Item = (Address, String)>) -> Self {
+        for (address, name) in names {
+            if address.as_u64() != 0 {
+                self.names.insert(address.as_u64(), (name, None));
+            }
+        }
+        self
+    }
+
+    /// Resolve an address to `name` or `name+0x10`
+    pub fn resolve(&self, address: Address) -> Option<String> {
+        let (&start, (name, size)) = self.names.range(..=address.as_u64()).next_back()?;
+        let delta = address.as_u64() - start;
+        if delta == 0 {
+            Some(name.clone())
+        } else if size.is_some_and(|size| delta < size) {
+            Some(format!("{}+0x{:X}", name, delta))
+        } else {
+            None
+        }
+    }
+
+    /// Decode up to `max_instructions` at `va`, stopping after a return
+    pub fn disassemble(&self, va: Address, max_instructions: usize) -> ParseResult<Vec<Instruction>> {
+        self.decode(va, max_instructions, |flow| flow == FlowKind::Return)
+    }
+
+    /// Decode one basic block at `va`, stopping after any jump or return
+    pub fn disassemble_block(&self, va: Address, max_instructions: usize) -> ParseResult<Vec<Instruction>> {
+        self.decode(va, max_instructions, FlowKind::ends_block)
+    }
+
+    fn decode(&self, va: Address, max_instructions: usize, stop: fn(FlowKind) -> bool) -> ParseResult<Vec<Instruction>> {
+        let code = self.code_at(va)?;
+        let mut instructions = match self.binary.architecture() {
+            Architecture::X64 => decode_x86(code, va, 64, max_instructions, stop),
+            Architecture::X86 => decode_x86(code, va, 32, max_instructions, stop),
+            Architecture::Arm64 => decode_arm64(code, va, max_instructions, stop),
+            arch => return Err(ParseError::UnsupportedArchitecture(format!("{:?}", arch))),
+        };
+
+        for instruction in &mut instructions {
+            instruction.target_name = instruction.target.and_then(|target| self.resolve(target));
+        }
+        Ok(instructions)
+    }
+
+    /// Bytes from `va` to the end of its section's raw data
+    fn code_at(&self, va: Address) -> ParseResult<&'a [u8]> {
+        let binary: &'a B = self.binary;
+        binary
+            .sections()
+            .iter()
+            .filter(|section| {
+                let start = section.virtual_address.as_u64();
+                va.as_u64() >= start && va.as_u64() < start + section.virtual_size.max(section.raw_size)
+            })
+            .find_map(|section| {
+                let delta = (va.as_u64() - section.virtual_address.as_u64()) as usize;
+                binary.section_data(section)?.get(delta..).filter(|code| !code.is_empty())
+            })
+            .ok_or(ParseError::AddressOutOfBounds(va.as_u64()))
+    }
+}
+
+fn decode_x86(
+    code: &[u8],
+    va: Address,
+    bitness: u32,
+    max_instructions: usize,
+    stop: fn(FlowKind) -> bool,
+) -> Vec<Instruction> {
+    let mut decoder = Decoder::with_ip(bitness, code, va.as_u64(), DecoderOptions::NONE);
+    let mut formatter = IntelFormatter::new();
+    let mut instructions = Vec::new();
+
+    while decoder.can_decode() && instructions.len() < max_instructions {
+        let start = decoder.position();
+        let decoded = decoder.decode();
+        let bytes = code[start..decoder.position()].to_vec();
+
+        if decoded.is_invalid() {
+            instructions.push(Instruction {
+                address: Address::new(decoded.ip()),
+                bytes,
+                mnemonic: "(bad)".to_string(),
+                operands: String::new(),
+                flow: FlowKind::Interrupt,
+                target: None,
+                target_name: None,
+            });
+            break;
+        }
+
+        let mut mnemonic = String::new();
+        formatter.format_mnemonic(&decoded, &mut mnemonic);
+        let mut operands = String::new();
+        formatter.format_all_operands(&decoded, &mut operands);
+
+        let flow = match decoded.flow_control() {
+            FlowControl::Next | FlowControl::XbeginXabortXend => FlowKind::Next,
+            FlowControl::Call => FlowKind::Call,
+            FlowControl::IndirectCall => FlowKind::IndirectCall,
+            FlowControl::UnconditionalBranch => FlowKind::Jump,
+            FlowControl::ConditionalBranch => FlowKind::ConditionalJump,
+            FlowControl::IndirectBranch => FlowKind::IndirectJump,
+            FlowControl::Return => FlowKind::Return,
+            FlowControl::Interrupt | FlowControl::Exception => FlowKind::Interrupt,
+        };
+
+        let is_near_branch = (0..decoded.op_count()).any(|i| {
+            matches!(decoded.op_kind(i), OpKind::NearBranch16 | OpKind::NearBranch32 | OpKind::NearBranch64)
+        });
+        let target = if is_near_branch {
+            Some(Address::new(decoded.near_branch_target()))
+        } else if decoded.is_ip_rel_memory_operand() {
+            Some(Address::new(decoded.ip_rel_memory_address()))
+        } else {
+            None
+        };
+
+        instructions.push(Instruction {
+            address: Address::new(decoded.ip()),
+            bytes,
+            mnemonic,
+            operands,
+            flow,
+            target,
+            target_name: None,
+        });
+        if stop(flow) {
+            break;
+        }
+    }
+    instructions
+}
+
+const CONDITIONS: [&str; 16] = [
+    "eq", "ne", "hs", "lo", "mi", "pl", "vs", "vc", "hi", "ls", "ge", "lt", "gt", "le", "al", "nv",
+];
+
+/// General purpose register name; register 31 is `sp` or the zero register
+fn reg(n: u32, is_64: bool, sp: bool) -> String {
+    match (n, is_64, sp) {
+        (31, true, true) => "sp".to_string(),
+        (31, false, true) => "wsp".to_string(),
+        (31, true, false) => "xzr".to_string(),
+        (31, false, false) => "wzr".to_string(),
+        (n, true, _) => format!("x{}", n),
+        (n, false, _) => format!("w{}", n),
+    }
+}
+
+pub(crate) fn sign_extend(value: u32, bits: u32) -> i64 {
+    let shift = 64 - bits;
+    ((value as i64) << shift) >> shift
+}
+
+fn hex(value: i64) -> String {
+    if value < 0 {
+        format!("#-0x{:X}", -value)
+    } else {
+        format!("#0x{:X}", value)
+    }
+}
+
+fn decode_arm64(code: &[u8], va: Address, max_instructions: usize, stop: fn(FlowKind) -> bool) -> Vec<Instruction> {
+    // Addresses held in registers after ADRP / ADD, for resolving pairs
+    let mut known: [Option<u64>; 32] = [None; 32];
+    let mut instructions = Vec::new();
+
+    for (index, word) in code.chunks_exact(4).take(max_instructions).enumerate() {
+        let pc = va.as_u64() + index as u64 * 4;
+        let w = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+        let (mnemonic, operands, flow, target, written) = decode_arm64_word(w, pc, &known);
+
+        if let Some((rd, address)) = written {
+            known[rd as usize] = address;
+        }
+        if matches!(flow, FlowKind::Call | FlowKind::IndirectCall) {
+            // The callee may clobber x0-x18 and the link register
+            known[..=18].fill(None);
+            known[30] = None;
+        }
+
+        instructions.push(Instruction {
+            address: Address::new(pc),
+            bytes: word.to_vec(),
+            mnemonic,
+            operands,
+            flow,
+            target: target.map(Address::new),
+            target_name: None,
+        });
+        if stop(flow) {
+            break;
+        }
+    }
+    instructions
+}
+
+/// Register written by an instruction and the address it now holds
+type Written = Option<(u32, Option<u64>)>;
+
+fn decode_arm64_word(
+    w: u32,
+    pc: u64,
+    known: &[Option<u64>; 32],
+) -> (String, String, FlowKind, Option<u64>, Written) {
+    let rd = w & 0x1F;
+    let rn = (w >> 5) & 0x1F;
+    let is_64 = w >> 31 == 1;
+    let op = |m: &str, o: String, flow: FlowKind| (m.to_string(), o, flow, None, None);
+
+    if w == 0xD503201F {
+        return op("nop", String::new(), FlowKind::Next);
+    }
+    if w & 0xFFFFFC1F == 0xD65F0000 {
+        let operands = if rn == 30 { String::new() } else { reg(rn, true, false) };
+        return op("ret", operands, FlowKind::Return);
+    }
+    if w & 0xFFFFFC1F == 0xD61F0000 {
+        return op("br", reg(rn, true, false), FlowKind::IndirectJump);
+    }
+    if w & 0xFFFFFC1F == 0xD63F0000 {
+        return op("blr", reg(rn, true, false), FlowKind::IndirectCall);
+    }
+    if w & 0xFFE0001F == 0xD4200000 {
+        return op("brk", hex(((w >> 5) & 0xFFFF) as i64), FlowKind::Interrupt);
+    }
+
+    // B / BL
+    if w & 0x7C000000 == 0x14000000 {
+        let target = pc.wrapping_add((sign_extend(w & 0x03FF_FFFF, 26) * 4) as u64);
+        let (mnemonic, flow) = if is_64 { ("bl", FlowKind::Call) } else { ("b", FlowKind::Jump) };
+        return (mnemonic.to_string(), format!("0x{:X}", target), flow, Some(target), None);
+    }
+    // B.cond
+    if w & 0xFF000010 == 0x54000000 {
+        let target = pc.wrapping_add((sign_extend((w >> 5) & 0x7FFFF, 19) * 4) as u64);
+        let mnemonic = format!("b.{}", CONDITIONS[(w & 0xF) as usize]);
+        return (mnemonic, format!("0x{:X}", target), FlowKind::ConditionalJump, Some(target), None);
+    }
+    // CBZ / CBNZ
+    if w & 0x7E000000 == 0x34000000 {
+        let target = pc.wrapping_add((sign_extend((w >> 5) & 0x7FFFF, 19) * 4) as u64);
+        let mnemonic = if (w >> 24) & 1 == 1 { "cbnz" } else { "cbz" };
+        let operands = format!("{}, 0x{:X}", reg(rd, is_64, false), target);
+        return (mnemonic.to_string(), operands, FlowKind::ConditionalJump, Some(target), None);
+    }
+    // TBZ / TBNZ
+    if w & 0x7E000000 == 0x36000000 {
+        let target = pc.wrapping_add((sign_extend((w >> 5) & 0x3FFF, 14) * 4) as u64);
+        let bit = ((w >> 31) << 5) | ((w >> 19) & 0x1F);
+        let mnemonic = if (w >> 24) & 1 == 1 { "tbnz" } else { "tbz" };
+        let operands = format!("{}, #{}, 0x{:X}", reg(rd, bit >= 32, false), bit, target);
+        return (mnemonic.to_string(), operands, FlowKind::ConditionalJump, Some(target), None);
+    }
+    // ADR / ADRP
+    if w & 0x1F000000 == 0x10000000 {
+        let imm = sign_extend((((w >> 5) & 0x7FFFF) << 2) | ((w >> 29) & 3), 21);
+        let (mnemonic, target) = if is_64 {
+            ("adrp", (pc & !0xFFF).wrapping_add((imm << 12) as u64))
+        } else {
+            ("adr", pc.wrapping_add(imm as u64))
+        };
+        let operands = format!("{}, 0x{:X}", reg(rd, true, false), target);
+        return (mnemonic.to_string(), operands, FlowKind::Next, Some(target), Some((rd, Some(target))));
+    }
+    // ADD / SUB (immediate)
+    if (w >> 23) & 0x3F == 0x22 {
+        let is_sub = (w >> 30) & 1 == 1;
+        let sets_flags = (w >> 29) & 1 == 1;
+        let imm = (((w >> 10) & 0xFFF) as u64) << if (w >> 22) & 1 == 1 { 12 } else { 0 };
+        let target = known[rn as usize].filter(|_| !is_sub).map(|base| base.wrapping_add(imm));
+        let dst = reg(rd, is_64, !sets_flags);
+        let src = reg(rn, is_64, true);
+        let (mnemonic, operands) = match (is_sub, sets_flags) {
+            (false, false) if imm == 0 && (rd == 31 || rn == 31) => ("mov", format!("{}, {}", dst, src)),
+            (true, true) if rd == 31 => ("cmp", format!("{}, {}", src, hex(imm as i64))),
+            (false, true) if rd == 31 => ("cmn", format!("{}, {}", src, hex(imm as i64))),
+            (false, false) => ("add", format!("{}, {}, {}", dst, src, hex(imm as i64))),
+            (false, true) => ("adds", format!("{}, {}, {}", dst, src, hex(imm as i64))),
+            (true, false) => ("sub", format!("{}, {}, {}", dst, src, hex(imm as i64))),
+            (true, true) => ("subs", format!("{}, {}, {}", dst, src, hex(imm as i64))),
+        };
+        return (mnemonic.to_string(), operands, FlowKind::Next, target, Some((rd, target)));
+    }
+    // MOVN / MOVZ / MOVK
+    if (w >> 23) & 0x3F == 0x25 {
+        let shift = ((w >> 21) & 3) * 16;
+        let imm = ((w >> 5) & 0xFFFF) as i64;
+        let mnemonic = match (w >> 29) & 3 {
+            0 => "movn",
+            2 => "movz",
+            3 => "movk",
+            _ => return op(".word", format!("0x{:08X}", w), FlowKind::Next),
+        };
+        let mut operands = format!("{}, {}", reg(rd, is_64, false), hex(imm));
+        if shift != 0 {
+            operands.push_str(&format!(", lsl #{}", shift));
+        }
+        return (mnemonic.to_string(), operands, FlowKind::Next, None, Some((rd, None)));
+    }
+    // MOV (register), alias of ORR Rd, ZR, Rm
+    if w & 0x7FE0FFE0 == 0x2A0003E0 {
+        let operands = format!("{}, {}", reg(rd, is_64, false), reg((w >> 16) & 0x1F, is_64, false));
+        return ("mov".to_string(), operands, FlowKind::Next, None, Some((rd, None)));
+    }
+    // LDR (literal)
+    if w & 0x3B000000 == 0x18000000 && (w >> 26) & 1 == 0 {
+        let target = pc.wrapping_add((sign_extend((w >> 5) & 0x7FFFF, 19) * 4) as u64);
+        let (mnemonic, wide) = match w >> 30 {
+            0 => ("ldr", false),
+            1 => ("ldr", true),
+            2 => ("ldrsw", true),
+            _ => return op(".word", format!("0x{:08X}", w), FlowKind::Next),
+        };
+        let operands = format!("{}, 0x{:X}", reg(rd, wide, false), target);
+        return (mnemonic.to_string(), operands, FlowKind::Next, Some(target), Some((rd, None)));
+    }
+    // LDR / STR (unsigned immediate)
+    if w & 0x3B000000 == 0x39000000 && (w >> 26) & 1 == 0 {
+        let size = w >> 30;
+        let opc = (w >> 22) & 3;
+        let offset = (((w >> 10) & 0xFFF) as u64) << size;
+        let (mnemonic, wide) = match (size, opc) {
+            (0, 0) => ("strb", false),
+            (0, 1) => ("ldrb", false),
+            (0, o) => ("ldrsb", o == 2),
+            (1, 0) => ("strh", false),
+            (1, 1) => ("ldrh", false),
+            (1, o) => ("ldrsh", o == 2),
+            (2, 0) => ("str", false),
+            (2, 1) => ("ldr", false),
+            (2, 2) => ("ldrsw", true),
+            (3, 0) => ("str", true),
+            (3, 1) => ("ldr", true),
+            _ => return op(".word", format!("0x{:08X}", w), FlowKind::Next),
+        };
+        let target = known[rn as usize].map(|base| base.wrapping_add(offset));
+        let address = if offset == 0 {
+            format!("[{}]", reg(rn, true, true))
+        } else {
+            format!("[{}, {}]", reg(rn, true, true), hex(offset as i64))
+        };
+        let operands = format!("{}, {}", reg(rd, wide, false), address);
+        let written = (opc != 0).then_some((rd, None));
+        return (mnemonic.to_string(), operands, FlowKind::Next, target, written);
+    }
+    // LDP / STP
+    if w & 0x3A000000 == 0x28000000 && (w >> 26) & 1 == 0 && (w >> 30) & 1 == 0 {
+        let wide = w >> 31 == 1;
+        let is_load = (w >> 22) & 1 == 1;
+        let offset = sign_extend((w >> 15) & 0x7F, 7) * if wide { 8 } else { 4 };
+        let base = reg(rn, true, true);
+        let address = match (w >> 23) & 3 {
+            1 => format!("[{}], {}", base, hex(offset)),
+            3 => format!("[{}, {}]!", base, hex(offset)),
+            _ if offset == 0 => format!("[{}]", base),
+            _ => format!("[{}, {}]", base, hex(offset)),
+        };
+        let rt2 = (w >> 10) & 0x1F;
+        let operands = format!("{}, {}, {}", reg(rd, wide, false), reg(rt2, wide, false), address);
+        let mnemonic = if is_load { "ldp" } else { "stp" };
+        let written = is_load.then_some((rd, None));
+        return (mnemonic.to_string(), operands, FlowKind::Next, None, written);
+    }
+
+    // Most other data-processing instructions write Rd
+    (".word".to_string(), format!("0x{:08X}", w), FlowKind::Next, None, Some((rd, None)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_x64_rip_relative_and_return() {
+        // lea rax, [rip+0x10]; call +0; ret; nop
+        let code = [0x48, 0x8D, 0x05, 0x10, 0x00, 0x00, 0x00, 0xE8, 0x00, 0x00, 0x00, 0x00, 0xC3, 0x90];
+        let instructions = decode_x86(&code, Address::new(0x1000), 64, 16, FlowKind::ends_block);
+
+        assert_eq!(instructions.len(), 3);
+        assert_eq!(instructions[0].mnemonic, "lea");
+        assert_eq!(instructions[0].target, Some(Address::new(0x1017)));
+        assert_eq!(instructions[1].flow, FlowKind::Call);
+        assert_eq!(instructions[1].target, Some(Address::new(0x100C)));
+        assert_eq!(instructions[2].flow, FlowKind::Return);
+    }
+
+    #[test]
+    fn test_arm64_adrp_pairs() {
+        let words: [u32; 6] = [
+            0xA9BF7BFD, // stp x29, x30, [sp, #-0x10]!
+            0x90000008, // adrp x8, 0x4000
+            0x91004108, // add x8, x8, #0x10
+            0xF9400909, // ldr x9, [x8, #0x10]
+            0x97FFFFFC, // bl 0x4000
+            0xD65F03C0, // ret
+        ];
+        let code: Vec<u8> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+        let instructions = decode_arm64(&code, Address::new(0x4000), 16, FlowKind::ends_block);
+
+        let text: Vec<String> = instructions.iter().map(|i| i.to_string()).collect();
+        assert_eq!(
+            text,
+            [
+                "stp x29, x30, [sp, #-0x10]!",
+                "adrp x8, 0x4000",
+                "add x8, x8, #0x10",
+                "ldr x9, [x8, #0x10]",
+                "bl 0x4000",
+                "ret",
+            ]
+        );
+        assert_eq!(instructions[2].target, Some(Address::new(0x4010)));
+        assert_eq!(instructions[3].target, Some(Address::new(0x4020)));
+        assert_eq!(instructions[4].flow, FlowKind::Call);
+        assert_eq!(instructions[4].target, Some(Address::new(0x4000)));
+    }
+
+    #[test]
+    fn test_arm64_clobbered_registers() {
+        let words: [u32; 8] = [
+            0x90000008, // adrp x8, 0x4000
+            0x90000013, // adrp x19, 0x4000
+            0xB2400108, // orr x8, x8, #1 (not decoded)
+            0xF9400909, // ldr x9, [x8, #0x10]
+            0x90000008, // adrp x8, 0x4000
+            0x97FFFFFB, // bl 0x4000
+            0xF9400909, // ldr x9, [x8, #0x10]
+            0xF9400A69, // ldr x9, [x19, #0x10] (callee-saved)
+        ];
+        let code: Vec<u8> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+        let instructions = decode_arm64(&code, Address::new(0x4000), 16, FlowKind::ends_block);
+
+        assert_eq!(instructions[2].mnemonic, ".word");
+        assert_eq!(instructions[3].target, None);
+        assert_eq!(instructions[6].target, None);
+        assert_eq!(instructions[7].target, Some(Address::new(0x4010)));
+    }
+
+    #[test]
+    fn test_arm64_adrp_below_zero() {
+        let words: [u32; 2] = [
+            0xF0FFFFE8, // adrp x8, -0x1000
+            0x91400508, // add x8, x8, #1, lsl #12
+        ];
+        let code: Vec<u8> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+        let instructions = decode_arm64(&code, Address::ZERO, 16, FlowKind::ends_block);
+        assert_eq!(instructions[1].target, Some(Address::ZERO));
+    }
+}
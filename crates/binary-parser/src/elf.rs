@@ -1,7 +1,11 @@
 //! ELF (Executable and Linkable Format) parser for Linux/Android binaries
 
-use crate::common::{BinaryFile, BinaryReader, Section, SectionFlags, Symbol, SymbolType};
+use crate::common::{
+    build_functions, checked_slice, merge_symbols, or_empty, BinaryFile, BinaryReader, Function, ImageLayout, Section,
+    SectionFlags, Symbol, SymbolType,
+};
 use crate::error::{ParseError, ParseResult};
+use crate::image::ImageData;
 use endfield_core::{Address, Architecture, BinaryFormat, Platform};
 
 /// ELF magic
@@ -32,8 +36,58 @@ const SHF_EXECINSTR: u64 = 0x4;
 /// Section types
 const SHT_SYMTAB: u32 = 2;
 const SHT_STRTAB: u32 = 3;
+const SHT_NOBITS: u32 = 8;
 const SHT_DYNSYM: u32 = 11;
 
+/// Program header types
+const PT_LOAD: u32 = 1;
+const PT_DYNAMIC: u32 = 2;
+const PT_GNU_EH_FRAME: u32 = 0x6474_E550;
+
+/// DWARF exception-header pointer encodings
+const DW_EH_PE_OMIT: u8 = 0xFF;
+const DW_EH_PE_PCREL: u8 = 0x10;
+const DW_EH_PE_DATAREL: u8 = 0x30;
+
+/// Dynamic section tags
+const DT_NULL: u64 = 0;
+const DT_NEEDED: u64 = 1;
+const DT_HASH: u64 = 4;
+const DT_STRTAB: u64 = 5;
+const DT_SYMTAB: u64 = 6;
+const DT_RELA: u64 = 7;
+const DT_RELASZ: u64 = 8;
+const DT_RELAENT: u64 = 9;
+const DT_STRSZ: u64 = 10;
+const DT_SYMENT: u64 = 11;
+const DT_PLTRELSZ: u64 = 2;
+const DT_REL: u64 = 17;
+const DT_RELSZ: u64 = 18;
+const DT_RELENT: u64 = 19;
+const DT_PLTREL: u64 = 20;
+const DT_JMPREL: u64 = 23;
+const DT_RELRSZ: u64 = 35;
+const DT_RELR: u64 = 36;
+const DT_RELRENT: u64 = 37;
+const DT_GNU_HASH: u64 = 0x6FFF_FEF5;
+
+/// Relative relocation types
+const R_386_RELATIVE: u32 = 8;
+const R_X86_64_RELATIVE: u32 = 8;
+const R_ARM_RELATIVE: u32 = 23;
+const R_AARCH64_RELATIVE: u32 = 1027;
+
+/// PLT slot relocation types
+const R_386_JMP_SLOT: u32 = 7;
+const R_X86_64_JUMP_SLOT: u32 = 7;
+const R_ARM_JUMP_SLOT: u32 = 22;
+const R_AARCH64_JUMP_SLOT: u32 = 1026;
+
+/// Segment flags
+const PF_X: u32 = 0x1;
+const PF_W: u32 = 0x2;
+const PF_R: u32 = 0x4;
+
 /// Symbol types
 const STT_NOTYPE: u8 = 0;
 const STT_OBJECT: u8 = 1;
@@ -41,20 +95,68 @@ const STT_FUNC: u8 = 2;
 const STT_SECTION: u8 = 3;
 const STT_FILE: u8 = 4;
 
+/// Program header (segment) entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramHeader {
+    pub p_type: u32,
+    pub p_flags: u32,
+    pub p_offset: u64,
+    pub p_vaddr: u64,
+    pub p_filesz: u64,
+    pub p_memsz: u64,
+}
+
+/// Entry of the `PT_DYNAMIC` segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DynamicEntry {
+    pub tag: u64,
+    pub value: u64,
+}
+
+/// Section header table location from the ELF header
+#[derive(Debug, Clone, Copy)]
+struct SectionTable {
+    offset: u64,
+    entry_size: u16,
+    count: u16,
+    names_index: u16,
+}
+
 /// Parsed ELF file
 pub struct ElfFile {
-    data: Vec<u8>,
+    data: ImageData,
     architecture: Architecture,
     is_64bit: bool,
     little_endian: bool,
     entry_point: Address,
     sections: Vec<Section>,
     symbols: Vec<Symbol>,
+    program_headers: Vec<ProgramHeader>,
+    dynamic: Vec<DynamicEntry>,
+    imported_functions: Vec<String>,
+    functions: Vec<Function>,
+    relocations_applied: usize,
+    layout: ImageLayout,
 }
 
 impl ElfFile {
-    /// Parse an ELF file from raw bytes
+    /// Parse an ELF file from raw bytes, detecting memory dumps
     pub fn parse(data: &[u8]) -> ParseResult<Self> {
+        Self::parse_with_layout(data, None)
+    }
+
+    /// Parse an ELF file with an explicit layout, or detect it when `None`
+    ///
+    /// Section headers are not loaded into memory, so mapped images get one
+    /// section per `PT_LOAD` segment, with raw offsets relative to the lowest
+    /// segment address.
+    pub fn parse_with_layout(data: &[u8], layout: Option<ImageLayout>) -> ParseResult<Self> {
+        Self::from_data(data.into(), layout)
+    }
+
+    /// Parse owned or memory-mapped bytes in place, without copying them
+    pub fn from_data(image: ImageData, layout: Option<ImageLayout>) -> ParseResult<Self> {
+        let data: &[u8] = &image;
         if data.len() < 16 {
             return Err(ParseError::truncated(16, data.len()));
         }
@@ -96,7 +198,7 @@ impl ElfFile {
             _ => Architecture::Unknown,
         };
 
-        let (entry_point, _ph_offset, sh_offset, _ph_entsize, _ph_num, sh_entsize, sh_num, sh_strndx) =
+        let (entry_point, ph_offset, sh_offset, ph_entsize, ph_num, sh_entsize, sh_num, sh_strndx) =
             if is_64bit {
                 let e_entry = reader.read_u64()?;
                 let e_phoff = reader.read_u64()?;
@@ -123,15 +225,49 @@ impl ElfFile {
                 (e_entry, e_phoff, e_shoff, e_phentsize, e_phnum, e_shentsize, e_shnum, e_shstrndx)
             };
 
+        let program_headers =
+            Self::parse_program_headers(&mut reader, ph_offset, ph_entsize, ph_num, is_64bit)?;
+
+        let layout = layout.unwrap_or_else(|| {
+            let table = SectionTable { offset: sh_offset, entry_size: sh_entsize, count: sh_num, names_index: sh_strndx };
+            Self::detect_layout(data, &program_headers, table, is_64bit, little_endian)
+        });
+        if layout == ImageLayout::Mapped {
+            let sections = Self::segment_sections(data, &program_headers, layout);
+            return Self {
+                data: image,
+                architecture,
+                is_64bit,
+                little_endian,
+                entry_point: Address::new(entry_point),
+                sections,
+                symbols: Vec::new(),
+                program_headers,
+                dynamic: Vec::new(),
+                imported_functions: Vec::new(),
+                functions: Vec::new(),
+                relocations_applied: 0,
+                layout,
+            }
+            .load_dynamic()
+            .load_functions();
+        }
+
         // Parse section headers
         let mut raw_sections = Vec::new();
         let mut strtab_offset = 0u64;
         let mut strtab_size = 0u64;
 
         if sh_num > 0 && sh_offset > 0 {
+            let min_entsize = if is_64bit { 64 } else { 40 };
+            if sh_entsize < min_entsize {
+                return Err(ParseError::invalid_header(format!("Section header entry size {}", sh_entsize)));
+            }
             for i in 0..sh_num as usize {
-                let offset = sh_offset as usize + i * sh_entsize as usize;
-                reader.set_offset(offset);
+                let offset = (i as u64 * sh_entsize as u64)
+                    .checked_add(sh_offset)
+                    .ok_or_else(|| ParseError::overflow("section header offset"))?;
+                reader.set_offset(usize::try_from(offset).map_err(|_| ParseError::overflow("section header offset"))?);
 
                 let (sh_name, sh_type, sh_flags, sh_addr, sh_offset, sh_size) = if is_64bit {
                     let sh_name = reader.read_u32()?;
@@ -165,12 +301,9 @@ impl ElfFile {
         let mut sections = Vec::with_capacity(raw_sections.len());
         for (sh_name, sh_type, sh_flags, sh_addr, sh_offset, sh_size) in raw_sections.iter() {
             let name = if strtab_size > 0 && (*sh_name as u64) < strtab_size {
-                let name_offset = strtab_offset as usize + *sh_name as usize;
-                let mut end = name_offset;
-                while end < data.len() && data[end] != 0 {
-                    end += 1;
-                }
-                String::from_utf8_lossy(&data[name_offset..end]).to_string()
+                let name_offset = strtab_offset.saturating_add(*sh_name as u64);
+                let max_len = (strtab_size - *sh_name as u64).min(usize::MAX as u64) as usize;
+                reader.string_at(name_offset, max_len).unwrap_or_default()
             } else {
                 String::new()
             };
@@ -186,30 +319,737 @@ impl ElfFile {
                 flags |= SectionFlags::EXECUTE;
             }
 
+            // .bss and friends occupy no file bytes; sh_offset just marks the spot
+            let raw_size = if *sh_type == SHT_NOBITS { 0 } else { *sh_size };
             sections.push(Section {
                 name,
                 virtual_address: Address::new(*sh_addr),
                 virtual_size: *sh_size,
                 raw_offset: *sh_offset,
-                raw_size: *sh_size,
+                raw_size,
                 characteristics: flags,
             });
         }
 
+        // Section headers are optional at runtime and often stripped
+        if sections.is_empty() {
+            sections = Self::segment_sections(data, &program_headers, layout);
+        }
+
         // Parse symbols
         let symbols = Self::parse_symbols(data, &raw_sections, is_64bit, little_endian)?;
 
-        Ok(Self {
-            data: data.to_vec(),
+        Self {
+            data: image,
             architecture,
             is_64bit,
             little_endian,
             entry_point: Address::new(entry_point),
             sections,
             symbols,
+            program_headers,
+            dynamic: Vec::new(),
+            imported_functions: Vec::new(),
+            functions: Vec::new(),
+            relocations_applied: 0,
+            layout,
+        }
+        .load_dynamic()
+        .load_functions()
+    }
+
+    /// Get the `PT_DYNAMIC` entries
+    pub fn dynamic_entries(&self) -> &[DynamicEntry] {
+        &self.dynamic
+    }
+
+    /// Number of relative relocations applied to the data
+    pub fn relocations_applied(&self) -> usize {
+        self.relocations_applied
+    }
+
+    /// Value of the first dynamic entry with `tag`
+    fn dynamic_value(&self, tag: u64) -> Option<u64> {
+        self.dynamic.iter().find(|entry| entry.tag == tag).map(|entry| entry.value)
+    }
+
+    /// Parse `PT_DYNAMIC`, recover symbols from it when no symbol table
+    /// section was found, and apply relative relocations
+    ///
+    /// A malformed table only drops what it describes: relocations are
+    /// collected before any is applied, so a bad one leaves the data untouched.
+    fn load_dynamic(mut self) -> Self {
+        self.dynamic = or_empty("dynamic segment", self.parse_dynamic());
+        if self.dynamic.is_empty() {
+            return self;
+        }
+
+        let (symbols, imported_functions) = or_empty("dynamic symbol table", self.parse_dynamic_symbols());
+        if self.symbols.is_empty() {
+            self.symbols = symbols;
+        }
+        self.imported_functions = imported_functions;
+        let slots = or_empty("PLT relocations", self.parse_jump_slots());
+        merge_symbols(&mut self.symbols, slots);
+        self.relocations_applied = or_empty("dynamic relocations", self.apply_relative_relocations());
+
+        self
+    }
+
+    /// Recover function extents from the `.eh_frame` FDEs
+    fn load_functions(mut self) -> ParseResult<Self> {
+        let entries = match self.eh_frame_address() {
+            Some(eh_frame) => self.parse_eh_frame(eh_frame),
+            None => Vec::new(),
+        };
+        self.functions = build_functions(entries, &self.sections);
+        Ok(self)
+    }
+
+    /// `.eh_frame` address, from its section or through `PT_GNU_EH_FRAME`
+    fn eh_frame_address(&self) -> Option<u64> {
+        if let Some(section) = self.find_section(".eh_frame") {
+            return Some(section.virtual_address.as_u64());
+        }
+
+        let header = match self.find_section(".eh_frame_hdr") {
+            Some(section) => section.virtual_address.as_u64(),
+            None => self.program_headers.iter().find(|ph| ph.p_type == PT_GNU_EH_FRAME)?.p_vaddr,
+        };
+        let mut reader = self.reader_at_va(header).ok()?;
+        let _version = reader.read_u8().ok()?;
+        let encoding = reader.read_u8().ok()?;
+        reader.skip(2).ok()?; // FDE count and table encodings
+        self.read_encoded(&mut reader, encoding, header.wrapping_add(4), header).ok()
+    }
+
+    /// Walk `.eh_frame` records, returning `(pc_begin, Some(pc_end))` per FDE
+    ///
+    /// Stops at the zero terminator or the first malformed record.
+    fn parse_eh_frame(&self, eh_frame: u64) -> Vec<(u64, Option<u64>)> {
+        let Ok(mut reader) = self.reader_at_va(eh_frame) else {
+            return Vec::new();
+        };
+        let base = reader.offset();
+        let va_of = |reader: &BinaryReader| eh_frame + (reader.offset() - base) as u64;
+
+        // FDE pointer encoding per CIE offset
+        let mut cie_encodings = std::collections::HashMap::new();
+        let mut entries = Vec::new();
+
+        while let Ok(length) = reader.read_u32() {
+            let (length, id_size) = match length {
+                0 => break,
+                0xFFFF_FFFF => match reader.read_u64() {
+                    Ok(length) => (length as usize, 8),
+                    Err(_) => break,
+                },
+                length => (length as usize, 4),
+            };
+            let id_offset = reader.offset();
+            let Some(end) = id_offset.checked_add(length) else {
+                break;
+            };
+            let id = if id_size == 8 { reader.read_u64() } else { reader.read_u32().map(|id| id as u64) };
+            let Ok(id) = id else {
+                break;
+            };
+
+            if id == 0 {
+                if let Ok(encoding) = self.parse_cie(&mut reader, va_of) {
+                    cie_encodings.insert(id_offset, encoding);
+                }
+            } else if let Some(&encoding) =
+                (id_offset as u64).checked_sub(id).and_then(|cie| cie_encodings.get(&(cie as usize)))
+            {
+                let field_va = va_of(&reader);
+                let pc_begin = self.read_encoded(&mut reader, encoding, field_va, 0);
+                let pc_range = self.read_encoded(&mut reader, encoding & 0x0F, 0, 0);
+                if let (Ok(pc_begin), Ok(pc_range)) = (pc_begin, pc_range) {
+                    if pc_range > 0 {
+                        entries.push((pc_begin, Some(pc_begin.wrapping_add(pc_range))));
+                    }
+                }
+            }
+
+            if end > self.data.len() {
+                break;
+            }
+            reader.set_offset(end);
+        }
+        entries
+    }
+
+    /// Parse a CIE after its id, returning the FDE pointer encoding
+    fn parse_cie(&self, reader: &mut BinaryReader, va_of: impl Fn(&BinaryReader) -> u64) -> ParseResult<u8> {
+        let version = reader.read_u8()?;
+        let augmentation = reader.read_cstring(16)?;
+        if augmentation.contains("eh") {
+            self.read_word(reader)?;
+        }
+        reader.read_uleb128()?; // code alignment
+        reader.read_sleb128()?; // data alignment
+        if version == 1 {
+            reader.read_u8()?;
+        } else {
+            reader.read_uleb128()?;
+        }
+
+        let mut encoding = 0;
+        if augmentation.starts_with('z') {
+            reader.read_uleb128()?;
+            for c in augmentation.chars().skip(1) {
+                match c {
+                    'R' => encoding = reader.read_u8()?,
+                    'L' => {
+                        reader.read_u8()?;
+                    }
+                    'P' => {
+                        let personality = reader.read_u8()?;
+                        let va = va_of(reader);
+                        self.read_encoded(reader, personality, va, 0)?;
+                    }
+                    'S' | 'B' => {}
+                    _ => break,
+                }
+            }
+        }
+        Ok(encoding)
+    }
+
+    /// Read a `DW_EH_PE_*` encoded pointer located at `field_va`
+    fn read_encoded(&self, reader: &mut BinaryReader, encoding: u8, field_va: u64, data_base: u64) -> ParseResult<u64> {
+        if encoding == DW_EH_PE_OMIT {
+            return Ok(0);
+        }
+        let value = match encoding & 0x0F {
+            0x00 => self.read_word(reader)?,
+            0x01 => reader.read_uleb128()?,
+            0x02 => reader.read_u16()? as u64,
+            0x03 => reader.read_u32()? as u64,
+            0x04 | 0x0C => reader.read_u64()?,
+            0x09 => reader.read_sleb128()? as u64,
+            0x0A => reader.read_u16()? as i16 as i64 as u64,
+            0x0B => reader.read_i32()? as i64 as u64,
+            format => return Err(ParseError::parse(format!("Unsupported pointer encoding 0x{:02X}", format))),
+        };
+        Ok(match encoding & 0x70 {
+            DW_EH_PE_PCREL => field_va.wrapping_add(value),
+            DW_EH_PE_DATAREL => data_base.wrapping_add(value),
+            _ => value,
         })
     }
 
+    /// Create a reader at a virtual address
+    fn reader_at_va(&self, va: u64) -> ParseResult<BinaryReader<'_>> {
+        let offset = self
+            .va_to_offset(Address::new(va))
+            .ok_or(ParseError::AddressOutOfBounds(va))?;
+        Ok(BinaryReader::new_at(&self.data, offset as usize, self.little_endian))
+    }
+
+    fn read_word(&self, reader: &mut BinaryReader) -> ParseResult<u64> {
+        if self.is_64bit {
+            reader.read_u64()
+        } else {
+            Ok(reader.read_u32()? as u64)
+        }
+    }
+
+    fn parse_dynamic(&self) -> ParseResult<Vec<DynamicEntry>> {
+        let Some(segment) = self.program_headers.iter().find(|ph| ph.p_type == PT_DYNAMIC) else {
+            return Ok(Vec::new());
+        };
+
+        let offset = match self.layout {
+            ImageLayout::File => segment.p_offset,
+            ImageLayout::Mapped => match self.va_to_offset(Address::new(segment.p_vaddr)) {
+                Some(offset) => offset,
+                None => return Ok(Vec::new()),
+            },
+        };
+        let mut reader = BinaryReader::new_at(&self.data, offset as usize, self.little_endian);
+        let entry_size = if self.is_64bit { 16 } else { 8 };
+
+        let mut entries = Vec::new();
+        for _ in 0..segment.p_filesz / entry_size {
+            let tag = self.read_word(&mut reader)?;
+            let value = self.read_word(&mut reader)?;
+            if tag == DT_NULL {
+                break;
+            }
+            entries.push(DynamicEntry { tag, value });
+        }
+
+        Ok(entries)
+    }
+
+    /// Number of `.dynsym` entries, from `DT_HASH` or `DT_GNU_HASH`
+    fn dynamic_symbol_count(&self) -> ParseResult<usize> {
+        if let Some(hash) = self.dynamic_value(DT_HASH) {
+            let mut reader = self.reader_at_va(hash)?;
+            let _nbucket = reader.read_u32()?;
+            return Ok(reader.read_u32()? as usize);
+        }
+
+        let Some(gnu_hash) = self.dynamic_value(DT_GNU_HASH) else {
+            return Ok(0);
+        };
+        let mut reader = self.reader_at_va(gnu_hash)?;
+        let nbuckets = reader.read_u32()? as usize;
+        let symoffset = reader.read_u32()? as usize;
+        let bloom_size = reader.read_u32()? as usize;
+        let _bloom_shift = reader.read_u32()?;
+        reader.skip(bloom_size * if self.is_64bit { 8 } else { 4 })?;
+
+        let mut last = 0usize;
+        for _ in 0..nbuckets {
+            last = last.max(reader.read_u32()? as usize);
+        }
+        if last < symoffset {
+            return Ok(symoffset);
+        }
+
+        // Walk the last chain to its terminator (low bit set)
+        reader.skip((last - symoffset) * 4)?;
+        loop {
+            let hash = reader.read_u32()?;
+            if hash & 1 != 0 {
+                return Ok(last + 1);
+            }
+            last += 1;
+        }
+    }
+
+    /// Defined symbols and imported names from `DT_SYMTAB`/`DT_STRTAB`
+    ///
+    /// The defined symbols stand in for the symbol table of files without section headers.
+    fn parse_dynamic_symbols(&self) -> ParseResult<(Vec<Symbol>, Vec<String>)> {
+        let (Some(symtab), Some(strtab)) = (self.dynamic_value(DT_SYMTAB), self.dynamic_value(DT_STRTAB))
+        else {
+            return Ok(Default::default());
+        };
+        let strsz = self.dynamic_value(DT_STRSZ).unwrap_or(u64::MAX);
+        let sym_size = self
+            .dynamic_value(DT_SYMENT)
+            .unwrap_or(if self.is_64bit { 24 } else { 16 });
+        if sym_size == 0 {
+            return Err(ParseError::invalid_header("DT_SYMENT is zero"));
+        }
+        let count = self.dynamic_symbol_count()?;
+
+        let mut symbols = Vec::new();
+        let mut imported = Vec::new();
+        for index in 1..count as u64 {
+            let address = index
+                .checked_mul(sym_size)
+                .and_then(|offset| symtab.checked_add(offset))
+                .ok_or_else(|| ParseError::overflow("dynamic symbol address"))?;
+            let mut reader = self.reader_at_va(address)?;
+            let (st_name, st_value, st_size, st_info) = if self.is_64bit {
+                let st_name = reader.read_u32()?;
+                let st_info = reader.read_u8()?;
+                let _st_other = reader.read_u8()?;
+                let _st_shndx = reader.read_u16()?;
+                let st_value = reader.read_u64()?;
+                let st_size = reader.read_u64()?;
+                (st_name, st_value, st_size, st_info)
+            } else {
+                let st_name = reader.read_u32()?;
+                let st_value = reader.read_u32()? as u64;
+                let st_size = reader.read_u32()? as u64;
+                let st_info = reader.read_u8()?;
+                (st_name, st_value, st_size, st_info)
+            };
+
+            if st_name as u64 >= strsz {
+                continue;
+            }
+            let name = self.reader_at_va(strtab.wrapping_add(st_name as u64))?.read_cstring(4096)?;
+            if name.is_empty() {
+                continue;
+            }
+            // Undefined (imported) symbols have no address in this image
+            if st_value == 0 {
+                imported.push(name);
+                continue;
+            }
+
+            let symbol_type = match st_info & 0xf {
+                STT_FUNC => SymbolType::Function,
+                STT_OBJECT => SymbolType::Object,
+                STT_SECTION => SymbolType::Section,
+                STT_FILE => SymbolType::File,
+                _ => SymbolType::Unknown,
+            };
+            symbols.push(Symbol {
+                name,
+                address: Address::new(st_value),
+                size: if st_size > 0 { Some(st_size) } else { None },
+                symbol_type,
+                type_name: None,
+            });
+        }
+
+        Ok((symbols, imported))
+    }
+
+    /// Imported functions at the GOT slots their PLT stubs jump through,
+    /// from the `DT_JMPREL` jump slot relocations
+    fn parse_jump_slots(&self) -> ParseResult<Vec<Symbol>> {
+        let (Some(table), Some(size), Some(symtab), Some(strtab)) = (
+            self.dynamic_value(DT_JMPREL),
+            self.dynamic_value(DT_PLTRELSZ),
+            self.dynamic_value(DT_SYMTAB),
+            self.dynamic_value(DT_STRTAB),
+        ) else {
+            return Ok(Vec::new());
+        };
+        let word = if self.is_64bit { 8 } else { 4 };
+        let has_addend = self.dynamic_value(DT_PLTREL) != Some(DT_REL);
+        let entry = word * if has_addend { 3 } else { 2 };
+        let sym_size = self
+            .dynamic_value(DT_SYMENT)
+            .unwrap_or(if self.is_64bit { 24 } else { 16 });
+        let jump_slot = match self.architecture {
+            Architecture::Arm64 => R_AARCH64_JUMP_SLOT,
+            Architecture::Arm32 => R_ARM_JUMP_SLOT,
+            Architecture::X64 => R_X86_64_JUMP_SLOT,
+            Architecture::X86 => R_386_JMP_SLOT,
+            Architecture::Unknown => return Ok(Vec::new()),
+        };
+
+        let mut reader = self.reader_at_va(table)?;
+        let mut slots = Vec::new();
+        for _ in 0..size / entry {
+            let r_offset = self.read_word(&mut reader)?;
+            let r_info = self.read_word(&mut reader)?;
+            if has_addend {
+                self.read_word(&mut reader)?;
+            }
+            let (r_type, index) = if self.is_64bit {
+                (r_info as u32, r_info >> 32)
+            } else {
+                ((r_info & 0xff) as u32, r_info >> 8)
+            };
+            if r_type != jump_slot || index == 0 {
+                continue;
+            }
+
+            let st_name = index
+                .checked_mul(sym_size)
+                .and_then(|offset| symtab.checked_add(offset))
+                .ok_or_else(|| ParseError::overflow("dynamic symbol address"))?;
+            let st_name = self.reader_at_va(st_name)?.read_u32()?;
+            let name = self.reader_at_va(strtab.wrapping_add(st_name as u64))?.read_cstring(4096)?;
+            if !name.is_empty() {
+                slots.push(Symbol {
+                    name,
+                    address: Address::new(r_offset),
+                    size: Some(word),
+                    symbol_type: SymbolType::Function,
+                    type_name: None,
+                });
+            }
+        }
+        Ok(slots)
+    }
+
+    fn is_relative_relocation(&self, r_type: u32) -> bool {
+        match self.architecture {
+            Architecture::Arm64 => r_type == R_AARCH64_RELATIVE,
+            Architecture::Arm32 => r_type == R_ARM_RELATIVE,
+            Architecture::X64 => r_type == R_X86_64_RELATIVE,
+            Architecture::X86 => r_type == R_386_RELATIVE,
+            Architecture::Unknown => false,
+        }
+    }
+
+    /// Apply `DT_RELA`, `DT_REL` and `DT_RELR` relative relocations for a load
+    /// base of zero, so pointer slots hold the addresses they point to
+    fn apply_relative_relocations(&mut self) -> ParseResult<usize> {
+        let word = if self.is_64bit { 8 } else { 4 };
+        // Collected first: patching needs `self.data` mutably
+        let mut patches = Vec::new();
+
+        for (table, size, entry, has_addend) in [
+            (DT_RELA, DT_RELASZ, DT_RELAENT, true),
+            (DT_REL, DT_RELSZ, DT_RELENT, false),
+        ] {
+            let (Some(table), Some(size)) = (self.dynamic_value(table), self.dynamic_value(size)) else {
+                continue;
+            };
+            let entry = self
+                .dynamic_value(entry)
+                .unwrap_or(word * if has_addend { 3 } else { 2 });
+            if entry == 0 {
+                continue;
+            }
+
+            let mut reader = self.reader_at_va(table)?;
+            for _ in 0..size / entry {
+                let start = reader.offset();
+                let r_offset = self.read_word(&mut reader)?;
+                let r_info = self.read_word(&mut reader)?;
+                let r_type = if self.is_64bit { r_info as u32 } else { (r_info & 0xff) as u32 };
+                let addend = if has_addend { Some(self.read_word(&mut reader)?) } else { None };
+                reader.set_offset(
+                    usize::try_from(entry)
+                        .ok()
+                        .and_then(|entry| start.checked_add(entry))
+                        .ok_or_else(|| ParseError::overflow("relocation entry offset"))?,
+                );
+
+                if self.is_relative_relocation(r_type) {
+                    patches.push((r_offset, addend));
+                }
+            }
+        }
+
+        if let (Some(table), Some(size)) = (self.dynamic_value(DT_RELR), self.dynamic_value(DT_RELRSZ)) {
+            let entry = self.dynamic_value(DT_RELRENT).unwrap_or(word).max(1);
+            let mut reader = self.reader_at_va(table)?;
+            let mut next = 0u64;
+            for _ in 0..size / entry {
+                let value = self.read_word(&mut reader)?;
+                if value & 1 == 0 {
+                    // Address entry
+                    patches.push((value, None));
+                    next = value.wrapping_add(word);
+                } else {
+                    // Bitmap of the following word * 8 - 1 slots
+                    for bit in 1..word * 8 {
+                        if (value >> bit) & 1 != 0 {
+                            patches.push((next.wrapping_add((bit - 1) * word), None));
+                        }
+                    }
+                    next = next.wrapping_add((word * 8 - 1) * word);
+                }
+            }
+        }
+
+        // With a load base of zero, REL/RELR slots already hold the right value
+        let mut applied = 0;
+        for (address, addend) in patches {
+            let Some(offset) = self.va_to_offset(Address::new(address)) else {
+                continue;
+            };
+            let Some(slot) = usize::try_from(offset)
+                .ok()
+                .and_then(|offset| self.data.get_mut(offset..offset.checked_add(word as usize)?))
+            else {
+                continue;
+            };
+            if let Some(addend) = addend {
+                let bytes = match (self.is_64bit, self.little_endian) {
+                    (true, true) => addend.to_le_bytes().to_vec(),
+                    (true, false) => addend.to_be_bytes().to_vec(),
+                    (false, true) => (addend as u32).to_le_bytes().to_vec(),
+                    (false, false) => (addend as u32).to_be_bytes().to_vec(),
+                };
+                slot.copy_from_slice(&bytes);
+            }
+            applied += 1;
+        }
+
+        Ok(applied)
+    }
+
+    /// Get the program headers
+    pub fn program_headers(&self) -> &[ProgramHeader] {
+        &self.program_headers
+    }
+
+    fn parse_program_headers(
+        reader: &mut BinaryReader,
+        ph_offset: u64,
+        ph_entsize: u16,
+        ph_num: u16,
+        is_64bit: bool,
+    ) -> ParseResult<Vec<ProgramHeader>> {
+        let mut headers = Vec::with_capacity(ph_num as usize);
+        if ph_offset == 0 {
+            return Ok(headers);
+        }
+        let min_entsize = if is_64bit { 56 } else { 32 };
+        if ph_num > 0 && ph_entsize < min_entsize {
+            return Err(ParseError::invalid_header(format!("Program header entry size {}", ph_entsize)));
+        }
+
+        for i in 0..ph_num as usize {
+            let offset = (i as u64 * ph_entsize as u64)
+                .checked_add(ph_offset)
+                .ok_or_else(|| ParseError::overflow("program header offset"))?;
+            reader.set_offset(usize::try_from(offset).map_err(|_| ParseError::overflow("program header offset"))?);
+            let header = if is_64bit {
+                let p_type = reader.read_u32()?;
+                let p_flags = reader.read_u32()?;
+                let p_offset = reader.read_u64()?;
+                let p_vaddr = reader.read_u64()?;
+                let _p_paddr = reader.read_u64()?;
+                let p_filesz = reader.read_u64()?;
+                let p_memsz = reader.read_u64()?;
+                ProgramHeader { p_type, p_flags, p_offset, p_vaddr, p_filesz, p_memsz }
+            } else {
+                let p_type = reader.read_u32()?;
+                let p_offset = reader.read_u32()? as u64;
+                let p_vaddr = reader.read_u32()? as u64;
+                let _p_paddr = reader.read_u32()?;
+                let p_filesz = reader.read_u32()? as u64;
+                let p_memsz = reader.read_u32()? as u64;
+                let p_flags = reader.read_u32()?;
+                ProgramHeader { p_type, p_flags, p_offset, p_vaddr, p_filesz, p_memsz }
+            };
+            headers.push(header);
+        }
+
+        Ok(headers)
+    }
+
+    /// Lowest `PT_LOAD` address, the base a mapped image is relative to
+    fn load_base(program_headers: &[ProgramHeader]) -> Option<u64> {
+        program_headers
+            .iter()
+            .filter(|ph| ph.p_type == PT_LOAD)
+            .map(|ph| ph.p_vaddr)
+            .min()
+    }
+
+    /// Guess whether `data` is a memory dump rather than an on-disk file
+    ///
+    /// Only data spanning every `PT_LOAD` segment at `p_vaddr - base` can be a
+    /// dump. A `PT_DYNAMIC` table found at exactly one of its file offset and
+    /// mapped address settles it; otherwise files are told apart by an intact
+    /// section header table. Dumps often keep the original `e_shoff`, which
+    /// then points at unrelated memory, so the table itself is checked too.
+    fn detect_layout(
+        data: &[u8],
+        program_headers: &[ProgramHeader],
+        table: SectionTable,
+        is_64bit: bool,
+        little_endian: bool,
+    ) -> ImageLayout {
+        let Some(base) = Self::load_base(program_headers) else {
+            return ImageLayout::File;
+        };
+
+        let loads = || program_headers.iter().filter(|ph| ph.p_type == PT_LOAD);
+        let image_end = loads().map(|ph| (ph.p_vaddr - base).saturating_add(ph.p_filesz)).max().unwrap_or(0);
+        let segments_move = loads().any(|ph| ph.p_offset != ph.p_vaddr - base);
+        if !segments_move || (data.len() as u64) < image_end {
+            return ImageLayout::File;
+        }
+
+        if let Some(dynamic) = program_headers.iter().find(|ph| ph.p_type == PT_DYNAMIC) {
+            let table_at = |offset| Self::looks_like_dynamic(data, offset, dynamic.p_filesz, is_64bit, little_endian);
+            let in_file = table_at(dynamic.p_offset);
+            let in_memory = dynamic.p_vaddr.checked_sub(base).is_some_and(table_at);
+            if in_file != in_memory {
+                return if in_memory { ImageLayout::Mapped } else { ImageLayout::File };
+            }
+        }
+
+        if Self::section_table_intact(data, program_headers, table, is_64bit, little_endian) {
+            ImageLayout::File
+        } else {
+            ImageLayout::Mapped
+        }
+    }
+
+    /// Whether `size` bytes at `offset` read as dynamic entries ending in `DT_NULL`
+    fn looks_like_dynamic(data: &[u8], offset: u64, size: u64, is_64bit: bool, little_endian: bool) -> bool {
+        let Ok(offset) = usize::try_from(offset) else {
+            return false;
+        };
+        let entry_size = if is_64bit { 16 } else { 8 };
+        let mut reader = BinaryReader::new_at(data, offset, little_endian);
+        let mut entries = 0;
+        for _ in 0..size / entry_size {
+            let entry = if is_64bit {
+                reader.read_u64().and_then(|tag| reader.read_u64().map(|_| tag))
+            } else {
+                reader.read_u32().and_then(|tag| reader.read_u32().map(|_| tag as u64))
+            };
+            match entry {
+                Ok(DT_NULL) => return entries > 0,
+                // Generic tags, then the OS- and processor-specific ranges
+                Ok(tag) if tag <= DT_RELRENT || (0x6000_0000..0x8000_0000).contains(&tag) => entries += 1,
+                _ => return false,
+            }
+        }
+        false
+    }
+
+    /// Whether the section header table is one an on-disk file would keep:
+    /// inside the data, clear of every segment's file bytes, opening with the
+    /// null entry and naming a `SHT_STRTAB` for section names
+    fn section_table_intact(
+        data: &[u8],
+        program_headers: &[ProgramHeader],
+        table: SectionTable,
+        is_64bit: bool,
+        little_endian: bool,
+    ) -> bool {
+        let min_entry_size = if is_64bit { 64 } else { 40 };
+        if table.offset == 0 || table.count == 0 || table.entry_size < min_entry_size || table.names_index >= table.count {
+            return false;
+        }
+        let entry_size = table.entry_size as u64;
+        let Some(end) = (entry_size * table.count as u64).checked_add(table.offset) else {
+            return false;
+        };
+        if end > data.len() as u64 {
+            return false;
+        }
+        let inside_segment = program_headers
+            .iter()
+            .filter(|ph| ph.p_type == PT_LOAD)
+            .any(|ph| table.offset < ph.p_offset.saturating_add(ph.p_filesz) && ph.p_offset < end);
+        if inside_segment {
+            return false;
+        }
+
+        let null_entry = &data[table.offset as usize..(table.offset + entry_size) as usize];
+        let names = table.offset + table.names_index as u64 * entry_size;
+        let names_type = BinaryReader::new_at(data, names as usize + 4, little_endian).read_u32();
+        null_entry.iter().all(|&b| b == 0) && matches!(names_type, Ok(SHT_STRTAB))
+    }
+
+    /// One section per `PT_LOAD` segment, for images without section headers
+    fn segment_sections(data: &[u8], program_headers: &[ProgramHeader], layout: ImageLayout) -> Vec<Section> {
+        let base = Self::load_base(program_headers).unwrap_or(0);
+        program_headers
+            .iter()
+            .filter(|ph| ph.p_type == PT_LOAD)
+            .enumerate()
+            .map(|(i, ph)| {
+                let mut flags = SectionFlags::INITIALIZED;
+                if ph.p_flags & PF_R != 0 {
+                    flags |= SectionFlags::READ;
+                }
+                if ph.p_flags & PF_W != 0 {
+                    flags |= SectionFlags::WRITE;
+                }
+                if ph.p_flags & PF_X != 0 {
+                    flags |= SectionFlags::EXECUTE;
+                }
+
+                let (raw_offset, raw_size) = match layout {
+                    ImageLayout::File => (ph.p_offset, ph.p_filesz),
+                    ImageLayout::Mapped => (ph.p_vaddr - base, ph.p_memsz),
+                };
+                Section {
+                    name: format!("LOAD{}", i),
+                    virtual_address: Address::new(ph.p_vaddr),
+                    virtual_size: ph.p_memsz,
+                    raw_offset,
+                    raw_size: raw_size.min((data.len() as u64).saturating_sub(raw_offset)),
+                    characteristics: flags,
+                }
+            })
+            .collect()
+    }
+
     fn parse_symbols(
         data: &[u8],
         raw_sections: &[(u32, u32, u64, u64, u64, u64)],
@@ -241,8 +1081,10 @@ impl ElfFile {
             let mut reader = BinaryReader::new(data, little_endian);
 
             for j in 0..num_symbols {
-                let offset = *sh_offset as usize + j * sym_size;
-                reader.set_offset(offset);
+                let offset = (j as u64 * sym_size as u64)
+                    .checked_add(*sh_offset)
+                    .ok_or_else(|| ParseError::overflow("symbol table offset"))?;
+                reader.set_offset(usize::try_from(offset).map_err(|_| ParseError::overflow("symbol table offset"))?);
 
                 let (st_name, st_value, st_size, st_info) = if is_64bit {
                     let st_name = reader.read_u32()?;
@@ -260,20 +1102,14 @@ impl ElfFile {
                     (st_name, st_value, st_size, st_info)
                 };
 
-                let name = if (st_name as u64) < strtab_size {
-                    let name_offset = strtab_offset as usize + st_name as usize;
-                    let mut end = name_offset;
-                    while end < data.len() && data[end] != 0 {
-                        end += 1;
-                    }
-                    String::from_utf8_lossy(&data[name_offset..end]).to_string()
-                } else {
-                    continue;
-                };
-
-                if name.is_empty() {
+                if st_name as u64 >= strtab_size {
                     continue;
                 }
+                let max_len = (strtab_size - st_name as u64).min(usize::MAX as u64) as usize;
+                let name = match reader.string_at(strtab_offset.saturating_add(st_name as u64), max_len) {
+                    Ok(name) if !name.is_empty() => name,
+                    _ => continue,
+                };
 
                 let sym_type = st_info & 0xf;
                 let symbol_type = match sym_type {
@@ -289,6 +1125,7 @@ impl ElfFile {
                     address: Address::new(st_value),
                     size: if st_size > 0 { Some(st_size) } else { None },
                     symbol_type,
+                    type_name: None,
                 });
             }
         }
@@ -316,13 +1153,17 @@ impl BinaryFile for ElfFile {
     }
 
     fn image_base(&self) -> Address {
+        if self.layout == ImageLayout::Mapped {
+            return Address::new(Self::load_base(&self.program_headers).unwrap_or(0));
+        }
+
         // ELF doesn't have a fixed image base like PE
         // Return the lowest section address
         self.sections
             .iter()
             .filter(|s| s.virtual_address.as_u64() > 0)
             .map(|s| s.virtual_address)
-            .min()
+            .min_by_key(|a| a.as_u64())
             .unwrap_or(Address::ZERO)
     }
 
@@ -338,62 +1179,375 @@ impl BinaryFile for ElfFile {
         &self.symbols
     }
 
-    fn va_to_offset(&self, va: Address) -> Option<u64> {
-        for section in &self.sections {
-            let section_va_start = section.virtual_address.as_u64();
-            let section_va_end = section_va_start + section.virtual_size;
+    fn functions(&self) -> &[Function] {
+        &self.functions
+    }
 
-            if va.as_u64() >= section_va_start && va.as_u64() < section_va_end {
-                let offset_in_section = va.as_u64() - section_va_start;
-                return Some(section.raw_offset + offset_in_section);
-            }
+    fn add_symbols(&mut self, symbols: Vec<Symbol>) -> usize {
+        merge_symbols(&mut self.symbols, symbols)
+    }
+
+    fn imported_functions(&self) -> Vec<String> {
+        self.imported_functions.clone()
+    }
+
+    fn libraries(&self) -> Vec<String> {
+        let Some(strtab) = self.dynamic_value(DT_STRTAB) else {
+            return Vec::new();
+        };
+        self.dynamic
+            .iter()
+            .filter(|entry| entry.tag == DT_NEEDED)
+            .filter_map(|entry| self.read_string_va(Address::new(strtab.wrapping_add(entry.value)), 256).ok())
+            .collect()
+    }
+
+    fn va_to_offset(&self, va: Address) -> Option<u64> {
+        let section = self.sections.iter().find(|s| s.contains_va(va.as_u64()))?;
+        let delta = va.as_u64() - section.virtual_address.as_u64();
+        // Files hold no bytes for .bss or a segment's tail past p_filesz
+        if self.layout == ImageLayout::File && delta >= section.raw_size {
+            return None;
         }
-        None
+        section.raw_offset.checked_add(delta)
     }
 
     fn offset_to_va(&self, offset: u64) -> Option<Address> {
-        for section in &self.sections {
-            let raw_start = section.raw_offset;
-            let raw_end = raw_start + section.raw_size;
-
-            if offset >= raw_start && offset < raw_end {
-                let section_offset = offset - raw_start;
-                return Some(Address::new(
-                    section.virtual_address.as_u64() + section_offset,
-                ));
-            }
-        }
-        None
+        let section = self.sections.iter().find(|s| s.contains_offset(offset))?;
+        Some(Address::new(section.virtual_address.as_u64().wrapping_add(offset - section.raw_offset)))
     }
 
     fn read_va(&self, va: Address, size: usize) -> ParseResult<&[u8]> {
         let offset = self
             .va_to_offset(va)
-            .ok_or_else(|| ParseError::AddressOutOfBounds(va.as_u64()))? as usize;
-
-        if offset + size > self.data.len() {
-            return Err(ParseError::AddressOutOfBounds(va.as_u64()));
-        }
-
-        Ok(&self.data[offset..offset + size])
+            .ok_or_else(|| ParseError::AddressOutOfBounds(va.as_u64()))?;
+        checked_slice(&self.data, offset, size as u64).map_err(|_| ParseError::AddressOutOfBounds(va.as_u64()))
     }
 
     fn read_string_va(&self, va: Address, max_len: usize) -> ParseResult<String> {
         let offset = self
             .va_to_offset(va)
-            .ok_or_else(|| ParseError::AddressOutOfBounds(va.as_u64()))? as usize;
+            .ok_or_else(|| ParseError::AddressOutOfBounds(va.as_u64()))?;
+        BinaryReader::new(&self.data, true)
+            .string_at(offset, max_len)
+            .map_err(|_| ParseError::AddressOutOfBounds(va.as_u64()))
+    }
 
-        let end = (offset + max_len).min(self.data.len());
-        for i in offset..end {
-            if self.data[i] == 0 {
-                return Ok(String::from_utf8_lossy(&self.data[offset..i]).to_string());
-            }
+    fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn layout(&self) -> ImageLayout {
+        self.layout
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::put;
+
+    /// AArch64 ELF64 header followed by `segments` as
+    /// `(p_type, p_flags, p_offset, p_vaddr, p_filesz, p_memsz)`
+    fn elf64(size: usize, segments: &[(u32, u32, u64, u64, u64, u64)]) -> Vec<u8> {
+        let mut image = vec![0u8; size];
+        put(&mut image, 0, &[0x7F, b'E', b'L', b'F', ELFCLASS64, ELFDATA2LSB, 1]);
+        put(&mut image, 16, &3u16.to_le_bytes()); // ET_DYN
+        put(&mut image, 18, &EM_AARCH64.to_le_bytes());
+        put(&mut image, 32, &0x40u64.to_le_bytes()); // e_phoff
+        put(&mut image, 54, &56u16.to_le_bytes()); // e_phentsize
+        put(&mut image, 56, &(segments.len() as u16).to_le_bytes()); // e_phnum
+
+        for (i, &(p_type, flags, offset, vaddr, filesz, memsz)) in segments.iter().enumerate() {
+            let header = 0x40 + i * 56;
+            put(&mut image, header, &p_type.to_le_bytes());
+            put(&mut image, header + 4, &flags.to_le_bytes());
+            put(&mut image, header + 8, &offset.to_le_bytes());
+            put(&mut image, header + 16, &vaddr.to_le_bytes());
+            put(&mut image, header + 32, &filesz.to_le_bytes());
+            put(&mut image, header + 40, &memsz.to_le_bytes());
         }
+        image
+    }
 
-        Ok(String::from_utf8_lossy(&self.data[offset..end]).to_string())
+    /// Shared object without section headers: code at 0x0 and data at file
+    /// offset 0x200 loaded at 0x1200
+    fn sample_so() -> Vec<u8> {
+        let mut image = elf64(
+            0x300,
+            &[
+                (PT_LOAD, PF_R | PF_X, 0, 0, 0x200, 0x200),
+                (PT_LOAD, PF_R | PF_W, 0x200, 0x1200, 0x100, 0x180),
+            ],
+        );
+        put(&mut image, 0x200, b"data");
+        image
     }
 
-    fn data(&self) -> &[u8] {
-        &self.data
+    /// Stripped shared object whose symbols and relocations are only reachable
+    /// through `PT_DYNAMIC`
+    fn sample_so_dynamic() -> Vec<u8> {
+        let mut image = elf64(
+            0x400,
+            &[
+                (PT_LOAD, PF_R | PF_X, 0, 0, 0x200, 0x200),
+                (PT_LOAD, PF_R | PF_W, 0x200, 0x1200, 0x200, 0x280),
+                (PT_DYNAMIC, PF_R | PF_W, 0x280, 0x1280, 0x90, 0x90),
+            ],
+        );
+
+        // DT_GNU_HASH: one bucket starting at symbol 1, chain ends at symbol 2
+        put(&mut image, 0xF0, &1u32.to_le_bytes());
+        put(&mut image, 0xF4, &1u32.to_le_bytes());
+        put(&mut image, 0xF8, &1u32.to_le_bytes());
+        put(&mut image, 0x108, &1u32.to_le_bytes());
+        put(&mut image, 0x10C, &0x10u32.to_le_bytes());
+        put(&mut image, 0x110, &0x21u32.to_le_bytes());
+
+        // .dynsym at 0x118, .dynstr at 0x160
+        for (index, (name, info, value, size)) in
+            [(1u32, STT_FUNC | 0x10, 0x180u64, 0x10u64), (13, STT_OBJECT | 0x10, 0x1220, 0x10)]
+                .iter()
+                .enumerate()
+        {
+            let sym = 0x118 + (index + 1) * 24;
+            put(&mut image, sym, &name.to_le_bytes());
+            put(&mut image, sym + 4, &[*info]);
+            put(&mut image, sym + 8, &value.to_le_bytes());
+            put(&mut image, sym + 16, &size.to_le_bytes());
+        }
+        put(&mut image, 0x160, b"\0il2cpp_init\0g_CodeRegistration\0");
+
+        // One R_AARCH64_RELATIVE pointing the slot at 0x1210 to 0x1220
+        put(&mut image, 0x190, &0x1210u64.to_le_bytes());
+        put(&mut image, 0x198, &(R_AARCH64_RELATIVE as u64).to_le_bytes());
+        put(&mut image, 0x1A0, &0x1220u64.to_le_bytes());
+
+        for (i, (tag, value)) in [
+            (DT_GNU_HASH, 0xF0u64),
+            (DT_SYMTAB, 0x118),
+            (DT_STRTAB, 0x160),
+            (DT_STRSZ, 32),
+            (DT_SYMENT, 24),
+            (DT_RELA, 0x190),
+            (DT_RELASZ, 24),
+            (DT_RELAENT, 24),
+        ]
+        .iter()
+        .enumerate()
+        {
+            put(&mut image, 0x280 + i * 16, &tag.to_le_bytes());
+            put(&mut image, 0x288 + i * 16, &value.to_le_bytes());
+        }
+        image
+    }
+
+    fn sample_so_mapped() -> Vec<u8> {
+        map_so(&sample_so())
+    }
+
+    /// Memory image of a [`sample_so`]-shaped file, ELF header included
+    fn map_so(file: &[u8]) -> Vec<u8> {
+        let mut image = vec![0u8; 0x1380];
+        image[..0x200].copy_from_slice(&file[..0x200]);
+        image[0x1200..0x1300].copy_from_slice(&file[0x200..0x300]);
+        image
+    }
+
+    /// [`sample_so`] padded with a section header table at 0x1300, inside the
+    /// span its memory image covers, holding the null entry and `.shstrtab`
+    fn sample_so_with_sections() -> Vec<u8> {
+        with_sections(sample_so(), 0x1300, &[])
+    }
+
+    /// `image` with a section header table at `offset`: the null entry,
+    /// `.shstrtab` and `sections` as `(name, sh_type, sh_flags, sh_addr,
+    /// sh_offset, sh_size)`, followed by the names
+    fn with_sections(mut image: Vec<u8>, offset: usize, sections: &[(&str, u32, u64, u64, u64, u64)]) -> Vec<u8> {
+        let count = sections.len() + 2;
+        let names_offset = offset + count * 64;
+        let mut names = b"\0.shstrtab\0".to_vec();
+        let mut entries = vec![(1, SHT_STRTAB, 0, 0, names_offset as u64, 0)];
+        for &(name, sh_type, flags, addr, sh_offset, size) in sections {
+            entries.push((names.len() as u32, sh_type, flags, addr, sh_offset, size));
+            names.extend(name.as_bytes());
+            names.push(0);
+        }
+        entries[0].5 = names.len() as u64;
+
+        image.resize(names_offset + names.len(), 0);
+        put(&mut image, 40, &(offset as u64).to_le_bytes()); // e_shoff
+        put(&mut image, 58, &64u16.to_le_bytes()); // e_shentsize
+        put(&mut image, 60, &(count as u16).to_le_bytes()); // e_shnum
+        put(&mut image, 62, &1u16.to_le_bytes()); // e_shstrndx
+        for (i, (name, sh_type, flags, addr, sh_offset, size)) in entries.into_iter().enumerate() {
+            let header = offset + (i + 1) * 64;
+            put(&mut image, header, &name.to_le_bytes());
+            put(&mut image, header + 4, &sh_type.to_le_bytes());
+            put(&mut image, header + 8, &flags.to_le_bytes());
+            put(&mut image, header + 16, &addr.to_le_bytes());
+            put(&mut image, header + 24, &sh_offset.to_le_bytes());
+            put(&mut image, header + 32, &size.to_le_bytes());
+        }
+        put(&mut image, names_offset, &names);
+        image
+    }
+
+    #[test]
+    fn test_mapped_layout() {
+        let file = ElfFile::parse(&sample_so()).unwrap();
+        assert_eq!(file.layout(), ImageLayout::File);
+        assert_eq!(file.program_headers().len(), 2);
+
+        let elf = ElfFile::parse(&sample_so_mapped()).unwrap();
+        assert_eq!(elf.layout(), ImageLayout::Mapped);
+        assert_eq!(elf.image_base(), Address::ZERO);
+
+        let data = elf.find_section("LOAD1").unwrap();
+        assert_eq!((data.raw_offset, data.raw_size), (0x1200, 0x180));
+        assert!(data.characteristics.contains(SectionFlags::WRITE));
+        assert_eq!(elf.read_va(Address::new(0x1200), 4).unwrap(), b"data");
+        assert_eq!(elf.section_data(data).unwrap().len(), 0x180);
+
+        // The zero-filled tail past p_filesz only exists in memory
+        assert_eq!(file.va_to_offset(Address::new(0x12FF)), Some(0x2FF));
+        assert_eq!(file.va_to_offset(Address::new(0x1300)), None);
+        assert_eq!(elf.va_to_offset(Address::new(0x1300)), Some(0x1300));
+    }
+
+    #[test]
+    fn test_mapped_layout_with_section_headers() {
+        let file = sample_so_with_sections();
+        let elf = ElfFile::parse(&file).unwrap();
+        assert_eq!(elf.layout(), ImageLayout::File);
+        assert!(elf.find_section(".shstrtab").is_some());
+
+        // The dump keeps e_shoff, which now lands in the zero-filled tail of LOAD1
+        let dump = map_so(&file);
+        assert_eq!(u64::from_le_bytes(dump[40..48].try_into().unwrap()), 0x1300);
+        let elf = ElfFile::parse(&dump).unwrap();
+        assert_eq!(elf.layout(), ImageLayout::Mapped);
+        assert_eq!(elf.read_va(Address::new(0x1200), 4).unwrap(), b"data");
+    }
+
+    #[test]
+    fn test_nobits_sections() {
+        // .bss claims the file bytes at 0x300, where a name happens to sit
+        let mut image = sample_so();
+        image.resize(0x400, 0);
+        put(&mut image, 0x300, b"il2cpp_init\0");
+        let image = with_sections(
+            image,
+            0x400,
+            &[
+                (".data", 1, SHF_ALLOC | SHF_WRITE, 0x1200, 0x200, 0x100),
+                (".bss", SHT_NOBITS, SHF_ALLOC | SHF_WRITE, 0x1300, 0x300, 0x80),
+            ],
+        );
+
+        let elf = ElfFile::parse(&image).unwrap();
+        let bss = elf.find_section(".bss").unwrap();
+        assert_eq!((bss.virtual_size, bss.raw_size), (0x80, 0));
+        assert_eq!(elf.va_to_offset(Address::new(0x1300)), None);
+        let strings = crate::strings::extract(&elf, &Default::default());
+        let found: Vec<(u64, &str)> = strings.iter().map(|s| (s.address.as_u64(), s.value.as_str())).collect();
+        assert_eq!(found, [(0x1200, "data")]);
+    }
+
+    #[test]
+    fn test_eh_frame_functions() {
+        let mut image = elf64(
+            0x300,
+            &[
+                (PT_LOAD, PF_R | PF_X, 0, 0, 0x200, 0x200),
+                (PT_GNU_EH_FRAME, PF_R, 0x100, 0x100, 0x10, 0x10),
+            ],
+        );
+
+        // .eh_frame_hdr: version 1, eh_frame_ptr as pcrel sdata4 -> 0x120
+        put(&mut image, 0x100, &[1, 0x1B, 0x03, 0x3B]);
+        put(&mut image, 0x104, &0x1Cu32.to_le_bytes());
+
+        // CIE "zR" with pcrel sdata4 FDE pointers
+        put(&mut image, 0x120, &16u32.to_le_bytes());
+        put(&mut image, 0x128, &[1, b'z', b'R', 0, 1, 0x78, 30, 1, 0x1B]);
+
+        // FDEs for 0x180..0x190 and 0x190..0x1C0
+        for (record, cie_pointer, pc_begin, pc_range) in [(0x134usize, 0x14u32, 0x180u32, 0x10u32), (0x148, 0x28, 0x190, 0x30)] {
+            put(&mut image, record, &16u32.to_le_bytes());
+            put(&mut image, record + 4, &cie_pointer.to_le_bytes());
+            put(&mut image, record + 8, &(pc_begin - (record as u32 + 8)).to_le_bytes());
+            put(&mut image, record + 12, &pc_range.to_le_bytes());
+        }
+
+        let elf = ElfFile::parse(&image).unwrap();
+        let functions: Vec<(u64, u64)> =
+            elf.functions().iter().map(|f| (f.start.as_u64(), f.end.as_u64())).collect();
+        assert_eq!(functions, [(0x180, 0x190), (0x190, 0x1C0)]);
+        assert_eq!(elf.function_at(Address::new(0x1A0)).unwrap().start, Address::new(0x190));
+    }
+
+    #[test]
+    fn test_dynamic_symbols_and_relocations() {
+        let file = sample_so_dynamic();
+        let mut mapped = vec![0u8; 0x1480];
+        mapped[..0x200].copy_from_slice(&file[..0x200]);
+        mapped[0x1200..0x1400].copy_from_slice(&file[0x200..]);
+
+        for (data, layout) in [(file, ImageLayout::File), (mapped, ImageLayout::Mapped)] {
+            let elf = ElfFile::parse(&data).unwrap();
+            assert_eq!(elf.layout(), layout);
+            assert_eq!(elf.dynamic_entries().len(), 8);
+
+            let init = elf.find_symbol("il2cpp_init").unwrap();
+            assert_eq!((init.address, init.symbol_type), (Address::new(0x180), SymbolType::Function));
+            let registration = elf.find_symbol("g_CodeRegistration").unwrap();
+            assert_eq!(registration.address, Address::new(0x1220));
+
+            assert_eq!(elf.relocations_applied(), 1);
+            assert_eq!(elf.read_va(Address::new(0x1210), 8).unwrap(), 0x1220u64.to_le_bytes());
+        }
+    }
+
+    #[test]
+    fn test_jump_slots() {
+        // Add an undefined il2cpp_free as symbol 3, moving .dynstr out of its
+        // way, and a DT_JMPREL table binding the GOT slot at 0x1230 to it
+        let mut image = sample_so_dynamic();
+        put(&mut image, 0x160, &[0; 32]);
+        put(&mut image, 0x160, &32u32.to_le_bytes());
+        put(&mut image, 0x164, &[STT_FUNC | 0x10]);
+        put(&mut image, 0x1B0, b"\0il2cpp_init\0g_CodeRegistration\0il2cpp_free\0");
+        put(&mut image, 0x288 + 2 * 16, &0x1B0u64.to_le_bytes());
+        put(&mut image, 0x288 + 3 * 16, &44u64.to_le_bytes());
+        put(&mut image, 0x1E0, &0x1230u64.to_le_bytes());
+        put(&mut image, 0x1E8, &((3u64 << 32) | R_AARCH64_JUMP_SLOT as u64).to_le_bytes());
+        for (i, (tag, value)) in [(DT_JMPREL, 0x1E0u64), (DT_PLTRELSZ, 24), (DT_PLTREL, DT_RELA)].iter().enumerate() {
+            put(&mut image, 0x300 + i * 16, &tag.to_le_bytes());
+            put(&mut image, 0x308 + i * 16, &value.to_le_bytes());
+        }
+        put(&mut image, 0xD0, &0xC0u64.to_le_bytes()); // PT_DYNAMIC p_filesz
+        put(&mut image, 0xD8, &0xC0u64.to_le_bytes()); // p_memsz
+
+        let elf = ElfFile::parse(&image).unwrap();
+        assert_eq!(elf.dynamic_entries().len(), 11);
+        assert!(elf.find_symbol("il2cpp_init").is_some());
+        let free = elf.find_symbol("il2cpp_free").unwrap();
+        assert_eq!((free.address, free.size), (Address::new(0x1230), Some(8)));
+        assert_eq!(free.symbol_type, SymbolType::Function);
+    }
+
+    #[test]
+    fn test_malformed_dynamic_tables() {
+        // Point DT_GNU_HASH and DT_RELA outside every segment
+        let mut image = sample_so_dynamic();
+        put(&mut image, 0x288, &0xDEAD_0000u64.to_le_bytes());
+        put(&mut image, 0x288 + 5 * 16, &0xDEAD_0000u64.to_le_bytes());
+
+        let elf = ElfFile::parse(&image).unwrap();
+        assert_eq!(elf.dynamic_entries().len(), 8);
+        assert!(elf.symbols().is_empty());
+        assert_eq!(elf.relocations_applied(), 0);
+        assert_eq!(elf.sections().len(), 2);
     }
 }
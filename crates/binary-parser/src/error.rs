@@ -31,11 +31,26 @@ pub enum ParseError {
     #[error("Symbol not found: {0}")]
     SymbolNotFound(String),
 
+    #[error("Container entry not found: {0}")]
+    EntryNotFound(String),
+
     #[error("Parse error: {0}")]
     Parse(String),
 
     #[error("Truncated data: expected {expected} bytes, got {actual}")]
     TruncatedData { expected: usize, actual: usize },
+
+    #[error("Range {offset:#x}+{size:#x} exceeds data length {len:#x}")]
+    OutOfRange { offset: u64, size: u64, len: usize },
+
+    #[error("Arithmetic overflow computing {0}")]
+    Overflow(String),
+
+    #[error("Invalid load command: {0}")]
+    InvalidLoadCommand(String),
+
+    #[error("Patch manifest does not match: expected hash {expected}, got {actual}")]
+    ManifestMismatch { expected: String, actual: String },
 }
 
 pub type ParseResult<T> = std::result::Result<T, ParseError>;
@@ -52,4 +67,12 @@ impl ParseError {
     pub fn truncated(expected: usize, actual: usize) -> Self {
         Self::TruncatedData { expected, actual }
     }
+
+    pub fn out_of_range(offset: u64, size: u64, len: usize) -> Self {
+        Self::OutOfRange { offset, size, len }
+    }
+
+    pub fn overflow(what: impl Into<String>) -> Self {
+        Self::Overflow(what.into())
+    }
 }
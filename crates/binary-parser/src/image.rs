@@ -0,0 +1,79 @@
+//! Bytes backing a parsed binary
+//!
+//! Files are opened as private copy-on-write mappings, so parsing a large
+//! image touches only the pages it reads, and fixups written by the parsers
+//! (relocations, chained pointers, repaired headers) copy just the pages they
+//! patch without ever reaching the file on disk.
+
+use crate::error::ParseResult;
+use memmap2::{MmapMut, MmapOptions};
+use std::fs::File;
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+
+/// Owned or memory-mapped image bytes
+pub enum ImageData {
+    /// Heap buffer
+    Owned(Vec<u8>),
+    /// Private copy-on-write file mapping
+    Mapped(MmapMut),
+}
+
+impl ImageData {
+    /// Map a file copy-on-write
+    pub fn map(path: &Path) -> ParseResult<Self> {
+        let file = File::open(path)?;
+        // Zero-length mappings are rejected on some platforms
+        if file.metadata()?.len() == 0 {
+            return Ok(Self::Owned(Vec::new()));
+        }
+        // SAFETY: the mapping is private, so our writes never reach the file;
+        // as with any mapping, the file must not be truncated while mapped.
+        let map = unsafe { MmapOptions::new().map_copy(&file)? };
+        Ok(Self::Mapped(map))
+    }
+
+    /// Check whether the bytes are a file mapping
+    pub fn is_mapped(&self) -> bool {
+        matches!(self, Self::Mapped(_))
+    }
+}
+
+impl Deref for ImageData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Self::Owned(data) => data,
+            Self::Mapped(map) => map,
+        }
+    }
+}
+
+impl DerefMut for ImageData {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        match self {
+            Self::Owned(data) => data,
+            Self::Mapped(map) => map,
+        }
+    }
+}
+
+impl From<Vec<u8>> for ImageData {
+    fn from(data: Vec<u8>) -> Self {
+        Self::Owned(data)
+    }
+}
+
+impl From<&[u8]> for ImageData {
+    fn from(data: &[u8]) -> Self {
+        Self::Owned(data.to_vec())
+    }
+}
+
+impl std::fmt::Debug for ImageData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = if self.is_mapped() { "Mapped" } else { "Owned" };
+        write!(f, "ImageData::{}({} bytes)", kind, self.len())
+    }
+}
@@ -7,9 +7,30 @@ pub mod elf;
 pub mod macho;
 pub mod common;
 pub mod error;
+pub mod container;
+pub mod debug_info;
+pub mod scanner;
+pub mod disasm;
+pub mod xref;
+pub mod image;
+pub mod patch;
+pub mod protection;
+pub mod strings;
 
-pub use common::{BinaryFile, Section, Symbol};
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+
+pub use common::{BinaryFile, Function, ImageLayout, Section, Symbol};
+pub use container::{Package, PackageKind};
+pub use debug_info::{dwarf_symbols, load_debug_symbols, pdb_symbols};
+pub use disasm::{Disassembler, FlowKind, Instruction};
 pub use error::{ParseError, ParseResult};
+pub use image::ImageData;
+pub use patch::{Patch, PatchBuilder, PatchManifest};
+pub use protection::{Finding, MetadataReport, Protector, ProtectionReport};
+pub use scanner::{Pattern, PatternMatches, Scanner};
+pub use strings::{NativeString, StringEncoding, StringOptions};
+pub use xref::{Xref, XrefIndex, XrefKind};
 
 use endfield_core::{Architecture, BinaryFormat, Platform};
 use std::path::Path;
@@ -34,7 +55,8 @@ pub fn detect_format(data: &[u8]) -> Option<BinaryFormat> {
     if data.len() >= 4 {
         let magic = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
         match magic {
-            0xFEEDFACE | 0xFEEDFACF | 0xCAFEBABE | 0xBEBAFECA => {
+            0xFEEDFACE | 0xFEEDFACF | 0xCEFAEDFE | 0xCFFAEDFE | 0xCAFEBABE | 0xBEBAFECA
+            | 0xCAFEBABF | 0xBFBAFECA => {
                 return Some(BinaryFormat::MachO);
             }
             _ => {}
@@ -44,29 +66,132 @@ pub fn detect_format(data: &[u8]) -> Option<BinaryFormat> {
     None
 }
 
-/// Load a binary file and parse it
+/// Memory-map a binary file and parse it in place
+///
+/// The mapping is copy-on-write, so every page a parser writes to (applied
+/// relocations, chained fixups, repaired section headers) becomes private
+/// memory. For a shared library with relocated pointers throughout its data
+/// segments that is most of those segments; code pages usually stay shared.
 pub fn load_binary(path: &Path) -> ParseResult<Box<dyn BinaryFile>> {
-    let data = std::fs::read(path)?;
-    parse_binary(&data)
+    parse_image(ImageData::map(path)?, None)
+}
+
+/// Memory-map a binary file, selecting the slice named `arch` of a universal binary
+pub fn load_binary_arch(path: &Path, arch: &str) -> ParseResult<Box<dyn BinaryFile>> {
+    parse_image_arch(ImageData::map(path)?, arch)
 }
 
-/// Parse binary data
+/// Parse binary data, selecting the slice named `arch` (e.g. `arm64e`)
+///
+/// Only Mach-O binaries carry multiple architectures; other formats are rejected.
+pub fn parse_binary_arch(data: &[u8], arch: &str) -> ParseResult<Box<dyn BinaryFile>> {
+    parse_image_arch(data.into(), arch)
+}
+
+/// Like [`parse_binary_arch`], taking ownership of the bytes instead of copying them
+pub fn parse_image_arch(data: ImageData, arch: &str) -> ParseResult<Box<dyn BinaryFile>> {
+    match detect_format(&data).ok_or(ParseError::UnknownFormat)? {
+        BinaryFormat::MachO => Ok(Box::new(macho::MachOFile::from_data_arch(data, arch)?)),
+        format => Err(ParseError::UnsupportedArchitecture(format!(
+            "{} (architecture selection requires a Mach-O binary, got {:?})",
+            arch, format
+        ))),
+    }
+}
+
+/// Parse binary data, detecting whether it is a file or a memory dump
+///
+/// The bytes are copied, since parsers write fixups into the image; hand an
+/// owned buffer to [`parse_image`] to parse it without the copy.
 pub fn parse_binary(data: &[u8]) -> ParseResult<Box<dyn BinaryFile>> {
-    let format = detect_format(data).ok_or_else(|| ParseError::UnknownFormat)?;
+    parse_binary_with_layout(data, None)
+}
+
+/// Parse binary data with an explicit layout, or detect it when `None`
+///
+/// Mapped layouts are supported for PE and ELF; Mach-O is always read as a file.
+pub fn parse_binary_with_layout(
+    data: &[u8],
+    layout: Option<ImageLayout>,
+) -> ParseResult<Box<dyn BinaryFile>> {
+    parse_image(data.into(), layout)
+}
+
+/// Parse owned or memory-mapped bytes in place, with an explicit or detected layout
+pub fn parse_image(data: ImageData, layout: Option<ImageLayout>) -> ParseResult<Box<dyn BinaryFile>> {
+    let format = detect_format(&data).ok_or(ParseError::UnknownFormat)?;
 
     match format {
         BinaryFormat::PE => {
-            let pe = pe::PeFile::parse(data)?;
+            let pe = pe::PeFile::from_data(data, layout)?;
             Ok(Box::new(pe))
         }
         BinaryFormat::ELF => {
-            let elf = elf::ElfFile::parse(data)?;
+            let elf = elf::ElfFile::from_data(data, layout)?;
             Ok(Box::new(elf))
         }
         BinaryFormat::MachO => {
-            let macho = macho::MachOFile::parse(data)?;
+            let macho = macho::MachOFile::from_data(data)?;
             Ok(Box::new(macho))
         }
         BinaryFormat::Unknown => Err(ParseError::UnknownFormat),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Seed corpus of the `parse_binary` fuzz target
+    const SEEDS: [&[u8]; 8] = [
+        include_bytes!("../fuzz/corpus/parse_binary/pe64"),
+        include_bytes!("../fuzz/corpus/parse_binary/pe64_relocations"),
+        include_bytes!("../fuzz/corpus/parse_binary/pe64_mapped"),
+        include_bytes!("../fuzz/corpus/parse_binary/elf64_arm64"),
+        include_bytes!("../fuzz/corpus/parse_binary/elf64_dynamic"),
+        include_bytes!("../fuzz/corpus/parse_binary/elf64_mapped"),
+        include_bytes!("../fuzz/corpus/parse_binary/macho_arm64"),
+        include_bytes!("../fuzz/corpus/parse_binary/macho_fat"),
+    ];
+
+    #[test]
+    fn test_malformed_inputs() {
+        for seed in SEEDS {
+            assert!(parse_binary(seed).is_ok());
+
+            // Truncated and corrupted copies must fail cleanly rather than panic
+            for len in (0..seed.len()).step_by(7) {
+                let _ = parse_binary(&seed[..len]);
+            }
+            for offset in 0..seed.len().min(0x400) {
+                for value in [0x00, 0x7F, 0x80, 0xFF] {
+                    let mut data = seed.to_vec();
+                    data[offset] = value;
+                    if let Ok(binary) = parse_binary(&data) {
+                        for section in binary.sections() {
+                            let _ = binary.section_data(section);
+                            let _ = binary.read_string_va(section.virtual_address, 64);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_load_binary_mapped() {
+        let seed = SEEDS[4];
+        let path = std::env::temp_dir().join(format!("endfield-mapped-{}.so", std::process::id()));
+        std::fs::write(&path, seed).unwrap();
+
+        let mapped = load_binary(&path).unwrap();
+        let owned = parse_binary(seed).unwrap();
+        // Relocations are applied to the private mapping only
+        assert_ne!(mapped.data(), seed);
+        assert_eq!(mapped.data(), owned.data());
+        assert_eq!(std::fs::read(&path).unwrap(), seed);
+
+        drop(mapped);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,7 +1,11 @@
 //! Mach-O format parser for macOS/iOS binaries
 
-use crate::common::{BinaryFile, BinaryReader, Section, SectionFlags, Symbol, SymbolType};
+use crate::common::{
+    build_functions, checked_slice, merge_symbols, or_empty, BinaryFile, BinaryReader, Function, Section, SectionFlags,
+    Symbol, SymbolType,
+};
 use crate::error::{ParseError, ParseResult};
+use crate::image::ImageData;
 use endfield_core::{Address, Architecture, BinaryFormat, Platform};
 
 /// Mach-O magic numbers
@@ -10,6 +14,7 @@ const MH_MAGIC_64: u32 = 0xFEEDFACF;   // 64-bit
 const MH_CIGAM: u32 = 0xCEFAEDFE;      // 32-bit big endian
 const MH_CIGAM_64: u32 = 0xCFFAEDFE;   // 64-bit big endian
 const FAT_MAGIC: u32 = 0xCAFEBABE;     // Universal binary
+const FAT_MAGIC_64: u32 = 0xCAFEBABF;  // Universal binary with 64-bit offsets
 
 /// CPU types
 const CPU_TYPE_I386: u32 = 7;
@@ -17,98 +22,244 @@ const CPU_TYPE_X86_64: u32 = 0x01000007;
 const CPU_TYPE_ARM: u32 = 12;
 const CPU_TYPE_ARM64: u32 = 0x0100000C;
 
+/// CPU subtypes
+const CPU_SUBTYPE_MASK: u32 = 0xFF000000;
+const CPU_SUBTYPE_ARM64E: u32 = 2;
+const CPU_SUBTYPE_ARM_V7: u32 = 9;
+const CPU_SUBTYPE_ARM_V7S: u32 = 11;
+
 /// Load commands
 const LC_SEGMENT: u32 = 0x01;
 const LC_SYMTAB: u32 = 0x02;
 const LC_SEGMENT_64: u32 = 0x19;
 const LC_MAIN: u32 = 0x80000028;
+const LC_LOAD_DYLIB: u32 = 0x0C;
+const LC_LOAD_WEAK_DYLIB: u32 = 0x80000018;
+const LC_REEXPORT_DYLIB: u32 = 0x8000001F;
+const LC_FUNCTION_STARTS: u32 = 0x26;
+const LC_DYLD_INFO: u32 = 0x22;
+const LC_DYLD_INFO_ONLY: u32 = 0x80000022;
+const LC_DYLD_EXPORTS_TRIE: u32 = 0x80000033;
+const LC_DYLD_CHAINED_FIXUPS: u32 = 0x80000034;
+
+/// Chained fixup pointer formats
+const DYLD_CHAINED_PTR_ARM64E: u16 = 1;
+const DYLD_CHAINED_PTR_64: u16 = 2;
+const DYLD_CHAINED_PTR_64_OFFSET: u16 = 6;
+const DYLD_CHAINED_PTR_ARM64E_USERLAND: u16 = 9;
+const DYLD_CHAINED_PTR_ARM64E_USERLAND24: u16 = 12;
+
+/// Page start marking a page without fixups
+const DYLD_CHAINED_PTR_START_NONE: u16 = 0xFFFF;
+
+/// Export trie flags
+const EXPORT_SYMBOL_FLAGS_REEXPORT: u64 = 0x08;
+const EXPORT_SYMBOL_FLAGS_STUB_AND_RESOLVER: u64 = 0x10;
 
 /// Segment flags
 const VM_PROT_READ: u32 = 0x01;
 const VM_PROT_WRITE: u32 = 0x02;
 const VM_PROT_EXECUTE: u32 = 0x04;
 
+/// Section types without file contents
+const SECTION_TYPE: u32 = 0xFF;
+const S_ZEROFILL: u32 = 0x1;
+const S_GB_ZEROFILL: u32 = 0xC;
+const S_THREAD_LOCAL_ZEROFILL: u32 = 0x12;
+
+/// Architecture slice of a universal (FAT) binary
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FatSlice {
+    pub cpu_type: u32,
+    pub cpu_subtype: u32,
+    pub offset: u64,
+    pub size: u64,
+}
+
+impl FatSlice {
+    /// Architecture of the slice
+    pub fn architecture(&self) -> Architecture {
+        match self.cpu_type {
+            CPU_TYPE_I386 => Architecture::X86,
+            CPU_TYPE_X86_64 => Architecture::X64,
+            CPU_TYPE_ARM => Architecture::Arm32,
+            CPU_TYPE_ARM64 => Architecture::Arm64,
+            _ => Architecture::Unknown,
+        }
+    }
+
+    /// Conventional name such as `arm64`, `arm64e` or `x86_64`
+    pub fn name(&self) -> String {
+        let subtype = self.cpu_subtype & !CPU_SUBTYPE_MASK;
+        match (self.cpu_type, subtype) {
+            (CPU_TYPE_ARM64, CPU_SUBTYPE_ARM64E) => "arm64e".to_string(),
+            (CPU_TYPE_ARM64, _) => "arm64".to_string(),
+            (CPU_TYPE_X86_64, _) => "x86_64".to_string(),
+            (CPU_TYPE_I386, _) => "i386".to_string(),
+            (CPU_TYPE_ARM, CPU_SUBTYPE_ARM_V7) => "armv7".to_string(),
+            (CPU_TYPE_ARM, CPU_SUBTYPE_ARM_V7S) => "armv7s".to_string(),
+            (CPU_TYPE_ARM, _) => "arm".to_string(),
+            (cpu_type, subtype) => format!("cpu_{:#x}_{:#x}", cpu_type, subtype),
+        }
+    }
+}
+
+/// Segment from an `LC_SEGMENT(_64)` command
+#[derive(Debug, Clone)]
+struct Segment {
+    fileoff: u64,
+}
+
 /// Parsed Mach-O file
 pub struct MachOFile {
-    data: Vec<u8>,
+    data: ImageData,
     architecture: Architecture,
     is_64bit: bool,
     little_endian: bool,
     entry_point: Address,
     sections: Vec<Section>,
     symbols: Vec<Symbol>,
+    functions: Vec<Function>,
+    libraries: Vec<String>,
     text_base: Address,
+    fixups_applied: usize,
 }
 
 impl MachOFile {
     /// Parse a Mach-O file from raw bytes
+    ///
+    /// Universal binaries default to their first 64-bit slice; use
+    /// [`MachOFile::parse_arch`] to pick one explicitly.
     pub fn parse(data: &[u8]) -> ParseResult<Self> {
-        if data.len() < 4 {
-            return Err(ParseError::truncated(4, data.len()));
+        Self::from_data(data.into())
+    }
+
+    /// Parse owned or memory-mapped bytes in place, without copying them
+    pub fn from_data(image: ImageData) -> ParseResult<Self> {
+        if Self::is_fat(&image) {
+            let slice = Self::default_slice(&image)?;
+            return Self::parse_at(image, slice.offset as usize);
         }
+        Self::parse_at(image, 0)
+    }
 
-        let magic = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+    /// Parse the slice named `arch` (see [`FatSlice::name`])
+    ///
+    /// Thin binaries are accepted when their own architecture matches.
+    pub fn parse_arch(data: &[u8], arch: &str) -> ParseResult<Self> {
+        Self::from_data_arch(data.into(), arch)
+    }
 
-        let (is_64bit, little_endian, offset) = match magic {
-            MH_MAGIC => (false, true, 0),
-            MH_MAGIC_64 => (true, true, 0),
-            MH_CIGAM => (false, false, 0),
-            MH_CIGAM_64 => (true, false, 0),
-            FAT_MAGIC => {
-                // Universal binary - find the appropriate architecture
-                return Self::parse_fat_binary(data);
-            }
-            _ => return Err(ParseError::InvalidMagic {
-                expected: MH_MAGIC_64,
-                actual: magic,
-            }),
-        };
+    /// Like [`MachOFile::parse_arch`], without copying the bytes
+    pub fn from_data_arch(image: ImageData, arch: &str) -> ParseResult<Self> {
+        let slices = Self::slices(&image)?;
+        let slice = slices.iter().find(|slice| slice.name() == arch).ok_or_else(|| {
+            let available: Vec<String> = slices.iter().map(FatSlice::name).collect();
+            ParseError::UnsupportedArchitecture(format!(
+                "{} (available: {})",
+                arch,
+                available.join(", ")
+            ))
+        })?;
+        let offset = slice.offset as usize;
+        Self::parse_at(image, offset)
+    }
 
-        Self::parse_macho(data, offset, is_64bit, little_endian)
+    /// Parse a specific slice returned by [`MachOFile::slices`]
+    pub fn parse_slice(data: &[u8], slice: &FatSlice) -> ParseResult<Self> {
+        Self::parse_at(data.into(), slice.offset as usize)
     }
 
-    fn parse_fat_binary(data: &[u8]) -> ParseResult<Self> {
+    /// List the architecture slices, a single slice for thin binaries
+    pub fn slices(data: &[u8]) -> ParseResult<Vec<FatSlice>> {
+        if !Self::is_fat(data) {
+            let mut reader = BinaryReader::new(data, true);
+            let magic = reader.read_u32()?;
+            let little_endian = match magic {
+                MH_MAGIC | MH_MAGIC_64 => true,
+                MH_CIGAM | MH_CIGAM_64 => false,
+                _ => {
+                    return Err(ParseError::InvalidMagic {
+                        expected: MH_MAGIC_64,
+                        actual: magic,
+                    })
+                }
+            };
+            let mut reader = BinaryReader::new_at(data, 4, little_endian);
+            return Ok(vec![FatSlice {
+                cpu_type: reader.read_u32()?,
+                cpu_subtype: reader.read_u32()?,
+                offset: 0,
+                size: data.len() as u64,
+            }]);
+        }
+
         let mut reader = BinaryReader::new(data, false); // FAT headers are big endian
-        let _magic = reader.read_u32()?;
+        let magic = reader.read_u32()?;
         let nfat_arch = reader.read_u32()?;
 
-        // Try to find x86_64 or arm64 first
-        let mut best_offset = None;
-        let mut best_is_64 = false;
-
+        let mut slices = Vec::new();
         for _ in 0..nfat_arch {
-            let cputype = reader.read_u32()?;
-            let _cpusubtype = reader.read_u32()?;
-            let offset = reader.read_u32()?;
-            let _size = reader.read_u32()?;
-            let _align = reader.read_u32()?;
+            let cpu_type = reader.read_u32()?;
+            let cpu_subtype = reader.read_u32()?;
+            let (offset, size) = if magic == FAT_MAGIC_64 {
+                let offset = reader.read_u64()?;
+                let size = reader.read_u64()?;
+                reader.skip(8)?; // align, reserved
+                (offset, size)
+            } else {
+                let offset = reader.read_u32()? as u64;
+                let size = reader.read_u32()? as u64;
+                reader.skip(4)?; // align
+                (offset, size)
+            };
 
-            match cputype {
-                CPU_TYPE_X86_64 | CPU_TYPE_ARM64 => {
-                    best_offset = Some(offset as usize);
-                    best_is_64 = true;
-                    break;
-                }
-                CPU_TYPE_I386 | CPU_TYPE_ARM => {
-                    if best_offset.is_none() {
-                        best_offset = Some(offset as usize);
-                        best_is_64 = false;
-                    }
-                }
-                _ => {}
+            if offset.saturating_add(size) > data.len() as u64 {
+                return Err(ParseError::truncated(
+                    offset.saturating_add(size) as usize,
+                    data.len(),
+                ));
             }
+            slices.push(FatSlice { cpu_type, cpu_subtype, offset, size });
         }
 
-        let offset = best_offset.ok_or_else(|| ParseError::invalid_header("No supported architecture in FAT binary"))?;
+        Ok(slices)
+    }
+
+    fn is_fat(data: &[u8]) -> bool {
+        data.len() >= 4 && matches!(u32::from_be_bytes([data[0], data[1], data[2], data[3]]), FAT_MAGIC | FAT_MAGIC_64)
+    }
+
+    /// Slice parsed by default: the first 64-bit one, then the first 32-bit one
+    fn default_slice(data: &[u8]) -> ParseResult<FatSlice> {
+        let slices = Self::slices(data)?;
+        slices
+            .iter()
+            .find(|slice| matches!(slice.cpu_type, CPU_TYPE_X86_64 | CPU_TYPE_ARM64))
+            .or_else(|| slices.iter().find(|slice| matches!(slice.cpu_type, CPU_TYPE_I386 | CPU_TYPE_ARM)))
+            .copied()
+            .ok_or_else(|| ParseError::invalid_header("No supported architecture in FAT binary"))
+    }
 
-        // Re-check magic at offset
-        let magic = u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]);
-        let little_endian = matches!(magic, MH_MAGIC | MH_MAGIC_64);
+    /// Parse the thin Mach-O image starting at `offset`
+    fn parse_at(image: ImageData, offset: usize) -> ParseResult<Self> {
+        let magic = BinaryReader::new_at(&image, offset, true).read_u32()?;
+        let (is_64bit, little_endian) = match magic {
+            MH_MAGIC => (false, true),
+            MH_MAGIC_64 => (true, true),
+            MH_CIGAM => (false, false),
+            MH_CIGAM_64 => (true, false),
+            _ => return Err(ParseError::InvalidMagic {
+                expected: MH_MAGIC_64,
+                actual: magic,
+            }),
+        };
 
-        Self::parse_macho(data, offset, best_is_64, little_endian)
+        Self::parse_macho(image, offset, is_64bit, little_endian)
     }
 
-    fn parse_macho(data: &[u8], base_offset: usize, is_64bit: bool, little_endian: bool) -> ParseResult<Self> {
+    fn parse_macho(image: ImageData, base_offset: usize, is_64bit: bool, little_endian: bool) -> ParseResult<Self> {
+        let data: &[u8] = &image;
         let mut reader = BinaryReader::new(data, little_endian);
         reader.set_offset(base_offset);
 
@@ -140,12 +291,20 @@ impl MachOFile {
         let mut symtab_count = 0u32;
         let mut strtab_offset = 0u32;
         let mut strtab_size = 0u32;
+        let mut segments = Vec::new();
+        let mut chained_fixups = None;
+        let mut export_trie = None;
+        let mut function_starts = None;
+        let mut libraries = Vec::new();
 
         // Parse load commands
         for _ in 0..ncmds {
             let cmd_start = reader.offset();
             let cmd = reader.read_u32()?;
             let cmdsize = reader.read_u32()?;
+            if cmdsize < 8 {
+                return Err(ParseError::InvalidLoadCommand(format!("{:#x} has size {}", cmd, cmdsize)));
+            }
 
             match cmd {
                 LC_SEGMENT | LC_SEGMENT_64 => {
@@ -181,10 +340,11 @@ impl MachOFile {
                     if seg_name == "__TEXT" {
                         text_base = Address::new(vmaddr);
                     }
+                    segments.push(Segment { fileoff });
 
                     // Parse sections within segment
                     for _ in 0..nsects {
-                        let (sectname, segname_sect, addr, size, offset) = if cmd == LC_SEGMENT_64 {
+                        let (sectname, segname_sect, addr, size, offset, section_flags) = if cmd == LC_SEGMENT_64 {
                             let sectname = reader.read_bytes(16)?;
                             let segname = reader.read_bytes(16)?;
                             let addr = reader.read_u64()?;
@@ -193,11 +353,11 @@ impl MachOFile {
                             let _align = reader.read_u32()?;
                             let _reloff = reader.read_u32()?;
                             let _nreloc = reader.read_u32()?;
-                            let _flags = reader.read_u32()?;
+                            let section_flags = reader.read_u32()?;
                             let _reserved1 = reader.read_u32()?;
                             let _reserved2 = reader.read_u32()?;
                             let _reserved3 = reader.read_u32()?;
-                            (sectname, segname, addr, size, offset)
+                            (sectname, segname, addr, size, offset, section_flags)
                         } else {
                             let sectname = reader.read_bytes(16)?;
                             let segname = reader.read_bytes(16)?;
@@ -207,10 +367,10 @@ impl MachOFile {
                             let _align = reader.read_u32()?;
                             let _reloff = reader.read_u32()?;
                             let _nreloc = reader.read_u32()?;
-                            let _flags = reader.read_u32()?;
+                            let section_flags = reader.read_u32()?;
                             let _reserved1 = reader.read_u32()?;
                             let _reserved2 = reader.read_u32()?;
-                            (sectname, segname, addr, size, offset)
+                            (sectname, segname, addr, size, offset, section_flags)
                         };
 
                         let section_name = String::from_utf8_lossy(sectname)
@@ -228,12 +388,17 @@ impl MachOFile {
                             flags |= SectionFlags::EXECUTE;
                         }
 
+                        // Zerofill sections (__bss, __common) have no file bytes at offset 0
+                        let zerofill = matches!(
+                            section_flags & SECTION_TYPE,
+                            S_ZEROFILL | S_GB_ZEROFILL | S_THREAD_LOCAL_ZEROFILL
+                        );
                         sections.push(Section {
                             name: format!("{},{}", seg_name, section_name),
                             virtual_address: Address::new(addr),
                             virtual_size: size,
                             raw_offset: (base_offset as u64) + offset as u64,
-                            raw_size: size,
+                            raw_size: if zerofill { 0 } else { size },
                             characteristics: flags,
                         });
                     }
@@ -243,21 +408,53 @@ impl MachOFile {
                     let _stacksize = reader.read_u64()?;
                     entry_point = Address::new(entryoff);
                 }
+                LC_LOAD_DYLIB | LC_LOAD_WEAK_DYLIB | LC_REEXPORT_DYLIB => {
+                    let name_offset = reader.read_u32()?;
+                    let max_len = cmdsize.saturating_sub(name_offset) as usize;
+                    libraries.push(reader.string_at(cmd_start as u64 + name_offset as u64, max_len)?);
+                }
                 LC_SYMTAB => {
                     symtab_offset = reader.read_u32()?;
                     symtab_count = reader.read_u32()?;
                     strtab_offset = reader.read_u32()?;
                     strtab_size = reader.read_u32()?;
                 }
+                LC_DYLD_CHAINED_FIXUPS => {
+                    let dataoff = reader.read_u32()?;
+                    let datasize = reader.read_u32()?;
+                    chained_fixups = Some((dataoff, datasize));
+                }
+                LC_FUNCTION_STARTS => {
+                    let dataoff = reader.read_u32()?;
+                    let datasize = reader.read_u32()?;
+                    function_starts = Some((dataoff, datasize));
+                }
+                LC_DYLD_EXPORTS_TRIE => {
+                    let dataoff = reader.read_u32()?;
+                    let datasize = reader.read_u32()?;
+                    export_trie = Some((dataoff, datasize));
+                }
+                LC_DYLD_INFO | LC_DYLD_INFO_ONLY => {
+                    reader.skip(32)?; // rebase, bind, weak bind and lazy bind ranges
+                    let export_off = reader.read_u32()?;
+                    let export_size = reader.read_u32()?;
+                    if export_trie.is_none() && export_size > 0 {
+                        export_trie = Some((export_off, export_size));
+                    }
+                }
                 _ => {}
             }
 
-            reader.set_offset(cmd_start + cmdsize as usize);
+            reader.set_offset(
+                cmd_start
+                    .checked_add(cmdsize as usize)
+                    .ok_or_else(|| ParseError::overflow("load command offset"))?,
+            );
         }
 
         // Adjust entry point to absolute address
         if entry_point.as_u64() > 0 && text_base.as_u64() > 0 {
-            entry_point = Address::new(text_base.as_u64() + entry_point.as_u64());
+            entry_point = Address::new(text_base.as_u64().wrapping_add(entry_point.as_u64()));
         }
 
         // Parse symbols
@@ -273,16 +470,256 @@ impl MachOFile {
             )?;
         }
 
-        Ok(Self {
-            data: data.to_vec(),
+        let functions = match function_starts {
+            Some((dataoff, datasize)) => {
+                let starts = reader
+                    .bytes_at(base_offset as u64 + dataoff as u64, datasize as u64)
+                    .and_then(|starts| Self::parse_function_starts(starts, text_base.as_u64()));
+                build_functions(or_empty("LC_FUNCTION_STARTS", starts), &sections)
+            }
+            None => Vec::new(),
+        };
+
+        let mut macho = Self {
+            data: image,
             architecture,
             is_64bit,
             little_endian,
             entry_point,
             sections,
             symbols,
+            functions,
+            libraries,
             text_base,
-        })
+            fixups_applied: 0,
+        };
+
+        // Trie and fixup failures skip their load command; fixups are
+        // collected before any is written, so a bad chain leaves the data untouched
+        if let Some((dataoff, datasize)) = export_trie {
+            let exports = checked_slice(&macho.data, base_offset as u64 + dataoff as u64, datasize as u64)
+                .and_then(|trie| macho.parse_export_trie(trie));
+            for symbol in or_empty("export trie", exports) {
+                if macho.find_symbol(&symbol.name).is_none() {
+                    macho.symbols.push(symbol);
+                }
+            }
+        }
+
+        if let Some((dataoff, _)) = chained_fixups {
+            let applied = macho.apply_chained_fixups(base_offset, base_offset + dataoff as usize, &segments);
+            macho.fixups_applied = or_empty("LC_DYLD_CHAINED_FIXUPS", applied);
+        }
+
+        Ok(macho)
+    }
+
+    /// Decode `LC_FUNCTION_STARTS`: ULEB128 deltas from the `__TEXT` base
+    fn parse_function_starts(data: &[u8], text_base: u64) -> ParseResult<Vec<(u64, Option<u64>)>> {
+        let mut starts = Vec::new();
+        let mut address = text_base;
+        let mut cursor = 0;
+        while cursor < data.len() {
+            let delta = read_uleb128(data, &mut cursor)?;
+            if delta == 0 {
+                break;
+            }
+            address = address.wrapping_add(delta);
+            starts.push((address, None));
+        }
+        Ok(starts)
+    }
+
+    /// Number of chained fixups resolved in the data
+    pub fn fixups_applied(&self) -> usize {
+        self.fixups_applied
+    }
+
+    /// Decode `LC_DYLD_CHAINED_FIXUPS` chains and write the target of every
+    /// rebase (and the addend of every bind) back into the data, so pointer
+    /// reads return plain addresses
+    fn apply_chained_fixups(
+        &mut self,
+        base_offset: usize,
+        header_offset: usize,
+        segments: &[Segment],
+    ) -> ParseResult<usize> {
+        let mut reader = BinaryReader::new_at(&self.data, header_offset, true);
+        let _fixups_version = reader.read_u32()?;
+        let starts_offset = reader.read_u32()? as usize;
+
+        let starts = header_offset + starts_offset;
+        reader.set_offset(starts);
+        let seg_count = reader.read_u32()? as usize;
+        let mut seg_info_offsets = Vec::with_capacity(seg_count.min(segments.len()));
+        for _ in 0..seg_count {
+            seg_info_offsets.push(reader.read_u32()? as usize);
+        }
+
+        let image_base = self.text_base.as_u64();
+        let mut patches = Vec::new();
+        for (segment, &info_offset) in segments.iter().zip(&seg_info_offsets) {
+            if info_offset == 0 {
+                continue;
+            }
+
+            reader.set_offset(starts + info_offset);
+            let _size = reader.read_u32()?;
+            let page_size = reader.read_u16()? as u64;
+            let pointer_format = reader.read_u16()?;
+            let _segment_offset = reader.read_u64()?;
+            let _max_valid_pointer = reader.read_u32()?;
+            let page_count = reader.read_u16()?;
+
+            let stride = match pointer_format {
+                DYLD_CHAINED_PTR_64 | DYLD_CHAINED_PTR_64_OFFSET => 4,
+                DYLD_CHAINED_PTR_ARM64E
+                | DYLD_CHAINED_PTR_ARM64E_USERLAND
+                | DYLD_CHAINED_PTR_ARM64E_USERLAND24 => 8,
+                other => {
+                    return Err(ParseError::parse(format!(
+                        "Unsupported chained pointer format {}",
+                        other
+                    )))
+                }
+            };
+
+            for page in 0..page_count as u64 {
+                let page_start = reader.read_u16()?;
+                if page_start == DYLD_CHAINED_PTR_START_NONE {
+                    continue;
+                }
+
+                let mut offset = (base_offset as u64)
+                    .checked_add(segment.fileoff)
+                    .and_then(|offset| offset.checked_add(page * page_size + page_start as u64))
+                    .ok_or_else(|| ParseError::overflow("chained fixup offset"))?;
+                loop {
+                    let raw = BinaryReader::new_at(&self.data, offset as usize, true).read_u64()?;
+                    let (value, next) = Self::decode_chained_pointer(raw, pointer_format, image_base);
+                    patches.push((offset as usize, value));
+                    if next == 0 {
+                        break;
+                    }
+                    offset = offset
+                        .checked_add(next * stride)
+                        .ok_or_else(|| ParseError::overflow("chained fixup offset"))?;
+                }
+            }
+        }
+
+        for &(offset, value) in &patches {
+            self.data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+        }
+
+        Ok(patches.len())
+    }
+
+    /// Decode one chained pointer into its resolved value and stride count to
+    /// the next fixup; binds resolve to their addend
+    fn decode_chained_pointer(raw: u64, pointer_format: u16, image_base: u64) -> (u64, u64) {
+        match pointer_format {
+            DYLD_CHAINED_PTR_64 | DYLD_CHAINED_PTR_64_OFFSET => {
+                let next = (raw >> 51) & 0xFFF;
+                let value = if raw >> 63 != 0 {
+                    (raw >> 24) & 0xFF
+                } else {
+                    let target = raw & 0xF_FFFF_FFFF;
+                    let high8 = (raw >> 36) & 0xFF;
+                    let target = if pointer_format == DYLD_CHAINED_PTR_64_OFFSET {
+                        target + image_base
+                    } else {
+                        target
+                    };
+                    (high8 << 56) | target
+                };
+                (value, next)
+            }
+            _ => {
+                let next = (raw >> 51) & 0x7FF;
+                let auth = raw >> 63 != 0;
+                let bind = (raw >> 62) & 1 != 0;
+                let value = match (auth, bind) {
+                    // Authenticated binds carry no addend
+                    (true, true) => 0,
+                    (false, true) => {
+                        let addend = (raw >> 32) & 0x7FFFF;
+                        // Sign-extend the 19-bit addend
+                        ((addend << 45) as i64 >> 45) as u64
+                    }
+                    (true, false) => (raw & 0xFFFF_FFFF) + image_base,
+                    (false, false) => {
+                        let target = raw & 0x7FF_FFFF_FFFF;
+                        let high8 = (raw >> 43) & 0xFF;
+                        let target = if pointer_format == DYLD_CHAINED_PTR_ARM64E {
+                            target
+                        } else {
+                            target + image_base
+                        };
+                        (high8 << 56) | target
+                    }
+                };
+                (value, next)
+            }
+        }
+    }
+
+    /// Walk the export trie, producing one symbol per exported address
+    fn parse_export_trie(&self, trie: &[u8]) -> ParseResult<Vec<Symbol>> {
+        let mut symbols = Vec::new();
+        let mut visited = std::collections::HashSet::new();
+        let mut pending = vec![(0usize, String::new())];
+
+        while let Some((node, prefix)) = pending.pop() {
+            // Malformed tries can loop back on themselves
+            if !visited.insert(node) {
+                continue;
+            }
+
+            let mut cursor = node;
+            let terminal_size = read_uleb128(trie, &mut cursor)? as usize;
+            let children = cursor
+                .checked_add(terminal_size)
+                .ok_or_else(|| ParseError::overflow("export trie node offset"))?;
+            if terminal_size > 0 {
+                let flags = read_uleb128(trie, &mut cursor)?;
+                if flags & EXPORT_SYMBOL_FLAGS_REEXPORT == 0 {
+                    let offset = read_uleb128(trie, &mut cursor)?;
+                    if flags & EXPORT_SYMBOL_FLAGS_STUB_AND_RESOLVER != 0 {
+                        let _resolver = read_uleb128(trie, &mut cursor)?;
+                    }
+
+                    let address = Address::new(self.text_base.as_u64().wrapping_add(offset));
+                    let executable = self
+                        .sections
+                        .iter()
+                        .any(|s| s.characteristics.contains(SectionFlags::EXECUTE) && s.contains_va(address.as_u64()));
+                    symbols.push(Symbol {
+                        name: prefix.clone(),
+                        address,
+                        size: None,
+                        symbol_type: if executable { SymbolType::Function } else { SymbolType::Object },
+                        type_name: None,
+                    });
+                }
+            }
+
+            cursor = children;
+            let child_count = *trie.get(cursor).ok_or_else(|| ParseError::truncated(1, 0))?;
+            cursor += 1;
+            for _ in 0..child_count {
+                let mut edge_reader = BinaryReader::new_at(trie, cursor, true);
+                let edge = edge_reader
+                    .read_cstring(trie.len())
+                    .map_err(|_| ParseError::parse("Unterminated export trie edge"))?;
+                cursor = edge_reader.offset();
+                let child = read_uleb128(trie, &mut cursor)? as usize;
+                pending.push((child, format!("{}{}", prefix, edge)));
+            }
+        }
+
+        symbols.sort_by_key(|symbol| symbol.address.as_u64());
+        Ok(symbols)
     }
 
     fn parse_symbols(
@@ -319,16 +756,11 @@ impl MachOFile {
                 continue;
             }
 
-            let name_offset = strtab_offset + n_strx as usize;
-            let mut end = name_offset;
-            while end < data.len() && data[end] != 0 {
-                end += 1;
-            }
-            let name = String::from_utf8_lossy(&data[name_offset..end]).to_string();
-
-            if name.is_empty() {
-                continue;
-            }
+            let name_offset = strtab_offset as u64 + n_strx as u64;
+            let name = match reader.string_at(name_offset, strtab_size - n_strx as usize) {
+                Ok(name) if !name.is_empty() => name,
+                _ => continue,
+            };
 
             // Determine symbol type from n_type
             let symbol_type = if n_type & 0x0e == 0x0e {
@@ -344,6 +776,7 @@ impl MachOFile {
                 address: Address::new(n_value),
                 size: None,
                 symbol_type,
+                type_name: None,
             });
         }
 
@@ -351,6 +784,23 @@ impl MachOFile {
     }
 }
 
+/// Read an unsigned LEB128 value, advancing `cursor`
+fn read_uleb128(data: &[u8], cursor: &mut usize) -> ParseResult<u64> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let byte = *data.get(*cursor).ok_or_else(|| ParseError::truncated(1, 0))?;
+        *cursor += 1;
+        if shift < 64 {
+            value |= ((byte & 0x7F) as u64) << shift;
+        }
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
 impl BinaryFile for MachOFile {
     fn format(&self) -> BinaryFormat {
         BinaryFormat::MachO
@@ -385,62 +835,277 @@ impl BinaryFile for MachOFile {
         &self.symbols
     }
 
-    fn va_to_offset(&self, va: Address) -> Option<u64> {
-        for section in &self.sections {
-            let section_va_start = section.virtual_address.as_u64();
-            let section_va_end = section_va_start + section.virtual_size;
+    fn functions(&self) -> &[Function] {
+        &self.functions
+    }
 
-            if va.as_u64() >= section_va_start && va.as_u64() < section_va_end {
-                let offset_in_section = va.as_u64() - section_va_start;
-                return Some(section.raw_offset + offset_in_section);
-            }
+    fn add_symbols(&mut self, symbols: Vec<Symbol>) -> usize {
+        merge_symbols(&mut self.symbols, symbols)
+    }
+
+    fn libraries(&self) -> Vec<String> {
+        self.libraries.clone()
+    }
+
+    fn va_to_offset(&self, va: Address) -> Option<u64> {
+        let section = self.sections.iter().find(|s| s.contains_va(va.as_u64()))?;
+        let delta = va.as_u64() - section.virtual_address.as_u64();
+        // Zerofill sections only exist in memory
+        if delta >= section.raw_size {
+            return None;
         }
-        None
+        section.raw_offset.checked_add(delta)
     }
 
     fn offset_to_va(&self, offset: u64) -> Option<Address> {
-        for section in &self.sections {
-            let raw_start = section.raw_offset;
-            let raw_end = raw_start + section.raw_size;
-
-            if offset >= raw_start && offset < raw_end {
-                let section_offset = offset - raw_start;
-                return Some(Address::new(
-                    section.virtual_address.as_u64() + section_offset,
-                ));
-            }
-        }
-        None
+        let section = self.sections.iter().find(|s| s.contains_offset(offset))?;
+        Some(Address::new(section.virtual_address.as_u64().wrapping_add(offset - section.raw_offset)))
     }
 
     fn read_va(&self, va: Address, size: usize) -> ParseResult<&[u8]> {
         let offset = self
             .va_to_offset(va)
-            .ok_or_else(|| ParseError::AddressOutOfBounds(va.as_u64()))? as usize;
-
-        if offset + size > self.data.len() {
-            return Err(ParseError::AddressOutOfBounds(va.as_u64()));
-        }
-
-        Ok(&self.data[offset..offset + size])
+            .ok_or_else(|| ParseError::AddressOutOfBounds(va.as_u64()))?;
+        checked_slice(&self.data, offset, size as u64).map_err(|_| ParseError::AddressOutOfBounds(va.as_u64()))
     }
 
     fn read_string_va(&self, va: Address, max_len: usize) -> ParseResult<String> {
         let offset = self
             .va_to_offset(va)
-            .ok_or_else(|| ParseError::AddressOutOfBounds(va.as_u64()))? as usize;
+            .ok_or_else(|| ParseError::AddressOutOfBounds(va.as_u64()))?;
+        BinaryReader::new(&self.data, true)
+            .string_at(offset, max_len)
+            .map_err(|_| ParseError::AddressOutOfBounds(va.as_u64()))
+    }
+
+    fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
 
-        let end = (offset + max_len).min(self.data.len());
-        for i in offset..end {
-            if self.data[i] == 0 {
-                return Ok(String::from_utf8_lossy(&self.data[offset..i]).to_string());
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::put;
+
+    const TEXT_BASE: u64 = 0x1_0000_0000;
+
+    /// arm64 image with `__TEXT`, `__DATA_CONST` holding three chained
+    /// pointers (`pointers`), and `__LINKEDIT` holding the fixups and export trie
+    fn sample_macho(pointer_format: u16, pointers: [u64; 3]) -> Vec<u8> {
+        let mut image = vec![0u8; 0x3000];
+        put(&mut image, 0, &MH_MAGIC_64.to_le_bytes());
+        put(&mut image, 4, &CPU_TYPE_ARM64.to_le_bytes());
+        put(&mut image, 12, &6u32.to_le_bytes()); // MH_DYLIB
+        put(&mut image, 16, &6u32.to_le_bytes());
+
+        let mut cursor = 32;
+        for (segment, section, index, prot) in [
+            ("__TEXT", Some("__text"), 0u64, VM_PROT_READ | VM_PROT_EXECUTE),
+            ("__DATA_CONST", Some("__const"), 1, VM_PROT_READ | VM_PROT_WRITE),
+            ("__LINKEDIT", None, 2, VM_PROT_READ),
+        ] {
+            let nsects = section.is_some() as u32;
+            put(&mut image, cursor, &LC_SEGMENT_64.to_le_bytes());
+            put(&mut image, cursor + 4, &(72 + 80 * nsects).to_le_bytes());
+            put(&mut image, cursor + 8, segment.as_bytes());
+            put(&mut image, cursor + 24, &(TEXT_BASE + index * 0x1000).to_le_bytes());
+            put(&mut image, cursor + 32, &0x1000u64.to_le_bytes());
+            put(&mut image, cursor + 40, &(index * 0x1000).to_le_bytes());
+            put(&mut image, cursor + 48, &0x1000u64.to_le_bytes());
+            put(&mut image, cursor + 60, &prot.to_le_bytes());
+            put(&mut image, cursor + 64, &nsects.to_le_bytes());
+            if let Some(section) = section {
+                let header = cursor + 72;
+                let offset = index * 0x1000 + if index == 0 { 0x800 } else { 0 };
+                put(&mut image, header, section.as_bytes());
+                put(&mut image, header + 16, segment.as_bytes());
+                put(&mut image, header + 32, &(TEXT_BASE + offset).to_le_bytes());
+                put(&mut image, header + 40, &0x100u64.to_le_bytes());
+                put(&mut image, header + 48, &(offset as u32).to_le_bytes());
             }
+            cursor += 72 + 80 * nsects as usize;
+        }
+        for (cmd, dataoff, datasize) in [
+            (LC_DYLD_CHAINED_FIXUPS, 0x2000u32, 0x100u32),
+            (LC_DYLD_EXPORTS_TRIE, 0x2100, 0x40),
+            (LC_FUNCTION_STARTS, 0x2180, 0x10),
+        ] {
+            put(&mut image, cursor, &cmd.to_le_bytes());
+            put(&mut image, cursor + 4, &16u32.to_le_bytes());
+            put(&mut image, cursor + 8, &dataoff.to_le_bytes());
+            put(&mut image, cursor + 12, &datasize.to_le_bytes());
+            cursor += 16;
+        }
+        put(&mut image, 20, &(cursor as u32 - 32).to_le_bytes());
+
+        // dyld_chained_fixups_header, starts_in_image, starts_in_segment
+        put(&mut image, 0x2004, &0x20u32.to_le_bytes());
+        put(&mut image, 0x2020, &3u32.to_le_bytes());
+        put(&mut image, 0x2028, &0x10u32.to_le_bytes());
+        put(&mut image, 0x2030, &24u32.to_le_bytes());
+        put(&mut image, 0x2034, &0x1000u16.to_le_bytes());
+        put(&mut image, 0x2036, &pointer_format.to_le_bytes());
+        put(&mut image, 0x2038, &0x1000u64.to_le_bytes());
+        put(&mut image, 0x2044, &1u16.to_le_bytes());
+        put(&mut image, 0x2046, &0u16.to_le_bytes());
+
+        for (i, pointer) in pointers.iter().enumerate() {
+            put(&mut image, 0x1000 + i * 8, &pointer.to_le_bytes());
         }
 
-        Ok(String::from_utf8_lossy(&self.data[offset..end]).to_string())
+        // Function starts at __TEXT + 0x800 and + 0x840
+        put(&mut image, 0x2180, &[0x80, 0x10, 0x40, 0x00]);
+
+        // Export trie: "_il2cpp_init" at __TEXT + 0x800
+        let mut trie = vec![0x00, 0x01];
+        trie.extend_from_slice(b"_il2cpp_init\0");
+        trie.push(trie.len() as u8 + 1);
+        trie.extend_from_slice(&[0x03, 0x00, 0x80, 0x10, 0x00]);
+        put(&mut image, 0x2100, &trie);
+
+        image
     }
 
-    fn data(&self) -> &[u8] {
-        &self.data
+    #[test]
+    fn test_zerofill_sections() {
+        // Turn __DATA_CONST,__const into a zerofill section at file offset 0
+        let mut image = sample_macho(DYLD_CHAINED_PTR_64, [0; 3]);
+        let header = 32 + 152 + 72;
+        assert_eq!(&image[header..header + 7], b"__const");
+        put(&mut image, header + 48, &0u32.to_le_bytes());
+        put(&mut image, header + 64, &S_ZEROFILL.to_le_bytes());
+
+        let macho = MachOFile::parse(&image).unwrap();
+        let bss = macho.find_section("__DATA_CONST,__const").unwrap();
+        assert_eq!((bss.raw_offset, bss.raw_size), (0, 0));
+        assert_eq!(macho.va_to_offset(Address::new(TEXT_BASE + 0x1000)), None);
+        assert!(crate::strings::extract(&macho, &Default::default())
+            .iter()
+            .all(|s| !bss.contains_va(s.address.as_u64())));
+    }
+
+    #[test]
+    fn test_chained_fixups_64() {
+        let next = |n: u64| n << 51;
+        let macho = MachOFile::parse(&sample_macho(
+            DYLD_CHAINED_PTR_64,
+            [
+                (TEXT_BASE + 0x800) | next(2),
+                (1 << 63) | (4 << 24) | next(2), // bind, addend 4
+                TEXT_BASE + 0x900,
+            ],
+        ))
+        .unwrap();
+
+        assert_eq!(macho.fixups_applied(), 3);
+        let pointers = macho.read_va(Address::new(TEXT_BASE + 0x1000), 24).unwrap();
+        assert_eq!(&pointers[..8], &(TEXT_BASE + 0x800).to_le_bytes());
+        assert_eq!(&pointers[8..16], &4u64.to_le_bytes());
+        assert_eq!(&pointers[16..], &(TEXT_BASE + 0x900).to_le_bytes());
+    }
+
+    #[test]
+    fn test_chained_fixups_arm64e() {
+        let next = |n: u64| n << 51;
+        let macho = MachOFile::parse(&sample_macho(
+            DYLD_CHAINED_PTR_ARM64E,
+            [
+                (TEXT_BASE + 0x800) | next(1),
+                (1 << 62) | next(1),                // bind
+                (1 << 63) | (0x1234 << 32) | 0x900, // authenticated rebase
+            ],
+        ))
+        .unwrap();
+
+        let pointers = macho.read_va(Address::new(TEXT_BASE + 0x1000), 24).unwrap();
+        assert_eq!(&pointers[..8], &(TEXT_BASE + 0x800).to_le_bytes());
+        assert_eq!(&pointers[8..16], &0u64.to_le_bytes());
+        assert_eq!(&pointers[16..], &(TEXT_BASE + 0x900).to_le_bytes());
+    }
+
+    #[test]
+    fn test_export_trie() {
+        let macho = MachOFile::parse(&sample_macho(DYLD_CHAINED_PTR_64, [0; 3])).unwrap();
+        let init = macho.find_symbol("_il2cpp_init").unwrap();
+        assert_eq!(init.address, Address::new(TEXT_BASE + 0x800));
+        assert_eq!(init.symbol_type, SymbolType::Function);
+    }
+
+    #[test]
+    fn test_function_starts() {
+        let macho = MachOFile::parse(&sample_macho(DYLD_CHAINED_PTR_64, [0; 3])).unwrap();
+        let functions: Vec<(u64, u64)> = macho
+            .functions()
+            .iter()
+            .map(|f| (f.start.as_u64() - TEXT_BASE, f.end.as_u64() - TEXT_BASE))
+            .collect();
+        // The last function runs to the end of __text
+        assert_eq!(functions, [(0x800, 0x840), (0x840, 0x900)]);
+    }
+
+    #[test]
+    fn test_malformed_load_commands() {
+        // Unknown pointer format, a trie edge past its end, and function
+        // starts past the end of the file
+        let mut image = sample_macho(99, [TEXT_BASE + 0x800; 3]);
+        put(&mut image, 0x2100 + 16, &[0x7F]);
+        image.truncate(0x2188);
+
+        let macho = MachOFile::parse(&image).unwrap();
+        assert_eq!(macho.fixups_applied(), 0);
+        assert!(macho.find_symbol("_il2cpp_init").is_none());
+        assert!(macho.functions().is_empty());
+        assert_eq!(macho.sections().len(), 2);
+    }
+
+    /// Universal binary holding an arm64 and an arm64e slice
+    fn sample_fat() -> Vec<u8> {
+        let arm64 = sample_macho(DYLD_CHAINED_PTR_64, [TEXT_BASE + 0x800, 0, 0]);
+        let mut arm64e = sample_macho(DYLD_CHAINED_PTR_ARM64E, [TEXT_BASE + 0x900, 0, 0]);
+        put(&mut arm64e, 8, &(0x8000_0000 | CPU_SUBTYPE_ARM64E).to_le_bytes());
+
+        let mut image = vec![0u8; 0x4000];
+        put(&mut image, 0, &FAT_MAGIC.to_be_bytes());
+        put(&mut image, 4, &2u32.to_be_bytes());
+        for (i, slice) in [&arm64, &arm64e].iter().enumerate() {
+            let entry = 8 + i * 20;
+            let offset = image.len() as u32;
+            put(&mut image, entry, &CPU_TYPE_ARM64.to_be_bytes());
+            put(&mut image, entry + 4, &slice[8..12].iter().rev().copied().collect::<Vec<u8>>());
+            put(&mut image, entry + 8, &offset.to_be_bytes());
+            put(&mut image, entry + 12, &(slice.len() as u32).to_be_bytes());
+            put(&mut image, entry + 16, &14u32.to_be_bytes());
+            image.extend_from_slice(slice);
+            image.resize(image.len().next_multiple_of(0x4000), 0);
+        }
+        image
+    }
+
+    #[test]
+    fn test_fat_slice_selection() {
+        let fat = sample_fat();
+        let names: Vec<String> = MachOFile::slices(&fat).unwrap().iter().map(FatSlice::name).collect();
+        assert_eq!(names, ["arm64", "arm64e"]);
+
+        let pointer = |macho: &MachOFile| macho.read_va(Address::new(TEXT_BASE + 0x1000), 8).unwrap().to_vec();
+        let default = MachOFile::parse(&fat).unwrap();
+        assert_eq!(pointer(&default), (TEXT_BASE + 0x800).to_le_bytes());
+        let arm64e = MachOFile::parse_arch(&fat, "arm64e").unwrap();
+        assert_eq!(pointer(&arm64e), (TEXT_BASE + 0x900).to_le_bytes());
+        assert!(arm64e.find_symbol("_il2cpp_init").is_some());
+
+        assert!(matches!(
+            MachOFile::parse_arch(&fat, "x86_64"),
+            Err(ParseError::UnsupportedArchitecture(_))
+        ));
+    }
+
+    #[test]
+    fn test_truncated_fat_header() {
+        let fat = sample_fat();
+        for len in [6, 20, 0x4002] {
+            assert!(MachOFile::parse(&fat[..len]).is_err());
+        }
     }
 }
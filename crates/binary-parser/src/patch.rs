@@ -0,0 +1,286 @@
+//! Byte patching
+//!
+//! Records patches at virtual addresses, checks them against section bounds
+//! and writes a patched copy of the original file. PE checksums are fixed up
+//! when the header carries one (ELF and Mach-O have none). Every build also
+//! yields a [`PatchManifest`] holding the original bytes and the hashes of the
+//! file before and after, so patches can be reverted or re-applied to exactly
+//! the build they were made for.
+
+use crate::common::BinaryFile;
+use crate::error::{ParseError, ParseResult};
+use crate::pe::PeFile;
+use endfield_core::{Address, BinaryFormat};
+use endfield_crypto::{HashAlgorithm, Hasher};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Algorithm used for the manifest hashes
+const MANIFEST_ALGORITHM: &str = "blake3";
+
+/// Bytes replaced at one location
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Patch {
+    /// Virtual address of the first byte
+    pub address: Address,
+    /// File offset of the first byte
+    pub offset: u64,
+    /// Bytes before patching
+    #[serde(with = "hex_bytes")]
+    pub original: Vec<u8>,
+    /// Bytes after patching
+    #[serde(with = "hex_bytes")]
+    pub patched: Vec<u8>,
+}
+
+/// Revertible record of the patches applied to one build
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatchManifest {
+    /// Format of the patched binary
+    pub format: BinaryFormat,
+    /// Hash algorithm of `source_hash` and `patched_hash`
+    pub algorithm: String,
+    /// Hash of the unpatched file
+    pub source_hash: String,
+    /// Hash of the patched file
+    pub patched_hash: String,
+    /// Patches in file order, including any checksum fixup
+    pub patches: Vec<Patch>,
+}
+
+impl PatchManifest {
+    /// Patch `data`, which must be the build the manifest was made for
+    pub fn apply(&self, data: &mut [u8]) -> ParseResult<()> {
+        self.verify(data, &self.source_hash)?;
+        for patch in &self.patches {
+            write(data, patch.offset, &patch.patched)?;
+        }
+        Ok(())
+    }
+
+    /// Restore the original bytes of `data`, which must be the patched build
+    pub fn revert(&self, data: &mut [u8]) -> ParseResult<()> {
+        self.verify(data, &self.patched_hash)?;
+        for patch in &self.patches {
+            write(data, patch.offset, &patch.original)?;
+        }
+        Ok(())
+    }
+
+    /// Save as JSON
+    pub fn save(&self, path: &Path) -> ParseResult<()> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| ParseError::parse(e.to_string()))?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Load from JSON
+    pub fn load(path: &Path) -> ParseResult<Self> {
+        let json = std::fs::read_to_string(path)?;
+        serde_json::from_str(&json).map_err(|e| ParseError::parse(e.to_string()))
+    }
+
+    fn verify(&self, data: &[u8], expected: &str) -> ParseResult<()> {
+        if self.algorithm != MANIFEST_ALGORITHM {
+            return Err(ParseError::parse(format!("Unsupported manifest hash: {}", self.algorithm)));
+        }
+        let actual = hash(data);
+        if actual != expected {
+            return Err(ParseError::ManifestMismatch {
+                expected: expected.to_string(),
+                actual,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Collects patches against a parsed binary
+pub struct PatchBuilder<'a, B: BinaryFile + ?Sized> {
+    binary: &'a B,
+    /// Sorted by offset, never overlapping
+    patches: Vec<(Address, u64, Vec<u8>)>,
+}
+
+impl<'a, B: BinaryFile + ?Sized> PatchBuilder<'a, B> {
+    /// Start patching `binary`
+    pub fn new(binary: &'a B) -> Self {
+        Self { binary, patches: Vec::new() }
+    }
+
+    /// Replace the bytes at `va`
+    ///
+    /// The patch must lie inside a single section's file-backed bytes and
+    /// must not overlap an earlier patch.
+    pub fn patch(&mut self, va: Address, bytes: &[u8]) -> ParseResult<&mut Self> {
+        if bytes.is_empty() {
+            return Err(ParseError::parse(format!("Empty patch at {}", va)));
+        }
+        let len = bytes.len() as u64;
+        let last = va
+            .as_u64()
+            .checked_add(len - 1)
+            .ok_or_else(|| ParseError::overflow("patch end address"))?;
+        let section = self
+            .binary
+            .sections()
+            .iter()
+            .find(|s| s.contains_va(va.as_u64()))
+            .ok_or(ParseError::AddressOutOfBounds(va.as_u64()))?;
+        let offset = self
+            .binary
+            .va_to_offset(va)
+            .ok_or(ParseError::AddressOutOfBounds(va.as_u64()))?;
+        if !section.contains_va(last) || !section.contains_offset(offset) || !section.contains_offset(offset + len - 1) {
+            return Err(ParseError::InvalidSection(format!(
+                "{} byte patch at {} extends past the file data of {}",
+                len, va, section.name
+            )));
+        }
+
+        let index = self.patches.partition_point(|(_, start, _)| *start < offset);
+        let overlaps_previous = index
+            .checked_sub(1)
+            .is_some_and(|previous| self.patches[previous].1 + self.patches[previous].2.len() as u64 > offset);
+        let overlaps_next = self.patches.get(index).is_some_and(|(_, start, _)| *start < offset + len);
+        if overlaps_previous || overlaps_next {
+            return Err(ParseError::parse(format!("Patch at {} overlaps an earlier patch", va)));
+        }
+
+        self.patches.insert(index, (va, offset, bytes.to_vec()));
+        Ok(self)
+    }
+
+    /// Number of recorded patches
+    pub fn len(&self) -> usize {
+        self.patches.len()
+    }
+
+    /// Check whether no patches are recorded
+    pub fn is_empty(&self) -> bool {
+        self.patches.is_empty()
+    }
+
+    /// Patch a copy of `original`, the unmodified file the binary was parsed from
+    ///
+    /// The parsed image may hold applied relocations or fixups, so its own
+    /// bytes are never written out.
+    pub fn build(&self, original: &[u8]) -> ParseResult<(Vec<u8>, PatchManifest)> {
+        let mut data = original.to_vec();
+        let mut patches = Vec::with_capacity(self.patches.len() + 1);
+        for (address, offset, bytes) in &self.patches {
+            let before = crate::common::checked_slice(original, *offset, bytes.len() as u64)?.to_vec();
+            write(&mut data, *offset, bytes)?;
+            patches.push(Patch {
+                address: *address,
+                offset: *offset,
+                original: before,
+                patched: bytes.clone(),
+            });
+        }
+
+        // A zero checksum is never checked by the loader, so leave it alone
+        if self.binary.format() == BinaryFormat::PE {
+            if let Some(offset) = PeFile::checksum_offset(&data) {
+                let before = data[offset..offset + 4].to_vec();
+                if before != [0; 4] {
+                    let checksum = PeFile::compute_checksum(&data).unwrap_or(0).to_le_bytes();
+                    data[offset..offset + 4].copy_from_slice(&checksum);
+                    // Headers are mapped at the image base with RVA == file offset
+                    patches.push(Patch {
+                        address: Address::new(self.binary.image_base().as_u64().wrapping_add(offset as u64)),
+                        offset: offset as u64,
+                        original: before,
+                        patched: checksum.to_vec(),
+                    });
+                }
+            }
+        }
+        patches.sort_by_key(|patch| patch.offset);
+
+        let manifest = PatchManifest {
+            format: self.binary.format(),
+            algorithm: MANIFEST_ALGORITHM.to_string(),
+            source_hash: hash(original),
+            patched_hash: hash(&data),
+            patches,
+        };
+        Ok((data, manifest))
+    }
+
+    /// Patch the file at `input`, write the result to `output` and return the manifest
+    pub fn write(&self, input: &Path, output: &Path) -> ParseResult<PatchManifest> {
+        let original = std::fs::read(input)?;
+        let (data, manifest) = self.build(&original)?;
+        std::fs::write(output, data)?;
+        Ok(manifest)
+    }
+}
+
+fn hash(data: &[u8]) -> String {
+    Hasher::new(HashAlgorithm::Blake3).hash(data).to_hex()
+}
+
+fn write(data: &mut [u8], offset: u64, bytes: &[u8]) -> ParseResult<()> {
+    crate::common::checked_slice(data, offset, bytes.len() as u64)?;
+    let start = offset as usize;
+    data[start..start + bytes.len()].copy_from_slice(bytes);
+    Ok(())
+}
+
+/// Serialize byte vectors as hex strings
+mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        hex::decode(text).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_binary;
+    use crate::test_util::sample_pe64;
+
+    #[test]
+    fn test_patch_round_trip() {
+        // Give the seed a checksum so the fixup runs
+        let mut original = sample_pe64();
+        let checksum_offset = PeFile::checksum_offset(&original).unwrap();
+        original[checksum_offset..checksum_offset + 4].copy_from_slice(&1u32.to_le_bytes());
+
+        let binary = parse_binary(&original).unwrap();
+        let entry = binary.entry_point();
+        let text = binary.find_section(".text").unwrap().clone();
+        let mut builder = PatchBuilder::new(binary.as_ref());
+        builder.patch(entry, &[0xC3]).unwrap();
+
+        assert!(builder.patch(entry, &[0x90, 0x90]).is_err());
+        assert!(builder.patch(Address::new(0x1000), &[0x90]).is_err());
+        let end = text.virtual_address.as_u64() + text.raw_size;
+        assert!(builder.patch(Address::new(end - 1), &[0x90, 0x90]).is_err());
+
+        let (patched, manifest) = builder.build(&original).unwrap();
+        let offset = binary.va_to_offset(entry).unwrap() as usize;
+        assert_eq!(patched[offset], 0xC3);
+        assert_eq!(manifest.patches.len(), 2);
+        let checksum = u32::from_le_bytes(patched[checksum_offset..checksum_offset + 4].try_into().unwrap());
+        assert_eq!(Some(checksum), PeFile::compute_checksum(&patched));
+
+        let mut data = patched.clone();
+        assert!(manifest.apply(&mut data).is_err());
+        manifest.revert(&mut data).unwrap();
+        assert_eq!(data, original);
+        manifest.apply(&mut data).unwrap();
+        assert_eq!(data, patched);
+
+        let json = serde_json::to_string(&manifest).unwrap();
+        assert_eq!(serde_json::from_str::<PatchManifest>(&json).unwrap(), manifest);
+    }
+}
@@ -1,7 +1,11 @@
 //! PE (Portable Executable) format parser for Windows binaries
 
-use crate::common::{BinaryFile, BinaryReader, Section, SectionFlags, Symbol, SymbolType};
+use crate::common::{
+    build_functions, checked_slice, merge_symbols, or_empty, BinaryFile, BinaryReader, Function, ImageLayout, Section,
+    SectionFlags, Symbol, SymbolType,
+};
 use crate::error::{ParseError, ParseResult};
+use crate::image::ImageData;
 use endfield_core::{Address, Architecture, BinaryFormat, Platform};
 
 /// DOS header magic
@@ -12,35 +16,130 @@ const PE_SIGNATURE: u32 = 0x00004550; // "PE\0\0"
 
 /// PE machine types
 const IMAGE_FILE_MACHINE_I386: u16 = 0x014c;
-const IMAGE_FILE_MACHINE_AMD64: u16 = 0x8664;
+pub(crate) const IMAGE_FILE_MACHINE_AMD64: u16 = 0x8664;
 const IMAGE_FILE_MACHINE_ARM: u16 = 0x01c0;
 const IMAGE_FILE_MACHINE_ARM64: u16 = 0xaa64;
 
 /// PE optional header magic
 const PE32_MAGIC: u16 = 0x10b;
-const PE32PLUS_MAGIC: u16 = 0x20b;
+pub(crate) const PE32PLUS_MAGIC: u16 = 0x20b;
 
 /// Section characteristics
-const IMAGE_SCN_MEM_EXECUTE: u32 = 0x20000000;
-const IMAGE_SCN_MEM_READ: u32 = 0x40000000;
+pub(crate) const IMAGE_SCN_MEM_EXECUTE: u32 = 0x20000000;
+pub(crate) const IMAGE_SCN_MEM_READ: u32 = 0x40000000;
 const IMAGE_SCN_MEM_WRITE: u32 = 0x80000000;
-const IMAGE_SCN_CNT_INITIALIZED_DATA: u32 = 0x00000040;
+pub(crate) const IMAGE_SCN_CNT_INITIALIZED_DATA: u32 = 0x00000040;
 const IMAGE_SCN_CNT_UNINITIALIZED_DATA: u32 = 0x00000080;
 
+/// Data directory indices
+pub const IMAGE_DIRECTORY_ENTRY_EXPORT: usize = 0;
+pub const IMAGE_DIRECTORY_ENTRY_IMPORT: usize = 1;
+pub const IMAGE_DIRECTORY_ENTRY_EXCEPTION: usize = 3;
+pub const IMAGE_DIRECTORY_ENTRY_BASERELOC: usize = 5;
+pub const IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR: usize = 14;
+
+/// Maximum number of data directories in the optional header
+const IMAGE_NUMBEROF_DIRECTORY_ENTRIES: usize = 16;
+
+/// Base relocation types
+const IMAGE_REL_BASED_ABSOLUTE: u8 = 0;
+const IMAGE_REL_BASED_HIGHLOW: u8 = 3;
+const IMAGE_REL_BASED_DIR64: u8 = 10;
+
+/// Import lookup table ordinal flags
+const IMAGE_ORDINAL_FLAG32: u64 = 0x8000_0000;
+pub(crate) const IMAGE_ORDINAL_FLAG64: u64 = 0x8000_0000_0000_0000;
+
+/// Exported function or data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Export {
+    /// Export name, `None` for ordinal-only exports
+    pub name: Option<String>,
+    /// Biased ordinal
+    pub ordinal: u32,
+    /// Virtual address, zero for forwarders
+    pub address: Address,
+    /// Forwarder target such as `NTDLL.RtlAllocateHeap`
+    pub forwarder: Option<String>,
+}
+
+/// Imported function
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import {
+    /// Name of the imported DLL
+    pub dll: String,
+    /// Imported name, `None` when imported by ordinal
+    pub name: Option<String>,
+    /// Ordinal when imported by ordinal
+    pub ordinal: Option<u16>,
+    /// Virtual address of the IAT slot the loader fills in
+    pub iat_address: Address,
+}
+
+/// Kind of fixup applied by a base relocation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelocationType {
+    /// 32-bit pointer
+    HighLow,
+    /// 64-bit pointer
+    Dir64,
+    /// Any other `IMAGE_REL_BASED_*` value, not applied when rebasing
+    Other(u8),
+}
+
+/// Entry from the `.reloc` base relocation directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Relocation {
+    /// Virtual address of the pointer to fix up
+    pub address: Address,
+    /// Kind of fixup
+    pub relocation_type: RelocationType,
+}
+
+/// Function entry from the `.pdata` exception directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuntimeFunction {
+    /// Function start address
+    pub begin: Address,
+    /// Function end address (exclusive), if known
+    pub end: Option<Address>,
+}
+
 /// Parsed PE file
 pub struct PeFile {
-    data: Vec<u8>,
+    data: ImageData,
     architecture: Architecture,
     image_base: Address,
     entry_point: Address,
     sections: Vec<Section>,
     symbols: Vec<Symbol>,
     is_64bit: bool,
+    data_directories: Vec<(u32, u32)>,
+    exports: Vec<Export>,
+    imports: Vec<Import>,
+    runtime_functions: Vec<RuntimeFunction>,
+    functions: Vec<Function>,
+    relocations: Vec<Relocation>,
+    layout: ImageLayout,
 }
 
 impl PeFile {
-    /// Parse a PE file from raw bytes
+    /// Parse a PE file from raw bytes, detecting memory dumps
     pub fn parse(data: &[u8]) -> ParseResult<Self> {
+        Self::parse_with_layout(data, None)
+    }
+
+    /// Parse a PE file with an explicit layout, or detect it when `None`
+    ///
+    /// Mapped images get their section headers repaired so that raw offsets
+    /// equal RVAs, both in the returned sections and in the header bytes.
+    pub fn parse_with_layout(data: &[u8], layout: Option<ImageLayout>) -> ParseResult<Self> {
+        Self::from_data(data.into(), layout)
+    }
+
+    /// Parse owned or memory-mapped bytes in place, without copying them
+    pub fn from_data(mut image: ImageData, layout: Option<ImageLayout>) -> ParseResult<Self> {
+        let data: &[u8] = &image;
         let mut reader = BinaryReader::new(data, true);
 
         // Parse DOS header
@@ -114,9 +213,33 @@ impl PeFile {
             reader.read_u32()? as u64
         };
 
+        reader.skip(4)?; // SectionAlignment
+        reader.skip(4)?; // FileAlignment
+        reader.skip(12)?; // OS, image and subsystem versions
+        reader.skip(4)?; // Win32VersionValue
+        reader.skip(4)?; // SizeOfImage
+        let size_of_headers = reader.read_u32()?;
+        reader.skip(4)?; // CheckSum
+        reader.skip(4)?; // Subsystem, DllCharacteristics
+        reader.skip(if is_64bit { 32 } else { 16 })?; // Stack and heap reserve/commit
+        reader.skip(4)?; // LoaderFlags
+        let number_of_rva_and_sizes = reader.read_u32()? as usize;
+
+        let section_header_offset = optional_header_offset + size_of_optional_header as usize;
+
+        // Never read past the optional header, whatever the count claims
+        let directories_room = section_header_offset.saturating_sub(reader.offset()) / 8;
+        let directory_count = number_of_rva_and_sizes
+            .min(IMAGE_NUMBEROF_DIRECTORY_ENTRIES)
+            .min(directories_room);
+        let mut data_directories = Vec::with_capacity(directory_count);
+        for _ in 0..directory_count {
+            let rva = reader.read_u32()?;
+            let size = reader.read_u32()?;
+            data_directories.push((rva, size));
+        }
+
         // Skip to section headers
-        let section_header_offset =
-            optional_header_offset + size_of_optional_header as usize;
         reader.set_offset(section_header_offset);
 
         // Parse section headers
@@ -154,9 +277,12 @@ impl PeFile {
                 flags |= SectionFlags::UNINITIALIZED;
             }
 
+            let virtual_address = image_base
+                .checked_add(virtual_address)
+                .ok_or_else(|| ParseError::overflow(format!("address of section {}", name)))?;
             sections.push(Section {
                 name,
-                virtual_address: Address::new(image_base + virtual_address),
+                virtual_address: Address::new(virtual_address),
                 virtual_size,
                 raw_offset: pointer_to_raw_data,
                 raw_size: size_of_raw_data,
@@ -164,24 +290,561 @@ impl PeFile {
             });
         }
 
-        // TODO: Parse export table for symbols
-        let symbols = Vec::new();
+        let layout = layout.unwrap_or_else(|| {
+            Self::detect_layout(data, &sections, image_base, size_of_headers)
+        });
+        if layout == ImageLayout::Mapped {
+            Self::repair_sections(&mut image, &mut sections, image_base, section_header_offset);
+        }
 
-        Ok(Self {
-            data: data.to_vec(),
+        let mut pe = Self {
+            data: image,
             architecture,
             image_base: Address::new(image_base),
-            entry_point: Address::new(image_base + address_of_entry_point as u64),
+            entry_point: Address::new(image_base.wrapping_add(address_of_entry_point as u64)),
             sections,
-            symbols,
+            symbols: Vec::new(),
             is_64bit,
-        })
+            data_directories,
+            exports: Vec::new(),
+            imports: Vec::new(),
+            runtime_functions: Vec::new(),
+            functions: Vec::new(),
+            relocations: Vec::new(),
+            layout,
+        };
+
+        pe.exports = or_empty("export directory", pe.parse_exports());
+        pe.imports = or_empty("import directory", pe.parse_imports());
+        pe.runtime_functions = or_empty("exception directory", pe.parse_runtime_functions());
+        pe.functions = build_functions(
+            pe.runtime_functions
+                .iter()
+                .map(|function| (function.begin.as_u64(), function.end.map(|end| end.as_u64())))
+                .collect(),
+            &pe.sections,
+        );
+        pe.relocations = or_empty("base relocation directory", pe.parse_relocations());
+        pe.symbols = pe.collect_symbols();
+
+        Ok(pe)
     }
 
-    /// Get the data directory entry
+    /// Get the data directory entry as `(rva, size)`, `None` if absent or empty
     pub fn get_data_directory(&self, index: usize) -> Option<(u32, u32)> {
-        // This would need to be implemented with proper parsing of the optional header
-        None
+        self.data_directories
+            .get(index)
+            .copied()
+            .filter(|&(rva, size)| rva != 0 && size != 0)
+    }
+
+    /// Get the export table
+    pub fn exports(&self) -> &[Export] {
+        &self.exports
+    }
+
+    /// Offset of the optional header's `CheckSum` field in raw PE bytes
+    ///
+    /// The field sits 64 bytes into the optional header for PE32 and PE32+ alike.
+    pub fn checksum_offset(data: &[u8]) -> Option<usize> {
+        let pe_offset = BinaryReader::new_at(data, 0x3C, true).read_u32().ok()? as usize;
+        let offset = pe_offset.checked_add(4 + 20 + 64)?;
+        (offset.checked_add(4)? <= data.len()).then_some(offset)
+    }
+
+    /// Image checksum of raw PE bytes, computed like `CheckSumMappedFile`
+    pub fn compute_checksum(data: &[u8]) -> Option<u32> {
+        let skip = Self::checksum_offset(data)?;
+        let mut sum = 0u64;
+        for (index, word) in data.chunks(2).enumerate() {
+            if (skip..skip + 4).contains(&(index * 2)) {
+                continue;
+            }
+            sum += u16::from_le_bytes([word[0], word.get(1).copied().unwrap_or(0)]) as u64;
+            sum = (sum & 0xFFFF) + (sum >> 16);
+        }
+        sum = (sum & 0xFFFF) + (sum >> 16);
+        Some((sum + data.len() as u64) as u32)
+    }
+
+    /// Get the import table
+    pub fn imports(&self) -> &[Import] {
+        &self.imports
+    }
+
+    /// Get the `.pdata` function entries
+    pub fn runtime_functions(&self) -> &[RuntimeFunction] {
+        &self.runtime_functions
+    }
+
+    /// Get the base relocations
+    pub fn relocations(&self) -> &[Relocation] {
+        &self.relocations
+    }
+
+    /// Create a copy of this file as if loaded at `image_base`
+    ///
+    /// Pointers covered by base relocations are patched, so `read_va` returns
+    /// runtime values, and every address (sections, symbols, exports, imports)
+    /// is moved by the same delta.
+    pub fn rebased(&self, image_base: Address) -> PeFile {
+        let delta = image_base.as_u64().wrapping_sub(self.image_base.as_u64());
+        let shift = |address: Address| Address::new(address.as_u64().wrapping_add(delta));
+
+        let mut data = self.data.to_vec();
+        for relocation in &self.relocations {
+            let size = match relocation.relocation_type {
+                RelocationType::HighLow => 4,
+                RelocationType::Dir64 => 8,
+                RelocationType::Other(_) => continue,
+            };
+            // Relocations in uninitialized data have nothing to patch on disk
+            let Some(offset) = self.va_to_offset(relocation.address) else {
+                continue;
+            };
+            let Some(bytes) = usize::try_from(offset)
+                .ok()
+                .and_then(|offset| data.get_mut(offset..offset.checked_add(size)?))
+            else {
+                continue;
+            };
+            if size == 4 {
+                let value = u32::from_le_bytes(bytes.try_into().unwrap()).wrapping_add(delta as u32);
+                bytes.copy_from_slice(&value.to_le_bytes());
+            } else {
+                let value = u64::from_le_bytes(bytes.try_into().unwrap()).wrapping_add(delta);
+                bytes.copy_from_slice(&value.to_le_bytes());
+            }
+        }
+
+        PeFile {
+            data: data.into(),
+            architecture: self.architecture,
+            image_base,
+            entry_point: shift(self.entry_point),
+            sections: self
+                .sections
+                .iter()
+                .map(|section| Section {
+                    virtual_address: shift(section.virtual_address),
+                    ..section.clone()
+                })
+                .collect(),
+            symbols: self
+                .symbols
+                .iter()
+                .map(|symbol| Symbol {
+                    address: shift(symbol.address),
+                    ..symbol.clone()
+                })
+                .collect(),
+            is_64bit: self.is_64bit,
+            data_directories: self.data_directories.clone(),
+            exports: self
+                .exports
+                .iter()
+                .map(|export| Export {
+                    address: if export.forwarder.is_some() { export.address } else { shift(export.address) },
+                    ..export.clone()
+                })
+                .collect(),
+            imports: self
+                .imports
+                .iter()
+                .map(|import| Import {
+                    iat_address: shift(import.iat_address),
+                    ..import.clone()
+                })
+                .collect(),
+            runtime_functions: self
+                .runtime_functions
+                .iter()
+                .map(|function| RuntimeFunction {
+                    begin: shift(function.begin),
+                    end: function.end.map(shift),
+                })
+                .collect(),
+            functions: self
+                .functions
+                .iter()
+                .map(|function| Function {
+                    start: shift(function.start),
+                    end: shift(function.end),
+                })
+                .collect(),
+            relocations: self
+                .relocations
+                .iter()
+                .map(|relocation| Relocation {
+                    address: shift(relocation.address),
+                    ..*relocation
+                })
+                .collect(),
+            layout: self.layout,
+        }
+    }
+
+    /// Guess whether `data` is a memory dump rather than an on-disk file
+    ///
+    /// A dump is at least as large as the mapped image, and the bytes right after
+    /// the headers are page padding rather than the first section's contents.
+    fn detect_layout(data: &[u8], sections: &[Section], image_base: u64, size_of_headers: u32) -> ImageLayout {
+        let rva = |section: &Section| section.virtual_address.as_u64() - image_base;
+
+        let image_end = sections
+            .iter()
+            .map(|s| rva(s) + s.virtual_size.min(s.raw_size))
+            .max()
+            .unwrap_or(0);
+        if sections.is_empty() || (data.len() as u64) < image_end {
+            return ImageLayout::File;
+        }
+
+        let Some(first) = sections
+            .iter()
+            .filter(|s| s.raw_size > 0 && s.raw_offset != rva(s))
+            .min_by_key(|s| s.raw_offset)
+        else {
+            // Raw offsets equal RVAs: both readings agree
+            return ImageLayout::File;
+        };
+
+        let probe = first.raw_size.min(first.virtual_size).min(0x200) as usize;
+        let raw_start = (first.raw_offset as usize).max(size_of_headers as usize);
+        let raw = data.get(raw_start..raw_start + probe);
+        let mapped = data.get(rva(first) as usize..rva(first) as usize + probe);
+        match (raw, mapped) {
+            (Some(raw), Some(mapped)) if raw.iter().all(|&b| b == 0) && mapped.iter().any(|&b| b != 0) => {
+                ImageLayout::Mapped
+            }
+            _ => ImageLayout::File,
+        }
+    }
+
+    /// Point every section's raw data at its RVA, in `sections` and in the headers
+    fn repair_sections(data: &mut [u8], sections: &mut [Section], image_base: u64, section_header_offset: usize) {
+        for (i, section) in sections.iter_mut().enumerate() {
+            let rva = section.virtual_address.as_u64() - image_base;
+            let size = section
+                .virtual_size
+                .max(section.raw_size)
+                .min((data.len() as u64).saturating_sub(rva));
+            section.raw_offset = rva;
+            section.raw_size = size;
+            if section.virtual_size == 0 {
+                section.virtual_size = size;
+            }
+
+            let header = section_header_offset + i * 40;
+            if let Some(fields) = data.get_mut(header + 8..header + 24) {
+                fields[0..4].copy_from_slice(&(section.virtual_size as u32).to_le_bytes());
+                fields[8..12].copy_from_slice(&(size as u32).to_le_bytes());
+                fields[12..16].copy_from_slice(&(rva as u32).to_le_bytes());
+            }
+        }
+    }
+
+    /// Virtual address of an RVA
+    fn rva_to_va(&self, rva: u64) -> Address {
+        Address::new(self.image_base.as_u64().wrapping_add(rva))
+    }
+
+    /// Create a reader positioned at an RVA
+    fn reader_at_rva(&self, rva: u32) -> ParseResult<BinaryReader<'_>> {
+        let va = self.rva_to_va(rva as u64);
+        let offset = self
+            .va_to_offset(va)
+            .ok_or(ParseError::AddressOutOfBounds(va.as_u64()))?;
+        let offset = usize::try_from(offset).map_err(|_| ParseError::AddressOutOfBounds(va.as_u64()))?;
+        Ok(BinaryReader::new_at(&self.data, offset, true))
+    }
+
+    /// Read a null-terminated string at an RVA
+    fn read_string_rva(&self, rva: u32) -> ParseResult<String> {
+        self.reader_at_rva(rva)?.read_cstring(4096)
+    }
+
+    fn parse_exports(&self) -> ParseResult<Vec<Export>> {
+        let Some((dir_rva, dir_size)) = self.get_data_directory(IMAGE_DIRECTORY_ENTRY_EXPORT) else {
+            return Ok(Vec::new());
+        };
+
+        let mut reader = self.reader_at_rva(dir_rva)?;
+        reader.skip(16)?; // Characteristics, TimeDateStamp, version, Name
+        let ordinal_base = reader.read_u32()?;
+        let number_of_functions = reader.read_u32()?;
+        let number_of_names = reader.read_u32()?;
+        let address_of_functions = reader.read_u32()?;
+        let address_of_names = reader.read_u32()?;
+        let address_of_name_ordinals = reader.read_u32()?;
+
+        if number_of_functions as usize > self.data.len() / 4 {
+            return Err(ParseError::invalid_header(format!(
+                "Export table claims {} functions",
+                number_of_functions
+            )));
+        }
+
+        // Map function index -> name
+        let mut names = vec![None; number_of_functions as usize];
+        if number_of_names > 0 {
+            let mut name_reader = self.reader_at_rva(address_of_names)?;
+            let mut ordinal_reader = self.reader_at_rva(address_of_name_ordinals)?;
+            for _ in 0..number_of_names {
+                let name_rva = name_reader.read_u32()?;
+                let index = ordinal_reader.read_u16()? as usize;
+                if let Some(slot) = names.get_mut(index) {
+                    *slot = Some(self.read_string_rva(name_rva)?);
+                }
+            }
+        }
+
+        let mut exports = Vec::new();
+        if number_of_functions > 0 {
+            let mut function_reader = self.reader_at_rva(address_of_functions)?;
+            for (index, name) in names.into_iter().enumerate() {
+                let rva = function_reader.read_u32()?;
+                if rva == 0 {
+                    continue;
+                }
+
+                // RVAs pointing back into the export directory are forwarder strings
+                let is_forwarder = rva >= dir_rva && rva < dir_rva.saturating_add(dir_size);
+                let (address, forwarder) = if is_forwarder {
+                    (Address::ZERO, Some(self.read_string_rva(rva)?))
+                } else {
+                    (self.rva_to_va(rva as u64), None)
+                };
+
+                exports.push(Export {
+                    name,
+                    ordinal: ordinal_base.wrapping_add(index as u32),
+                    address,
+                    forwarder,
+                });
+            }
+        }
+
+        Ok(exports)
+    }
+
+    fn parse_imports(&self) -> ParseResult<Vec<Import>> {
+        let Some((dir_rva, _)) = self.get_data_directory(IMAGE_DIRECTORY_ENTRY_IMPORT) else {
+            return Ok(Vec::new());
+        };
+
+        let thunk_size = if self.is_64bit { 8 } else { 4 };
+        let ordinal_flag = if self.is_64bit { IMAGE_ORDINAL_FLAG64 } else { IMAGE_ORDINAL_FLAG32 };
+
+        let mut imports = Vec::new();
+        let mut reader = self.reader_at_rva(dir_rva)?;
+        loop {
+            let original_first_thunk = reader.read_u32()?;
+            reader.skip(8)?; // TimeDateStamp, ForwarderChain
+            let name_rva = reader.read_u32()?;
+            let first_thunk = reader.read_u32()?;
+            if original_first_thunk == 0 && name_rva == 0 && first_thunk == 0 {
+                break;
+            }
+
+            let dll = self.read_string_rva(name_rva)?;
+
+            // Bound images overwrite the IAT, so prefer the lookup table
+            let lookup_rva = if original_first_thunk != 0 { original_first_thunk } else { first_thunk };
+            let mut thunk_reader = self.reader_at_rva(lookup_rva)?;
+            for index in 0u64.. {
+                let thunk = if self.is_64bit {
+                    thunk_reader.read_u64()?
+                } else {
+                    thunk_reader.read_u32()? as u64
+                };
+                if thunk == 0 {
+                    break;
+                }
+
+                let (name, ordinal) = if thunk & ordinal_flag != 0 {
+                    (None, Some(thunk as u16))
+                } else {
+                    // Skip the two-byte hint of IMAGE_IMPORT_BY_NAME
+                    (Some(self.read_string_rva((thunk as u32).wrapping_add(2))?), None)
+                };
+
+                imports.push(Import {
+                    dll: dll.clone(),
+                    name,
+                    ordinal,
+                    iat_address: self.rva_to_va(first_thunk as u64 + index * thunk_size),
+                });
+            }
+        }
+
+        Ok(imports)
+    }
+
+    fn parse_runtime_functions(&self) -> ParseResult<Vec<RuntimeFunction>> {
+        let Some((dir_rva, dir_size)) = self.get_data_directory(IMAGE_DIRECTORY_ENTRY_EXCEPTION) else {
+            return Ok(Vec::new());
+        };
+
+        let mut reader = self.reader_at_rva(dir_rva)?;
+        let mut functions = Vec::new();
+
+        match self.architecture {
+            Architecture::X64 => {
+                for _ in 0..dir_size / 12 {
+                    let begin = reader.read_u32()?;
+                    let end = reader.read_u32()?;
+                    let _unwind_info = reader.read_u32()?;
+                    functions.push(RuntimeFunction {
+                        begin: self.rva_to_va(begin as u64),
+                        end: Some(self.rva_to_va(end as u64)),
+                    });
+                }
+            }
+            Architecture::Arm64 | Architecture::Arm32 => {
+                for _ in 0..dir_size / 8 {
+                    let begin = reader.read_u32()?;
+                    let unwind_data = reader.read_u32()?;
+                    let length = self.arm_function_length(unwind_data);
+                    functions.push(RuntimeFunction {
+                        begin: self.rva_to_va((begin & !1) as u64),
+                        end: length.map(|len| self.rva_to_va((begin & !1) as u64 + len)),
+                    });
+                }
+            }
+            _ => {}
+        }
+
+        Ok(functions)
+    }
+
+    fn parse_relocations(&self) -> ParseResult<Vec<Relocation>> {
+        let Some((dir_rva, dir_size)) = self.get_data_directory(IMAGE_DIRECTORY_ENTRY_BASERELOC) else {
+            return Ok(Vec::new());
+        };
+
+        let mut relocations = Vec::new();
+        let mut reader = self.reader_at_rva(dir_rva)?;
+        let end = reader.offset() + dir_size as usize;
+        while reader.offset() + 8 <= end {
+            let block_start = reader.offset();
+            let page_rva = reader.read_u32()?;
+            let block_size = reader.read_u32()? as usize;
+            if block_size < 8 {
+                return Err(ParseError::invalid_header(format!(
+                    "Base relocation block at {:#x} is {} bytes",
+                    block_start, block_size
+                )));
+            }
+
+            for _ in 0..(block_size - 8) / 2 {
+                let entry = reader.read_u16()?;
+                let relocation_type = match (entry >> 12) as u8 {
+                    IMAGE_REL_BASED_ABSOLUTE => continue, // Padding
+                    IMAGE_REL_BASED_HIGHLOW => RelocationType::HighLow,
+                    IMAGE_REL_BASED_DIR64 => RelocationType::Dir64,
+                    other => RelocationType::Other(other),
+                };
+                let rva = page_rva as u64 + (entry & 0x0FFF) as u64;
+                relocations.push(Relocation {
+                    address: self.rva_to_va(rva),
+                    relocation_type,
+                });
+            }
+            // Padding is included in the size, so the next block starts right after it
+            reader.set_offset(block_start + block_size);
+        }
+
+        Ok(relocations)
+    }
+
+    /// Function length from an ARM/ARM64 `.pdata` unwind word
+    fn arm_function_length(&self, unwind_data: u32) -> Option<u64> {
+        let packed = unwind_data & 0x3 != 0;
+        if self.architecture == Architecture::Arm64 {
+            if packed {
+                Some(((unwind_data >> 2) & 0x7FF) as u64 * 4)
+            } else {
+                // .xdata header: FunctionLength in bits 0-17, in 4-byte units
+                let header = self.reader_at_rva(unwind_data).ok()?.read_u32().ok()?;
+                Some((header & 0x3FFFF) as u64 * 4)
+            }
+        } else if packed {
+            Some(((unwind_data >> 2) & 0x7FF) as u64 * 2)
+        } else {
+            let header = self.reader_at_rva(unwind_data).ok()?.read_u32().ok()?;
+            Some((header & 0x3FFFF) as u64 * 2)
+        }
+    }
+
+    /// Build the symbol list from exports, `.pdata` entries and imports
+    ///
+    /// Imports are placed at their IAT slots, the address `call [rip+X]`
+    /// goes through, and named `KERNEL32_16` when imported by ordinal.
+    fn collect_symbols(&self) -> Vec<Symbol> {
+        let mut symbols = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+
+        for export in &self.exports {
+            let Some(name) = &export.name else { continue };
+            if export.forwarder.is_some() {
+                continue;
+            }
+            let executable = self
+                .sections
+                .iter()
+                .any(|s| s.characteristics.contains(SectionFlags::EXECUTE) && s.contains_va(export.address.as_u64()));
+            seen.insert(export.address);
+            symbols.push(Symbol {
+                name: name.clone(),
+                address: export.address,
+                size: None,
+                symbol_type: if executable { SymbolType::Function } else { SymbolType::Object },
+                type_name: None,
+            });
+        }
+
+        for function in &self.runtime_functions {
+            let size = function
+                .end
+                .map(|end| end.as_u64().saturating_sub(function.begin.as_u64()))
+                .filter(|&size| size > 0);
+            if !seen.insert(function.begin) {
+                // Exported functions get their size from .pdata
+                if let Some(symbol) = symbols.iter_mut().find(|s| s.address == function.begin) {
+                    symbol.size = symbol.size.or(size);
+                }
+                continue;
+            }
+            symbols.push(Symbol {
+                name: format!("sub_{:X}", function.begin.as_u64()),
+                address: function.begin,
+                size,
+                symbol_type: SymbolType::Function,
+                type_name: None,
+            });
+        }
+
+        let thunk_size = if self.is_64bit { 8 } else { 4 };
+        for import in &self.imports {
+            let name = match (&import.name, import.ordinal) {
+                (Some(name), _) => name.clone(),
+                (None, Some(ordinal)) => {
+                    let dll = import.dll.rsplit_once('.').map_or(import.dll.as_str(), |(stem, _)| stem);
+                    format!("{}_{}", dll, ordinal)
+                }
+                (None, None) => continue,
+            };
+            symbols.push(Symbol {
+                name,
+                address: import.iat_address,
+                size: Some(thunk_size),
+                symbol_type: SymbolType::Function,
+                type_name: None,
+            });
+        }
+
+        symbols
     }
 }
 
@@ -218,69 +881,72 @@ impl BinaryFile for PeFile {
         &self.symbols
     }
 
-    fn va_to_offset(&self, va: Address) -> Option<u64> {
-        for section in &self.sections {
-            let section_va_start = section.virtual_address.as_u64();
-            let section_va_end = section_va_start + section.virtual_size;
+    fn functions(&self) -> &[Function] {
+        &self.functions
+    }
+
+    fn add_symbols(&mut self, symbols: Vec<Symbol>) -> usize {
+        merge_symbols(&mut self.symbols, symbols)
+    }
+
+    fn imported_functions(&self) -> Vec<String> {
+        self.imports.iter().filter_map(|import| import.name.clone()).collect()
+    }
 
-            if va.as_u64() >= section_va_start && va.as_u64() < section_va_end {
-                let rva = va.as_u64() - section_va_start;
-                return Some(section.raw_offset + rva);
+    fn libraries(&self) -> Vec<String> {
+        let mut libraries: Vec<String> = Vec::new();
+        for import in &self.imports {
+            if !libraries.iter().any(|dll| dll.eq_ignore_ascii_case(&import.dll)) {
+                libraries.push(import.dll.clone());
             }
         }
-        None
+        libraries
     }
 
-    fn offset_to_va(&self, offset: u64) -> Option<Address> {
-        for section in &self.sections {
-            let raw_start = section.raw_offset;
-            let raw_end = raw_start + section.raw_size;
-
-            if offset >= raw_start && offset < raw_end {
-                let section_offset = offset - raw_start;
-                return Some(Address::new(
-                    section.virtual_address.as_u64() + section_offset,
-                ));
-            }
+    fn va_to_offset(&self, va: Address) -> Option<u64> {
+        let section = self.sections.iter().find(|s| s.contains_va(va.as_u64()))?;
+        let delta = va.as_u64() - section.virtual_address.as_u64();
+        // Files hold no bytes for the zero-filled tail past SizeOfRawData
+        if self.layout == ImageLayout::File && delta >= section.raw_size {
+            return None;
         }
-        None
+        section.raw_offset.checked_add(delta)
+    }
+
+    fn offset_to_va(&self, offset: u64) -> Option<Address> {
+        let section = self.sections.iter().find(|s| s.contains_offset(offset))?;
+        Some(Address::new(section.virtual_address.as_u64().wrapping_add(offset - section.raw_offset)))
     }
 
     fn read_va(&self, va: Address, size: usize) -> ParseResult<&[u8]> {
         let offset = self
             .va_to_offset(va)
-            .ok_or_else(|| ParseError::AddressOutOfBounds(va.as_u64()))? as usize;
-
-        if offset + size > self.data.len() {
-            return Err(ParseError::AddressOutOfBounds(va.as_u64()));
-        }
-
-        Ok(&self.data[offset..offset + size])
+            .ok_or_else(|| ParseError::AddressOutOfBounds(va.as_u64()))?;
+        checked_slice(&self.data, offset, size as u64).map_err(|_| ParseError::AddressOutOfBounds(va.as_u64()))
     }
 
     fn read_string_va(&self, va: Address, max_len: usize) -> ParseResult<String> {
         let offset = self
             .va_to_offset(va)
-            .ok_or_else(|| ParseError::AddressOutOfBounds(va.as_u64()))? as usize;
-
-        let end = (offset + max_len).min(self.data.len());
-        for i in offset..end {
-            if self.data[i] == 0 {
-                return Ok(String::from_utf8_lossy(&self.data[offset..i]).to_string());
-            }
-        }
-
-        Ok(String::from_utf8_lossy(&self.data[offset..end]).to_string())
+            .ok_or_else(|| ParseError::AddressOutOfBounds(va.as_u64()))?;
+        BinaryReader::new(&self.data, true)
+            .string_at(offset, max_len)
+            .map_err(|_| ParseError::AddressOutOfBounds(va.as_u64()))
     }
 
     fn data(&self) -> &[u8] {
         &self.data
     }
+
+    fn layout(&self) -> ImageLayout {
+        self.layout
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::{put, sample_pe64};
 
     #[test]
     fn test_dos_magic() {
@@ -288,4 +954,192 @@ mod tests {
         let reader = BinaryReader::new(data, true);
         assert_eq!(reader.remaining(), 2);
     }
+
+
+    /// Add a `.reloc` block covering two pointers in `.rdata`
+    fn sample_pe64_with_relocations() -> Vec<u8> {
+        let mut image = sample_pe64();
+        let optional = 0x58;
+        put(&mut image, optional + 112 + 5 * 8, &0x2300u32.to_le_bytes());
+        put(&mut image, optional + 116 + 5 * 8, &16u32.to_le_bytes());
+
+        let block = 0x2300 - 0x2000 + 0x600;
+        put(&mut image, block, &0x2000u32.to_le_bytes());
+        put(&mut image, block + 4, &16u32.to_le_bytes());
+        put(&mut image, block + 8, &((10u16 << 12) | 0x380).to_le_bytes());
+        put(&mut image, block + 10, &((10u16 << 12) | 0x388).to_le_bytes());
+        put(&mut image, block + 12, &0u16.to_le_bytes()); // ABSOLUTE padding
+
+        put(&mut image, 0x380 + 0x600, &0x1_8000_1000u64.to_le_bytes());
+        put(&mut image, 0x388 + 0x600, &0x1_8000_1040u64.to_le_bytes());
+        image
+    }
+
+    #[test]
+    fn test_relocations() {
+        let pe = PeFile::parse(&sample_pe64_with_relocations()).unwrap();
+        assert_eq!(
+            pe.relocations(),
+            [
+                Relocation {
+                    address: Address::new(0x1_8000_2380),
+                    relocation_type: RelocationType::Dir64,
+                },
+                Relocation {
+                    address: Address::new(0x1_8000_2388),
+                    relocation_type: RelocationType::Dir64,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_relocation_blocks() {
+        // Two unpadded 10-byte blocks holding one entry each
+        let mut image = sample_pe64_with_relocations();
+        let optional = 0x58;
+        put(&mut image, optional + 116 + 5 * 8, &20u32.to_le_bytes());
+        let block = 0x2300 - 0x2000 + 0x600;
+        for (i, entry) in [0x380u16, 0x388].iter().enumerate() {
+            put(&mut image, block + i * 10, &0x2000u32.to_le_bytes());
+            put(&mut image, block + i * 10 + 4, &10u32.to_le_bytes());
+            put(&mut image, block + i * 10 + 8, &((10u16 << 12) | entry).to_le_bytes());
+        }
+        let pe = PeFile::parse(&image).unwrap();
+        let addresses: Vec<u64> = pe.relocations().iter().map(|r| r.address.as_u64()).collect();
+        assert_eq!(addresses, [0x1_8000_2380, 0x1_8000_2388]);
+
+        // A block too short to hold its own header is rejected rather than looped on
+        put(&mut image, block + 14, &4u32.to_le_bytes());
+        let pe = PeFile::parse(&image).unwrap();
+        assert!(pe.relocations().is_empty());
+    }
+
+    #[test]
+    fn test_rebased_view() {
+        let pe = PeFile::parse(&sample_pe64_with_relocations()).unwrap();
+        let rebased = pe.rebased(Address::new(0x7FF6_0000_0000));
+
+        assert_eq!(rebased.image_base(), Address::new(0x7FF6_0000_0000));
+        assert_eq!(rebased.find_section(".rdata").unwrap().virtual_address, Address::new(0x7FF6_0000_2000));
+        assert_eq!(rebased.find_symbol("il2cpp_init").unwrap().address, Address::new(0x7FF6_0000_1000));
+
+        let pointers = rebased.read_va(Address::new(0x7FF6_0000_2380), 16).unwrap();
+        assert_eq!(&pointers[..8], &0x7FF6_0000_1000u64.to_le_bytes());
+        assert_eq!(&pointers[8..], &0x7FF6_0000_1040u64.to_le_bytes());
+
+        // The original file is untouched
+        let original = pe.read_va(Address::new(0x1_8000_2380), 8).unwrap();
+        assert_eq!(original, &0x1_8000_1000u64.to_le_bytes());
+    }
+
+    /// Lay the sample out as the loader would map it
+    fn sample_pe64_mapped() -> Vec<u8> {
+        let file = sample_pe64();
+        let mut image = vec![0u8; 0x2400];
+        image[..0x400].copy_from_slice(&file[..0x400]);
+        image[0x1000..0x1200].copy_from_slice(&file[0x400..0x600]);
+        image[0x2000..0x2400].copy_from_slice(&file[0x600..0xA00]);
+        image
+    }
+
+    #[test]
+    fn test_mapped_layout() {
+        let file = PeFile::parse(&sample_pe64()).unwrap();
+        assert_eq!(file.layout(), ImageLayout::File);
+        // .text has 0x200 bytes on disk and 0x400 in memory
+        assert_eq!(file.va_to_offset(Address::new(0x1_8000_11FF)), Some(0x5FF));
+        assert_eq!(file.va_to_offset(Address::new(0x1_8000_1200)), None);
+        assert!(file.read_va(Address::new(0x1_8000_1200), 4).is_err());
+
+        let pe = PeFile::parse(&sample_pe64_mapped()).unwrap();
+        assert_eq!(pe.layout(), ImageLayout::Mapped);
+
+        let text = pe.find_section(".text").unwrap();
+        assert_eq!((text.raw_offset, text.raw_size), (0x1000, 0x400));
+        assert_eq!(pe.section_data(text).unwrap()[..3], [0x31, 0xC0, 0xC3]);
+        assert_eq!(pe.va_to_offset(Address::new(0x1_8000_2060)), Some(0x2060));
+        assert_eq!(pe.va_to_offset(Address::new(0x1_8000_1200)), Some(0x1200));
+        assert_eq!(pe.exports(), file.exports());
+        assert_eq!(pe.imports(), file.imports());
+
+        // Repaired headers read back as a file whose raw offsets equal RVAs
+        let repaired = PeFile::parse_with_layout(pe.data(), Some(ImageLayout::File)).unwrap();
+        assert_eq!(repaired.find_section(".rdata").unwrap().raw_offset, 0x2000);
+        assert_eq!(repaired.exports(), file.exports());
+    }
+
+    #[test]
+    fn test_data_directories() {
+        let pe = PeFile::parse(&sample_pe64()).unwrap();
+        assert_eq!(pe.get_data_directory(IMAGE_DIRECTORY_ENTRY_EXPORT), Some((0x2000, 0x100)));
+        assert_eq!(pe.get_data_directory(IMAGE_DIRECTORY_ENTRY_BASERELOC), None);
+        assert_eq!(pe.get_data_directory(64), None);
+    }
+
+    #[test]
+    fn test_exports_and_imports() {
+        let pe = PeFile::parse(&sample_pe64()).unwrap();
+
+        let exports = pe.exports();
+        assert_eq!(exports.len(), 3);
+        assert_eq!(exports[0].name.as_deref(), Some("il2cpp_init"));
+        assert_eq!(exports[0].address, Address::new(0x1_8000_1000));
+        assert_eq!((exports[1].name.as_ref(), exports[1].ordinal), (None, 2));
+        assert_eq!(exports[2].forwarder.as_deref(), Some("NTDLL.RtlFree"));
+
+        let imports = pe.imports();
+        assert_eq!(imports.len(), 2);
+        assert_eq!(imports[0].dll, "KERNEL32.dll");
+        assert_eq!(imports[0].name.as_deref(), Some("GetProcAddress"));
+        assert_eq!(imports[0].iat_address, Address::new(0x1_8000_2160));
+        assert_eq!((imports[1].ordinal, imports[1].iat_address), (Some(16), Address::new(0x1_8000_2168)));
+        assert_eq!(pe.imported_functions(), ["GetProcAddress"]);
+    }
+
+    #[test]
+    fn test_symbols_from_exports_and_pdata() {
+        let pe = PeFile::parse(&sample_pe64()).unwrap();
+
+        let init = pe.find_symbol("il2cpp_init").unwrap();
+        assert_eq!(init.symbol_type, SymbolType::Function);
+        assert_eq!(init.size, Some(0x20));
+
+        let names: Vec<&str> = pe.symbols().iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["il2cpp_init", "sub_180001040", "sub_180001080", "GetProcAddress", "KERNEL32_16"]);
+        assert!(pe.symbols().iter().all(|s| s.symbol_type == SymbolType::Function));
+
+        let slots: Vec<(u64, Option<u64>)> = pe.symbols()[3..].iter().map(|s| (s.address.as_u64(), s.size)).collect();
+        assert_eq!(slots, [(0x1_8000_2160, Some(8)), (0x1_8000_2168, Some(8))]);
+
+        let functions: Vec<(u64, u64)> =
+            pe.functions().iter().map(|f| (f.start.as_u64(), f.end.as_u64())).collect();
+        assert_eq!(functions, [(0x180001000, 0x180001020), (0x180001040, 0x180001060), (0x180001080, 0x180001090)]);
+        assert_eq!(pe.function_at(Address::new(0x180001050)).unwrap().size(), 0x20);
+        assert!(pe.function_at(Address::new(0x180001030)).is_none());
+    }
+
+    #[test]
+    fn test_malformed_directory() {
+        // Point the import directory past the end of the image
+        let mut image = sample_pe64();
+        put(&mut image, 0x58 + 112 + 8, &0x10_0000u32.to_le_bytes());
+
+        let pe = PeFile::parse(&image).unwrap();
+        assert!(pe.imports().is_empty());
+        assert_eq!(pe.exports().len(), 3);
+        assert_eq!(pe.sections().len(), 2);
+        assert_eq!(pe.entry_point(), Address::new(0x1_8000_1000));
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let pe = PeFile::parse(&sample_pe64()).unwrap();
+        let init = pe.find_symbol("il2cpp_init").unwrap().address;
+
+        let instructions = pe.disassemble(init, 16).unwrap();
+        let mnemonics: Vec<&str> = instructions.iter().map(|i| i.mnemonic.as_str()).collect();
+        assert_eq!(mnemonics, ["xor", "ret"]);
+        assert!(pe.disassemble(Address::new(0x1000), 16).is_err());
+    }
 }
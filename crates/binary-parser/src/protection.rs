@@ -0,0 +1,359 @@
+//! Packing and protection report
+//!
+//! Measures the Shannon entropy of every section, matches known protectors
+//! by section names, linked libraries and imported functions, and flags
+//! entry points that sit outside the code section, so packed or encrypted
+//! images are caught before dumping rather than producing garbage.
+
+use crate::common::{BinaryFile, SectionFlags};
+use endfield_core::{Address, Architecture, BinaryFormat};
+use serde::{Deserialize, Serialize};
+
+/// Entropy in bits per byte above which data looks compressed or encrypted
+///
+/// Compiled code sits around 5.5–6.5; compressed or encrypted data is close to 8.
+pub const HIGH_ENTROPY: f64 = 7.2;
+
+/// Sections smaller than this are too short for a meaningful entropy estimate
+const MIN_ENTROPY_SIZE: u64 = 512;
+
+/// Sections whose contents are routinely compressed (icons, embedded archives)
+const COMPRESSED_SECTIONS: &[&str] = &[".rsrc"];
+
+/// Names of the main code section, Mach-O sections being qualified by their segment
+const CODE_SECTIONS: &[&str] = &[".text", "__TEXT,__text"];
+
+/// Name prefix of the sections synthesized from ELF segments when section headers are missing
+const SEGMENT_PREFIX: &str = "LOAD";
+
+/// `global-metadata.dat` magic
+const METADATA_MAGIC: u32 = 0xFAB1_1BAF;
+
+/// Known packer or protector
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Protector {
+    VMProtect,
+    Themida,
+    /// Tencent ACE / TP anti-cheat
+    Ace,
+    /// 360 Jiagu
+    Jiagu,
+}
+
+impl Protector {
+    /// Human-readable name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::VMProtect => "VMProtect",
+            Self::Themida => "Themida/WinLicense",
+            Self::Ace => "ACE/Tencent",
+            Self::Jiagu => "Jiagu",
+        }
+    }
+}
+
+/// Where a protector signature is looked for
+#[derive(Clone, Copy)]
+enum SignatureKind {
+    /// Section name prefix
+    Section,
+    /// Substring of a linked library name
+    Library,
+    /// Prefix of an imported function name
+    Import,
+}
+
+/// Lowercase protector signatures
+const SIGNATURES: &[(Protector, SignatureKind, &str)] = &[
+    (Protector::VMProtect, SignatureKind::Section, ".vmp"),
+    (Protector::VMProtect, SignatureKind::Library, "vmprotectsdk"),
+    (Protector::VMProtect, SignatureKind::Import, "vmprotect"),
+    (Protector::Themida, SignatureKind::Section, ".themida"),
+    (Protector::Themida, SignatureKind::Section, ".winlice"),
+    (Protector::Themida, SignatureKind::Library, "secureengine"),
+    (Protector::Ace, SignatureKind::Library, "tersafe"),
+    (Protector::Ace, SignatureKind::Library, "libanogs"),
+    (Protector::Ace, SignatureKind::Library, "libanort"),
+    (Protector::Ace, SignatureKind::Library, "libtprt"),
+    (Protector::Ace, SignatureKind::Import, "anosdk"),
+    (Protector::Ace, SignatureKind::Import, "tp2_sdk"),
+    (Protector::Jiagu, SignatureKind::Section, ".jiagu"),
+    (Protector::Jiagu, SignatureKind::Library, "libjiagu"),
+    (Protector::Jiagu, SignatureKind::Library, "libprotectclass"),
+];
+
+/// Entropy of one section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SectionEntropy {
+    /// Section name
+    pub name: String,
+    /// Virtual address
+    pub virtual_address: Address,
+    /// Raw size in bytes
+    pub raw_size: u64,
+    /// Shannon entropy in bits per byte
+    pub entropy: f64,
+    /// Section is executable
+    pub executable: bool,
+    /// Section is writable
+    pub writable: bool,
+}
+
+/// Indicator of packing or protection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Finding {
+    /// Section name or linked library of a known protector
+    Protector { protector: Protector, evidence: String },
+    /// Section whose contents look compressed or encrypted
+    HighEntropy { section: String, entropy: f64 },
+    /// Section both writable and executable, typical of unpacking stubs
+    WritableCode { section: String },
+    /// Entry point outside the main code section
+    EntryPoint { address: Address, section: Option<String> },
+}
+
+/// Packing and protection report for a binary
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProtectionReport {
+    /// Binary format
+    pub format: BinaryFormat,
+    /// Target architecture
+    pub architecture: Architecture,
+    /// Entropy of the whole image
+    pub entropy: f64,
+    /// Entry point, zero for libraries without one
+    pub entry_point: Address,
+    /// Per-section entropy
+    pub sections: Vec<SectionEntropy>,
+    /// Indicators found
+    pub findings: Vec<Finding>,
+}
+
+impl ProtectionReport {
+    /// Analyze a parsed binary
+    pub fn analyze<B: BinaryFile + ?Sized>(binary: &B) -> Self {
+        let mut findings = Vec::new();
+
+        let sections: Vec<SectionEntropy> = binary
+            .sections()
+            .iter()
+            .map(|section| SectionEntropy {
+                name: section.name.clone(),
+                virtual_address: section.virtual_address,
+                raw_size: section.raw_size,
+                entropy: binary.section_data(section).map(shannon_entropy).unwrap_or(0.0),
+                executable: section.characteristics.contains(SectionFlags::EXECUTE),
+                writable: section.characteristics.contains(SectionFlags::WRITE),
+            })
+            .collect();
+
+        let libraries = binary.libraries();
+        let imports = binary.imported_functions();
+        for &(protector, kind, pattern) in SIGNATURES {
+            let names: Vec<&str> = match kind {
+                SignatureKind::Section => sections.iter().map(|s| s.name.as_str()).collect(),
+                SignatureKind::Library => libraries.iter().map(String::as_str).collect(),
+                SignatureKind::Import => imports.iter().map(String::as_str).collect(),
+            };
+            for name in names {
+                let lower = name.to_ascii_lowercase();
+                let matched = match kind {
+                    SignatureKind::Section | SignatureKind::Import => lower.starts_with(pattern),
+                    SignatureKind::Library => lower.contains(pattern),
+                };
+                if matched {
+                    findings.push(Finding::Protector { protector, evidence: name.to_string() });
+                }
+            }
+        }
+
+        for section in &sections {
+            if section.raw_size >= MIN_ENTROPY_SIZE
+                && section.entropy > HIGH_ENTROPY
+                && !COMPRESSED_SECTIONS.contains(&section.name.as_str())
+            {
+                findings.push(Finding::HighEntropy { section: section.name.clone(), entropy: section.entropy });
+            }
+            if section.executable && section.writable {
+                findings.push(Finding::WritableCode { section: section.name.clone() });
+            }
+        }
+
+        let entry_point = binary.entry_point();
+        if entry_point != Address::ZERO {
+            let containing = binary.sections().iter().find(|s| s.contains_va(entry_point.as_u64()));
+            let suspicious = match containing {
+                None => true,
+                Some(section) => {
+                    !section.characteristics.contains(SectionFlags::EXECUTE)
+                        || !(CODE_SECTIONS.contains(&section.name.as_str()) || section.name.starts_with(SEGMENT_PREFIX))
+                }
+            };
+            if suspicious {
+                findings.push(Finding::EntryPoint {
+                    address: entry_point,
+                    section: containing.map(|s| s.name.clone()),
+                });
+            }
+        }
+
+        Self {
+            format: binary.format(),
+            architecture: binary.architecture(),
+            entropy: shannon_entropy(binary.data()),
+            entry_point,
+            sections,
+            findings,
+        }
+    }
+
+    /// Protectors identified, without duplicates
+    pub fn protectors(&self) -> Vec<Protector> {
+        let mut protectors = Vec::new();
+        for finding in &self.findings {
+            if let Finding::Protector { protector, .. } = finding {
+                if !protectors.contains(protector) {
+                    protectors.push(*protector);
+                }
+            }
+        }
+        protectors
+    }
+
+    /// Check whether anything points at packing or protection
+    pub fn is_suspicious(&self) -> bool {
+        !self.findings.is_empty()
+    }
+}
+
+/// Encryption report for a `global-metadata.dat` blob
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetadataReport {
+    /// Size in bytes
+    pub size: u64,
+    /// Shannon entropy in bits per byte
+    pub entropy: f64,
+    /// Header version, when the magic matches
+    pub version: Option<i32>,
+    /// Magic is missing or the contents look encrypted
+    pub encrypted: bool,
+}
+
+impl MetadataReport {
+    /// Analyze raw metadata bytes
+    pub fn analyze(data: &[u8]) -> Self {
+        let word = |offset: usize| data.get(offset..offset + 4).map(|b| [b[0], b[1], b[2], b[3]]);
+        let magic_ok = word(0).map(u32::from_le_bytes) == Some(METADATA_MAGIC);
+        let version = word(4).map(i32::from_le_bytes).filter(|_| magic_ok);
+        let entropy = shannon_entropy(data);
+
+        Self {
+            size: data.len() as u64,
+            entropy,
+            version,
+            encrypted: !magic_ok || entropy > HIGH_ENTROPY,
+        }
+    }
+}
+
+/// Shannon entropy of `data` in bits per byte, from 0.0 to 8.0
+pub fn shannon_entropy(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let mut counts = [0u64; 256];
+    for &byte in data {
+        counts[byte as usize] += 1;
+    }
+    let len = data.len() as f64;
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_binary;
+    use crate::test_util::sample_pe64;
+    use crate::test_util::TestBinary;
+
+    #[test]
+    fn test_shannon_entropy() {
+        assert_eq!(shannon_entropy(&[]), 0.0);
+        assert_eq!(shannon_entropy(&[0x41; 64]), 0.0);
+        assert_eq!(shannon_entropy(&[0, 1, 0, 1]), 1.0);
+        let all: Vec<u8> = (0..=255).collect();
+        assert_eq!(shannon_entropy(&all), 8.0);
+    }
+
+    #[test]
+    fn test_protector_sections() {
+        let pe64 = sample_pe64();
+        let clean = ProtectionReport::analyze(parse_binary(&pe64).unwrap().as_ref());
+        assert!(clean.protectors().is_empty());
+
+        // Rename .text to a VMProtect section, which also moves the entry point out of .text
+        let mut data = pe64.clone();
+        let name = memchr::memmem::find(&data, b".text\0\0\0").unwrap();
+        data[name..name + 8].copy_from_slice(b".vmp0\0\0\0");
+        let report = ProtectionReport::analyze(parse_binary(&data).unwrap().as_ref());
+
+        assert_eq!(report.protectors(), vec![Protector::VMProtect]);
+        assert!(!clean.is_suspicious());
+        assert!(report.findings.contains(&Finding::EntryPoint {
+            address: clean.entry_point,
+            section: Some(".vmp0".to_string()),
+        }));
+    }
+
+    #[test]
+    fn test_macho_entry_point() {
+        let code = SectionFlags::READ | SectionFlags::EXECUTE;
+        let mut binary = TestBinary::new(BinaryFormat::MachO)
+            .section("__TEXT,__text", 0x1000, &[0xC0, 0x03, 0x5F, 0xD6], code)
+            .section("__TEXT,__stubs", 0x2000, &[0x1F, 0x20, 0x03, 0xD5], code);
+        binary.entry_point = Address::new(0x1000);
+        assert!(ProtectionReport::analyze(&binary).findings.is_empty());
+
+        binary.entry_point = Address::new(0x2000);
+        assert_eq!(
+            ProtectionReport::analyze(&binary).findings,
+            vec![Finding::EntryPoint {
+                address: Address::new(0x2000),
+                section: Some("__TEXT,__stubs".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_protector_imports() {
+        let mut binary = TestBinary::new(BinaryFormat::ELF);
+        binary.imported_functions = vec!["malloc".to_string(), "AnoSDKInit".to_string()];
+        let report = ProtectionReport::analyze(&binary);
+        assert_eq!(report.protectors(), vec![Protector::Ace]);
+        assert!(report.findings.contains(&Finding::Protector {
+            protector: Protector::Ace,
+            evidence: "AnoSDKInit".to_string(),
+        }));
+    }
+
+    #[test]
+    fn test_metadata_report() {
+        let mut data = vec![0u8; 1024];
+        data[..4].copy_from_slice(&METADATA_MAGIC.to_le_bytes());
+        data[4..8].copy_from_slice(&29i32.to_le_bytes());
+        let report = MetadataReport::analyze(&data);
+        assert_eq!(report.version, Some(29));
+        assert!(!report.encrypted);
+
+        let scrambled: Vec<u8> = (0..1024u32).map(|i| (i.wrapping_mul(167) >> 1) as u8 ^ (i as u8)).collect();
+        assert!(MetadataReport::analyze(&scrambled).encrypted);
+    }
+}
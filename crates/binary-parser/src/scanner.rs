@@ -0,0 +1,316 @@
+//! Multi-pattern signature scanner
+//!
+//! Parses IDA / x64dbg style signatures such as `48 8B ?? ?? 05 ?`, compiles
+//! a whole set into an anchor-byte table and scans executable sections in
+//! parallel, using memchr to jump between anchor candidates.
+
+use crate::common::BinaryFile;
+use crate::error::{ParseError, ParseResult};
+use endfield_core::Address;
+use rayon::prelude::*;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Bytes scanned per parallel work item
+const CHUNK_SIZE: usize = 1 << 20;
+
+/// Byte pattern with wildcards
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    bytes: Vec<u8>,
+    /// `true` where the byte must match
+    mask: Vec<bool>,
+}
+
+impl Pattern {
+    /// Parse an IDA / x64dbg style signature (`?` and `??` are wildcards)
+    pub fn parse(signature: &str) -> ParseResult<Self> {
+        let mut bytes = Vec::new();
+        let mut mask = Vec::new();
+
+        for token in signature.split_whitespace() {
+            if token.chars().all(|c| c == '?') && token.len() <= 2 {
+                bytes.push(0);
+                mask.push(false);
+            } else {
+                let byte = u8::from_str_radix(token, 16)
+                    .ok()
+                    .filter(|_| token.len() == 2)
+                    .ok_or_else(|| ParseError::parse(format!("Invalid pattern byte '{}'", token)))?;
+                bytes.push(byte);
+                mask.push(true);
+            }
+        }
+
+        Self::new(bytes, mask)
+    }
+
+    /// Create a pattern from bytes and a mask (non-zero = must match)
+    pub fn from_masked(bytes: &[u8], mask: &[u8]) -> ParseResult<Self> {
+        if bytes.len() != mask.len() {
+            return Err(ParseError::parse(format!(
+                "Pattern length {} does not match mask length {}",
+                bytes.len(),
+                mask.len()
+            )));
+        }
+        Self::new(bytes.to_vec(), mask.iter().map(|&m| m != 0).collect())
+    }
+
+    fn new(bytes: Vec<u8>, mask: Vec<bool>) -> ParseResult<Self> {
+        if !mask.iter().any(|&m| m) {
+            return Err(ParseError::parse("Pattern has no fixed bytes"));
+        }
+        Ok(Self { bytes, mask })
+    }
+
+    /// Pattern length in bytes
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Check whether the pattern is empty
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Check whether the pattern matches `data` at `offset`
+    pub fn matches_at(&self, data: &[u8], offset: usize) -> bool {
+        data.get(offset..offset + self.len()).is_some_and(|window| {
+            window
+                .iter()
+                .zip(self.bytes.iter().zip(&self.mask))
+                .all(|(&byte, (&expected, &fixed))| !fixed || byte == expected)
+        })
+    }
+
+    /// Pick the fixed byte least likely to occur in code as the anchor
+    fn anchor(&self) -> (usize, u8) {
+        (0..self.len())
+            .filter(|&i| self.mask[i])
+            .min_by_key(|&i| byte_frequency(self.bytes[i]))
+            .map(|i| (i, self.bytes[i]))
+            .expect("pattern has a fixed byte")
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> ParseResult<Self> {
+        Self::parse(s)
+    }
+}
+
+/// Rough frequency rank of a byte in compiled x86/ARM code (lower = rarer)
+fn byte_frequency(byte: u8) -> u8 {
+    match byte {
+        0x00 | 0xFF => 4,
+        0x48 | 0x8B | 0x89 | 0xCC | 0x90 | 0xE8 | 0x0F | 0x01 | 0x91 | 0xF9 | 0xB9 | 0xAA => 3,
+        0x40..=0x4F | 0x83 | 0x85 | 0xC3 | 0xD5 | 0xF8 | 0xA9 | 0xE0 | 0x03 | 0x02 => 2,
+        _ => 1,
+    }
+}
+
+/// Matches of one pattern in a scan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternMatches {
+    /// Pattern name
+    pub name: String,
+    /// Matching addresses, ascending
+    pub addresses: Vec<Address>,
+}
+
+/// Compiled set of named patterns
+#[derive(Debug, Clone, Default)]
+pub struct Scanner {
+    names: Vec<String>,
+    patterns: Vec<Pattern>,
+    /// (anchor byte, pattern index, anchor offset within the pattern)
+    anchors: Vec<(u8, usize, usize)>,
+}
+
+impl Scanner {
+    /// Create an empty scanner
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a named pattern
+    pub fn add(&mut self, name: impl Into<String>, pattern: Pattern) {
+        let (offset, byte) = pattern.anchor();
+        let index = self.patterns.len();
+        let at = self.anchors.partition_point(|&(b, _, _)| b <= byte);
+        self.anchors.insert(at, (byte, index, offset));
+        self.names.push(name.into());
+        self.patterns.push(pattern);
+    }
+
+    /// Parse and add a named signature
+    pub fn add_signature(&mut self, name: impl Into<String>, signature: &str) -> ParseResult<()> {
+        self.add(name, Pattern::parse(signature)?);
+        Ok(())
+    }
+
+    /// Parse a signature set
+    ///
+    /// One `name = 48 8B ?? ??` entry per line; blank lines and lines starting
+    /// with `#` or `//` are ignored.
+    pub fn parse_signatures(text: &str) -> ParseResult<Self> {
+        let mut scanner = Self::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+                continue;
+            }
+            let (name, signature) = line
+                .split_once('=')
+                .ok_or_else(|| ParseError::parse(format!("Signature line {}: expected 'name = pattern'", number + 1)))?;
+            scanner
+                .add_signature(name.trim(), signature)
+                .map_err(|e| ParseError::parse(format!("Signature line {}: {}", number + 1, e)))?;
+        }
+        Ok(scanner)
+    }
+
+    /// Load a signature set from a file
+    pub fn load(path: &Path) -> ParseResult<Self> {
+        Self::parse_signatures(&std::fs::read_to_string(path)?)
+    }
+
+    /// Number of patterns
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// Check whether the scanner has no patterns
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Scan raw data, returning match offsets per pattern in insertion order
+    pub fn scan_bytes(&self, data: &[u8]) -> Vec<Vec<usize>> {
+        let chunks: Vec<usize> = (0..data.len()).step_by(CHUNK_SIZE).collect();
+        let found: Vec<(usize, usize)> = chunks
+            .par_iter()
+            .flat_map_iter(|&start| self.scan_chunk(data, start))
+            .collect();
+
+        let mut results = vec![Vec::new(); self.patterns.len()];
+        for (index, offset) in found {
+            results[index].push(offset);
+        }
+        for offsets in &mut results {
+            offsets.sort_unstable();
+        }
+        results
+    }
+
+    /// Scan all executable sections of a binary
+    pub fn scan(&self, binary: &dyn BinaryFile) -> Vec<PatternMatches> {
+        let mut results: Vec<PatternMatches> = self
+            .names
+            .iter()
+            .map(|name| PatternMatches { name: name.clone(), addresses: Vec::new() })
+            .collect();
+
+        for section in binary.executable_sections() {
+            let Some(data) = binary.section_data(section) else {
+                continue;
+            };
+            for (matches, offsets) in results.iter_mut().zip(self.scan_bytes(data)) {
+                matches
+                    .addresses
+                    .extend(offsets.into_iter().map(|offset| section.virtual_address.offset(offset as i64)));
+            }
+        }
+
+        for matches in &mut results {
+            matches.addresses.sort_by_key(Address::as_u64);
+        }
+        results
+    }
+
+    /// Find matches starting in `[start, start + CHUNK_SIZE)`
+    fn scan_chunk(&self, data: &[u8], start: usize) -> Vec<(usize, usize)> {
+        let max_len = self.patterns.iter().map(Pattern::len).max().unwrap_or(0);
+        let chunk_end = (start + CHUNK_SIZE).min(data.len());
+        let window_end = (chunk_end + max_len).min(data.len());
+        let window = &data[start..window_end];
+
+        let mut bytes: Vec<u8> = self.anchors.iter().map(|&(b, _, _)| b).collect();
+        bytes.dedup();
+
+        let mut found = Vec::new();
+        for group in bytes.chunks(3) {
+            let hits: Box<dyn Iterator<Item = usize>> = match *group {
+                [a] => Box::new(memchr::memchr_iter(a, window)),
+                [a, b] => Box::new(memchr::memchr2_iter(a, b, window)),
+                [a, b, c] => Box::new(memchr::memchr3_iter(a, b, c, window)),
+                _ => unreachable!(),
+            };
+            for hit in hits {
+                let position = start + hit;
+                let byte = data[position];
+                let first = self.anchors.partition_point(|&(b, _, _)| b < byte);
+                for &(_, index, offset) in self.anchors[first..].iter().take_while(|&&(b, _, _)| b == byte) {
+                    let Some(match_start) = position.checked_sub(offset) else {
+                        continue;
+                    };
+                    if match_start >= start
+                        && match_start < chunk_end
+                        && self.patterns[index].matches_at(data, match_start)
+                    {
+                        found.push((index, match_start));
+                    }
+                }
+            }
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pattern() {
+        let pattern = Pattern::parse("48 8B ?? ?? 05 ?").unwrap();
+        assert_eq!(pattern.len(), 6);
+        assert!(pattern.matches_at(&[0x48, 0x8B, 0x12, 0x34, 0x05, 0x99], 0));
+        assert!(!pattern.matches_at(&[0x48, 0x8B, 0x12, 0x34, 0x06, 0x99], 0));
+        assert!(!pattern.matches_at(&[0x48, 0x8B, 0x12, 0x34, 0x05], 0));
+
+        assert!(Pattern::parse("48 8G").is_err());
+        assert!(Pattern::parse("?? ?").is_err());
+        assert!(Pattern::from_masked(&[1, 2], &[1]).is_err());
+    }
+
+    #[test]
+    fn test_scan_multiple_patterns() {
+        let mut data = vec![0x90u8; 3 * CHUNK_SIZE];
+        // Straddles the first chunk boundary
+        let boundary = CHUNK_SIZE - 2;
+        data[boundary..boundary + 4].copy_from_slice(&[0x48, 0x8B, 0x05, 0x11]);
+        data[10..14].copy_from_slice(&[0x48, 0x8B, 0x05, 0x22]);
+        data[2 * CHUNK_SIZE + 7..2 * CHUNK_SIZE + 10].copy_from_slice(&[0xE8, 0xAB, 0xCD]);
+
+        let scanner = Scanner::parse_signatures(
+            "# test set\n\
+             load = 48 8B 05 ??\n\
+             \n\
+             call = E8 ?? CD\n\
+             missing = DE AD BE EF\n",
+        )
+        .unwrap();
+        assert_eq!(scanner.len(), 3);
+
+        let results = scanner.scan_bytes(&data);
+        assert_eq!(results[0], [10, boundary]);
+        assert_eq!(results[1], [2 * CHUNK_SIZE + 7]);
+        assert!(results[2].is_empty());
+
+        assert!(Scanner::parse_signatures("no separator").is_err());
+    }
+}
@@ -0,0 +1,343 @@
+//! Native string extraction
+//!
+//! Finds printable ASCII/UTF-8 and UTF-16LE runs in section data, so strings
+//! compiled into native code (hostnames, URLs, keys) can be browsed and
+//! traced through the [`XrefIndex`] alongside the metadata string literals.
+
+use crate::common::{BinaryFile, Section};
+use crate::xref::{Xref, XrefIndex};
+use endfield_core::Address;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Character encoding of an extracted string
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StringEncoding {
+    /// 7-bit ASCII
+    Ascii,
+    /// UTF-8 with at least one multi-byte character
+    Utf8,
+    /// UTF-16 little-endian
+    Utf16Le,
+}
+
+/// String found in a binary
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NativeString {
+    /// Virtual address of the first byte
+    pub address: Address,
+    /// Encoding
+    pub encoding: StringEncoding,
+    /// Length in characters
+    pub length: usize,
+    /// Size in bytes, excluding any terminator
+    pub size: usize,
+    /// Decoded text
+    pub value: String,
+}
+
+impl NativeString {
+    /// Instructions referencing the string
+    pub fn references<'a>(&self, index: &'a XrefIndex) -> &'a [Xref] {
+        index.to(self.address)
+    }
+}
+
+/// String extraction settings
+#[derive(Debug, Clone)]
+pub struct StringOptions {
+    /// Minimum length in characters
+    pub min_length: usize,
+    /// Extract ASCII and UTF-8 strings
+    pub utf8: bool,
+    /// Extract UTF-16LE strings
+    pub utf16: bool,
+    /// Sections to scan by name; empty scans all data sections
+    pub sections: Vec<String>,
+}
+
+impl Default for StringOptions {
+    fn default() -> Self {
+        Self {
+            min_length: 4,
+            utf8: true,
+            utf16: true,
+            sections: Vec::new(),
+        }
+    }
+}
+
+/// Extract strings from the sections selected by `options`, sorted by address
+pub fn extract<B: BinaryFile + ?Sized>(binary: &B, options: &StringOptions) -> Vec<NativeString> {
+    let sections: Vec<&Section> = if options.sections.is_empty() {
+        binary.data_sections()
+    } else {
+        binary
+            .sections()
+            .iter()
+            .filter(|s| options.sections.contains(&s.name))
+            .collect()
+    };
+
+    let work: Vec<(u64, &[u8])> = sections
+        .into_iter()
+        .filter_map(|section| Some((section.virtual_address.as_u64(), binary.section_data(section)?)))
+        .collect();
+
+    let mut strings: Vec<NativeString> = work
+        .into_par_iter()
+        .flat_map_iter(|(va, data)| scan(data, va, options))
+        .collect();
+    strings.sort_by_key(|s| s.address.as_u64());
+    strings
+}
+
+/// Extract strings from one buffer mapped at `va`
+pub fn scan(data: &[u8], va: u64, options: &StringOptions) -> Vec<NativeString> {
+    let min_length = options.min_length.max(1);
+    let mut strings = if options.utf8 { scan_utf8(data, va, min_length) } else { Vec::new() };
+
+    if options.utf16 {
+        // ASCII text read at an even offset decodes as valid CJK code units;
+        // keep only UTF-16 runs that don't overlap a UTF-8 string
+        let narrow: Vec<(u64, u64)> = strings
+            .iter()
+            .map(|s| (s.address.as_u64(), s.address.as_u64() + s.size as u64))
+            .collect();
+        let overlaps = |start: u64, end: u64| {
+            let index = narrow.partition_point(|&(_, narrow_end)| narrow_end <= start);
+            narrow.get(index).is_some_and(|&(narrow_start, _)| narrow_start < end)
+        };
+        strings.extend(
+            scan_utf16(data, va, min_length)
+                .into_iter()
+                .filter(|s| !overlaps(s.address.as_u64(), s.address.as_u64() + s.size as u64)),
+        );
+        strings.sort_by_key(|s| s.address.as_u64());
+    }
+
+    strings
+}
+
+/// Check whether a decoded UTF-8 character continues a run
+///
+/// Valid multi-byte UTF-8 rarely occurs by chance, so any printable
+/// character is kept.
+fn is_printable(c: char) -> bool {
+    matches!(c, '\t' | '\n' | '\r') || !c.is_control()
+}
+
+/// Check whether a UTF-16 code unit belongs in extracted text
+///
+/// Almost every code unit decodes to some character, so only the scripts and
+/// symbols found in game strings are accepted; surrogates, private use and
+/// unassigned code points end a run.
+fn is_wide_text(c: char) -> bool {
+    matches!(
+        c,
+        '\t' | '\n' | '\r'
+            | ' '..='~'
+            | '\u{A0}'..='\u{24F}' // Latin-1 Supplement, Latin Extended-A and -B
+            | '\u{391}'..='\u{3A1}' // Greek
+            | '\u{3A3}'..='\u{3C9}'
+            | '\u{400}'..='\u{4FF}' // Cyrillic
+            | '\u{2010}'..='\u{205E}' // General Punctuation
+            | '\u{20A0}'..='\u{20C0}' // Currency Symbols
+            | '\u{2100}'..='\u{23FF}' // Letterlike symbols, arrows, mathematical operators
+            | '\u{2460}'..='\u{27BF}' // Enclosed alphanumerics, box drawing, shapes, dingbats
+            | '\u{3000}'..='\u{303F}' // CJK Symbols and Punctuation
+            | '\u{3041}'..='\u{3096}' // Hiragana
+            | '\u{3099}'..='\u{30FF}' // Katakana
+            | '\u{3131}'..='\u{318E}' // Hangul Compatibility Jamo
+            | '\u{4E00}'..='\u{9FFF}' // CJK Unified Ideographs
+            | '\u{AC00}'..='\u{D7A3}' // Hangul Syllables
+            | '\u{FF01}'..='\u{FF9F}' // Fullwidth and halfwidth forms
+            | '\u{1F300}'..='\u{1F64F}' // Pictographs and emoticons
+    )
+}
+
+/// Printable ASCII and UTF-8 runs
+fn scan_utf8(data: &[u8], va: u64, min_length: usize) -> Vec<NativeString> {
+    let mut strings = Vec::new();
+    let mut start = 0;
+    let mut length = 0;
+    let mut offset = 0;
+
+    let mut flush = |start: usize, end: usize, length: usize| {
+        if length >= min_length {
+            let bytes = &data[start..end];
+            strings.push(NativeString {
+                address: Address::new(va + start as u64),
+                encoding: if bytes.is_ascii() { StringEncoding::Ascii } else { StringEncoding::Utf8 },
+                length,
+                size: bytes.len(),
+                value: String::from_utf8_lossy(bytes).into_owned(),
+            });
+        }
+    };
+
+    while offset < data.len() {
+        let width = match data[offset] {
+            0x00..=0x7F => 1,
+            0xC2..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF4 => 4,
+            _ => 0,
+        };
+        let c = data
+            .get(offset..offset + width)
+            .filter(|_| width > 0)
+            .and_then(|bytes| std::str::from_utf8(bytes).ok())
+            .and_then(|s| s.chars().next())
+            .filter(|&c| is_printable(c));
+
+        match c {
+            Some(_) => {
+                if length == 0 {
+                    start = offset;
+                }
+                length += 1;
+                offset += width;
+            }
+            None => {
+                flush(start, offset, length);
+                length = 0;
+                offset += 1;
+            }
+        }
+    }
+    flush(start, offset, length);
+    strings
+}
+
+/// Printable UTF-16LE runs at even offsets, ending in a NUL code unit
+///
+/// Wide characters cover much of the code unit space, so the terminator is
+/// what keeps arbitrary data from decoding as CJK text.
+fn scan_utf16(data: &[u8], va: u64, min_length: usize) -> Vec<NativeString> {
+    let units: Vec<u16> = data.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect();
+    let mut strings = Vec::new();
+    let mut run = String::new();
+    let mut length = 0;
+    let mut start = 0;
+    let mut index = 0;
+
+    let mut flush = |run: &mut String, start: usize, end: usize, length: usize| {
+        if length >= min_length {
+            strings.push(NativeString {
+                address: Address::new(va + 2 * start as u64),
+                encoding: StringEncoding::Utf16Le,
+                length,
+                size: 2 * (end - start),
+                value: std::mem::take(run),
+            });
+        }
+        run.clear();
+    };
+
+    while index < units.len() {
+        let (c, width) = match char::decode_utf16(units[index..].iter().copied().take(2)).next() {
+            Some(Ok(c)) => (Some(c).filter(|&c| is_wide_text(c)), c.len_utf16()),
+            _ => (None, 1),
+        };
+        match c {
+            Some(c) => {
+                if length == 0 {
+                    start = index;
+                }
+                run.push(c);
+                length += 1;
+                index += width;
+            }
+            None => {
+                let terminated = units[index] == 0;
+                flush(&mut run, start, index, if terminated { length } else { 0 });
+                length = 0;
+                index += 1;
+            }
+        }
+    }
+    strings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_encodings() {
+        let mut data = b"\x01\x02https://api.example.com\0ab\0".to_vec();
+        data.extend("\0服务器地址\0".as_bytes());
+        data.extend([0u8; 2]);
+        data.extend("gate.hg.com".encode_utf16().flat_map(u16::to_le_bytes));
+        data.extend([0, 0, 0xFF, 0xFF]);
+
+        let strings = scan(&data, 0x1000, &StringOptions::default());
+        let found: Vec<(u64, StringEncoding, usize, &str)> = strings
+            .iter()
+            .map(|s| (s.address.as_u64(), s.encoding, s.length, s.value.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (0x1002, StringEncoding::Ascii, 23, "https://api.example.com"),
+                (0x101E, StringEncoding::Utf8, 5, "服务器地址"),
+                (0x1030, StringEncoding::Utf16Le, 11, "gate.hg.com"),
+            ]
+        );
+        assert_eq!(strings[1].size, 15);
+        assert_eq!(strings[2].size, 22);
+
+        let options = StringOptions { min_length: 24, ..Default::default() };
+        assert!(scan(&data, 0x1000, &options).is_empty());
+        let options = StringOptions { utf16: false, ..Default::default() };
+        assert_eq!(scan(&data, 0x1000, &options).len(), 2);
+    }
+
+    #[test]
+    fn test_scan_utf8_scripts() {
+        let texts = ["مرحبا بالعالم", "שלום עולם", "สวัสดีชาวโลก", "नमस्ते दुनिया", "Tiếng Việt"];
+        let mut data = Vec::new();
+        for text in texts {
+            data.extend(text.as_bytes());
+            data.push(0);
+        }
+
+        let options = StringOptions { utf16: false, ..Default::default() };
+        let strings = scan(&data, 0x1000, &options);
+        let found: Vec<&str> = strings.iter().map(|s| s.value.as_str()).collect();
+        assert_eq!(found, texts);
+        assert!(strings.iter().all(|s| s.encoding == StringEncoding::Utf8));
+    }
+
+    #[test]
+    fn test_scan_rejects_noise() {
+        let wide = |text: &str| -> Vec<u8> { text.encode_utf16().chain([0]).flat_map(u16::to_le_bytes).collect() };
+        let options = StringOptions { utf8: false, ..Default::default() };
+
+        // Private use, unassigned and unpaired surrogate code units, then an unterminated run
+        let mut data = wide("\u{E000}\u{E001}\u{E002}\u{E003}");
+        data.extend(wide("\u{0378}\u{0379}\u{0380}\u{0381}"));
+        data.extend([0x00, 0xD8, 0x41, 0x00, 0x42, 0x00, 0x43, 0x00, 0x44, 0x00, 0x00, 0xDC, 0, 0]);
+        data.extend("文件名称".encode_utf16().flat_map(u16::to_le_bytes));
+        data.extend([0xFF, 0xFF]);
+        assert!(scan(&data, 0x1000, &options).is_empty());
+
+        data.extend(wide("文件名称"));
+        let strings = scan(&data, 0x1000, &options);
+        assert_eq!(strings.len(), 1);
+        assert_eq!(strings[0].value, "文件名称");
+
+        // xorshift noise
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        let noise: Vec<u8> = (0..0x10000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect();
+        assert!(scan(&noise, 0x1000, &options).is_empty());
+    }
+}
@@ -0,0 +1,236 @@
+//! Helpers shared by the unit tests here and, through the `test-util`
+//! feature, by crates building on the parsers
+
+use crate::common::{checked_slice, BinaryFile, BinaryReader, Function, Section, SectionFlags, Symbol};
+use crate::error::{ParseError, ParseResult};
+use crate::pe::{
+    IMAGE_FILE_MACHINE_AMD64, IMAGE_ORDINAL_FLAG64, IMAGE_SCN_CNT_INITIALIZED_DATA, IMAGE_SCN_MEM_EXECUTE,
+    IMAGE_SCN_MEM_READ, PE32PLUS_MAGIC,
+};
+use endfield_core::{Address, Architecture, BinaryFormat, Platform};
+
+/// Copy `bytes` into `image` at `offset`
+pub fn put(image: &mut [u8], offset: usize, bytes: &[u8]) {
+    image[offset..offset + bytes.len()].copy_from_slice(bytes);
+}
+
+/// PE32+ image with `.text` at RVA 0x1000 and `.rdata` at RVA 0x2000
+/// holding export, import and exception directories
+pub fn sample_pe64() -> Vec<u8> {
+    let mut image = vec![0u8; 0xA00];
+    let rdata = |rva: usize| rva - 0x2000 + 0x600;
+
+    put(&mut image, 0, b"MZ");
+    put(&mut image, 0x3C, &0x40u32.to_le_bytes());
+    put(&mut image, 0x40, b"PE\0\0");
+    put(&mut image, 0x44, &IMAGE_FILE_MACHINE_AMD64.to_le_bytes());
+    put(&mut image, 0x46, &2u16.to_le_bytes());
+    put(&mut image, 0x54, &0xF0u16.to_le_bytes());
+
+    let optional = 0x58;
+    put(&mut image, optional, &PE32PLUS_MAGIC.to_le_bytes());
+    put(&mut image, optional + 16, &0x1000u32.to_le_bytes());
+    put(&mut image, optional + 24, &0x1_8000_0000u64.to_le_bytes());
+    put(&mut image, optional + 108, &16u32.to_le_bytes());
+    for (index, rva, size) in [(0usize, 0x2000u32, 0x100u32), (1, 0x2100, 0x28), (3, 0x2200, 36)] {
+        put(&mut image, optional + 112 + index * 8, &rva.to_le_bytes());
+        put(&mut image, optional + 116 + index * 8, &size.to_le_bytes());
+    }
+
+    let sections = optional + 0xF0;
+    for (i, (name, rva, raw, raw_size, flags)) in [
+        (b".text\0\0\0", 0x1000u32, 0x400u32, 0x200u32, IMAGE_SCN_MEM_EXECUTE | IMAGE_SCN_MEM_READ),
+        (b".rdata\0\0", 0x2000, 0x600, 0x400, IMAGE_SCN_MEM_READ | IMAGE_SCN_CNT_INITIALIZED_DATA),
+    ]
+    .iter()
+    .enumerate()
+    {
+        let header = sections + i * 40;
+        put(&mut image, header, *name);
+        put(&mut image, header + 8, &0x400u32.to_le_bytes());
+        put(&mut image, header + 12, &rva.to_le_bytes());
+        put(&mut image, header + 16, &raw_size.to_le_bytes());
+        put(&mut image, header + 20, &raw.to_le_bytes());
+        put(&mut image, header + 36, &flags.to_le_bytes());
+    }
+
+    // il2cpp_init: xor eax, eax; ret
+    put(&mut image, 0x400, &[0x31, 0xC0, 0xC3]);
+
+    // Exports: il2cpp_init, an ordinal-only function and a forwarder
+    let exports = rdata(0x2000);
+    put(&mut image, exports + 16, &1u32.to_le_bytes());
+    put(&mut image, exports + 20, &3u32.to_le_bytes());
+    put(&mut image, exports + 24, &2u32.to_le_bytes());
+    put(&mut image, exports + 28, &0x2040u32.to_le_bytes());
+    put(&mut image, exports + 32, &0x2050u32.to_le_bytes());
+    put(&mut image, exports + 36, &0x2058u32.to_le_bytes());
+    for (i, rva) in [0x1000u32, 0x1080, 0x2070].iter().enumerate() {
+        put(&mut image, rdata(0x2040) + i * 4, &rva.to_le_bytes());
+    }
+    put(&mut image, rdata(0x2050), &0x2060u32.to_le_bytes());
+    put(&mut image, rdata(0x2054), &0x2090u32.to_le_bytes());
+    put(&mut image, rdata(0x2058), &[0, 0, 2, 0]);
+    put(&mut image, rdata(0x2060), b"il2cpp_init\0");
+    put(&mut image, rdata(0x2070), b"NTDLL.RtlFree\0");
+    put(&mut image, rdata(0x2090), b"il2cpp_free\0");
+
+    // Imports: KERNEL32.dll!GetProcAddress and ordinal 16
+    let imports = rdata(0x2100);
+    put(&mut image, imports, &0x2140u32.to_le_bytes());
+    put(&mut image, imports + 12, &0x2180u32.to_le_bytes());
+    put(&mut image, imports + 16, &0x2160u32.to_le_bytes());
+    for table in [0x2140, 0x2160] {
+        put(&mut image, rdata(table), &0x2190u64.to_le_bytes());
+        put(&mut image, rdata(table) + 8, &(IMAGE_ORDINAL_FLAG64 | 16).to_le_bytes());
+    }
+    put(&mut image, rdata(0x2180), b"KERNEL32.dll\0");
+    put(&mut image, rdata(0x2192), b"GetProcAddress\0");
+
+    // .pdata
+    for (i, (begin, end)) in [(0x1000u32, 0x1020u32), (0x1040, 0x1060), (0x1080, 0x1090)]
+        .iter()
+        .enumerate()
+    {
+        put(&mut image, rdata(0x2200) + i * 12, &begin.to_le_bytes());
+        put(&mut image, rdata(0x2204) + i * 12, &end.to_le_bytes());
+    }
+
+    image
+}
+
+/// Binary assembled section by section, for code that only needs the [`BinaryFile`] view
+pub struct TestBinary {
+    pub format: BinaryFormat,
+    pub architecture: Architecture,
+    pub image_base: Address,
+    pub entry_point: Address,
+    pub imported_functions: Vec<String>,
+    pub symbols: Vec<Symbol>,
+    pub functions: Vec<Function>,
+    data: Vec<u8>,
+    sections: Vec<Section>,
+}
+
+impl TestBinary {
+    /// ARM64 binary with no sections and a zero image base
+    pub fn new(format: BinaryFormat) -> Self {
+        Self {
+            format,
+            architecture: Architecture::Arm64,
+            image_base: Address::ZERO,
+            entry_point: Address::ZERO,
+            imported_functions: Vec::new(),
+            symbols: Vec::new(),
+            functions: Vec::new(),
+            data: Vec::new(),
+            sections: Vec::new(),
+        }
+    }
+
+    /// ELF binary with no sections, enough for metadata-only dumps
+    pub fn empty() -> Self {
+        Self::new(BinaryFormat::ELF)
+    }
+
+    /// ARM64 code mapped at 0x1000 in a single executable section
+    pub fn code(words: &[u32]) -> Self {
+        let code: Vec<u8> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+        Self { image_base: Address::new(0x1000), ..Self::empty() }.section(
+            ".text",
+            0x1000,
+            &code,
+            SectionFlags::READ | SectionFlags::EXECUTE,
+        )
+    }
+
+    /// Pointer-sized `words` mapped at `base` in a single data section
+    pub fn words(base: u64, words: &[u64], architecture: Architecture) -> Self {
+        let data: Vec<u8> = match architecture.pointer_size() {
+            4 => words.iter().flat_map(|&w| (w as u32).to_le_bytes()).collect(),
+            _ => words.iter().flat_map(|&w| w.to_le_bytes()).collect(),
+        };
+        Self { architecture, image_base: Address::new(base), ..Self::empty() }.section(
+            ".data",
+            base,
+            &data,
+            SectionFlags::READ | SectionFlags::WRITE,
+        )
+    }
+
+    /// Append a section holding `bytes` mapped at `va`
+    pub fn section(mut self, name: &str, va: u64, bytes: &[u8], characteristics: SectionFlags) -> Self {
+        self.sections.push(Section {
+            name: name.to_string(),
+            virtual_address: Address::new(va),
+            virtual_size: bytes.len() as u64,
+            raw_offset: self.data.len() as u64,
+            raw_size: bytes.len() as u64,
+            characteristics,
+        });
+        self.data.extend_from_slice(bytes);
+        self
+    }
+}
+
+impl BinaryFile for TestBinary {
+    fn format(&self) -> BinaryFormat {
+        self.format
+    }
+
+    fn architecture(&self) -> Architecture {
+        self.architecture
+    }
+
+    fn platform(&self) -> Platform {
+        Platform::Unknown
+    }
+
+    fn image_base(&self) -> Address {
+        self.image_base
+    }
+
+    fn entry_point(&self) -> Address {
+        self.entry_point
+    }
+
+    fn sections(&self) -> &[Section] {
+        &self.sections
+    }
+
+    fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    fn functions(&self) -> &[Function] {
+        &self.functions
+    }
+
+    fn imported_functions(&self) -> Vec<String> {
+        self.imported_functions.clone()
+    }
+
+    fn va_to_offset(&self, va: Address) -> Option<u64> {
+        let section = self.sections.iter().find(|s| s.contains_va(va.as_u64()))?;
+        Some(section.raw_offset + (va.as_u64() - section.virtual_address.as_u64()))
+    }
+
+    fn offset_to_va(&self, offset: u64) -> Option<Address> {
+        let section = self.sections.iter().find(|s| s.contains_offset(offset))?;
+        Some(Address::new(section.virtual_address.as_u64() + (offset - section.raw_offset)))
+    }
+
+    fn read_va(&self, va: Address, size: usize) -> ParseResult<&[u8]> {
+        let offset = self.va_to_offset(va).ok_or(ParseError::AddressOutOfBounds(va.as_u64()))?;
+        checked_slice(&self.data, offset, size as u64)
+    }
+
+    fn read_string_va(&self, va: Address, max_len: usize) -> ParseResult<String> {
+        let offset = self.va_to_offset(va).ok_or(ParseError::AddressOutOfBounds(va.as_u64()))?;
+        BinaryReader::new(&self.data, true).string_at(offset, max_len)
+    }
+
+    fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
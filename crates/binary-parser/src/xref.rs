@@ -0,0 +1,279 @@
+//! Cross-reference index
+//!
+//! Sweeps executable sections for direct calls and branches and for
+//! PC-relative data loads (x86 RIP-relative operands, AArch64 ADR, ADRP +
+//! ADD/LDR pairs and literal loads), indexed by target address.
+
+use crate::common::BinaryFile;
+use crate::disasm::sign_extend;
+use endfield_core::{Address, Architecture};
+use iced_x86::{Decoder, DecoderOptions, FlowControl, OpKind};
+use rayon::prelude::*;
+
+/// Bytes swept per parallel work item for fixed-width code
+const CHUNK_SIZE: usize = 1 << 20;
+
+/// Kind of reference
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum XrefKind {
+    /// Direct call
+    Call,
+    /// Direct conditional or unconditional branch
+    Jump,
+    /// PC-relative data load or address computation
+    Load,
+}
+
+/// Reference from an instruction to an address
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Xref {
+    /// Referencing instruction
+    pub from: Address,
+    /// Referenced address
+    pub to: Address,
+    /// Reference kind
+    pub kind: XrefKind,
+}
+
+/// References indexed by target and by source
+#[derive(Debug, Clone, Default)]
+pub struct XrefIndex {
+    /// Sorted by (to, from)
+    xrefs: Vec<Xref>,
+    /// Indices into `xrefs` sorted by from
+    by_source: Vec<usize>,
+}
+
+impl XrefIndex {
+    /// Sweep all executable sections of a binary
+    pub fn build<B: BinaryFile + ?Sized>(binary: &B) -> Self {
+        let architecture = binary.architecture();
+        let mut work = Vec::new();
+        for section in binary.executable_sections() {
+            let Some(data) = binary.section_data(section) else {
+                continue;
+            };
+            let va = section.virtual_address.as_u64();
+            if architecture == Architecture::Arm64 {
+                for start in (0..data.len()).step_by(CHUNK_SIZE) {
+                    let end = (start + CHUNK_SIZE).min(data.len());
+                    work.push((va + start as u64, &data[start..end]));
+                }
+            } else {
+                work.push((va, data));
+            }
+        }
+
+        let xrefs = work
+            .into_par_iter()
+            .flat_map_iter(|(va, code)| match architecture {
+                Architecture::X64 => sweep_x86(code, va, 64),
+                Architecture::X86 => sweep_x86(code, va, 32),
+                Architecture::Arm64 => sweep_arm64(code, va),
+                _ => Vec::new(),
+            })
+            .collect();
+        Self::from_xrefs(xrefs)
+    }
+
+    /// Build an index from already collected references
+    pub fn from_xrefs(mut xrefs: Vec<Xref>) -> Self {
+        xrefs.sort_by_key(|xref| (xref.to.as_u64(), xref.from.as_u64()));
+        let mut by_source: Vec<usize> = (0..xrefs.len()).collect();
+        by_source.sort_by_key(|&i| xrefs[i].from.as_u64());
+        Self { xrefs, by_source }
+    }
+
+    /// References to `target`
+    pub fn to(&self, target: Address) -> &[Xref] {
+        let start = self.xrefs.partition_point(|xref| xref.to.as_u64() < target.as_u64());
+        let end = self.xrefs.partition_point(|xref| xref.to.as_u64() <= target.as_u64());
+        &self.xrefs[start..end]
+    }
+
+    /// References made by instructions in `[start, end)`
+    pub fn from_range(&self, start: Address, end: Address) -> Vec<Xref> {
+        let first = self.by_source.partition_point(|&i| self.xrefs[i].from.as_u64() < start.as_u64());
+        self.by_source[first..]
+            .iter()
+            .map(|&i| self.xrefs[i])
+            .take_while(|xref| xref.from.as_u64() < end.as_u64())
+            .collect()
+    }
+
+    /// All references, ordered by target
+    pub fn iter(&self) -> impl Iterator<Item = &Xref> {
+        self.xrefs.iter()
+    }
+
+    /// Number of references
+    pub fn len(&self) -> usize {
+        self.xrefs.len()
+    }
+
+    /// Check whether the index is empty
+    pub fn is_empty(&self) -> bool {
+        self.xrefs.is_empty()
+    }
+}
+
+fn sweep_x86(code: &[u8], va: u64, bitness: u32) -> Vec<Xref> {
+    let mut decoder = Decoder::with_ip(bitness, code, va, DecoderOptions::NONE);
+    let mut instruction = iced_x86::Instruction::default();
+    let mut xrefs = Vec::new();
+
+    while decoder.can_decode() {
+        decoder.decode_out(&mut instruction);
+        if instruction.is_invalid() {
+            continue;
+        }
+        let from = Address::new(instruction.ip());
+
+        let is_near_branch = matches!(
+            instruction.op0_kind(),
+            OpKind::NearBranch16 | OpKind::NearBranch32 | OpKind::NearBranch64
+        );
+        let kind = match instruction.flow_control() {
+            FlowControl::Call if is_near_branch => Some(XrefKind::Call),
+            FlowControl::UnconditionalBranch | FlowControl::ConditionalBranch if is_near_branch => Some(XrefKind::Jump),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            xrefs.push(Xref { from, to: Address::new(instruction.near_branch_target()), kind });
+        } else if instruction.is_ip_rel_memory_operand() {
+            xrefs.push(Xref { from, to: Address::new(instruction.ip_rel_memory_address()), kind: XrefKind::Load });
+        }
+    }
+    xrefs
+}
+
+fn sweep_arm64(code: &[u8], va: u64) -> Vec<Xref> {
+    // Addresses held in registers after ADRP / ADR / ADD
+    let mut known: [Option<u64>; 32] = [None; 32];
+    let mut xrefs = Vec::new();
+
+    for (index, word) in code.chunks_exact(4).enumerate() {
+        let pc = va + index as u64 * 4;
+        let w = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+        if let Some((to, kind)) = arm64_reference(w, pc, &mut known) {
+            xrefs.push(Xref { from: Address::new(pc), to: Address::new(to), kind });
+        }
+    }
+    xrefs
+}
+
+/// Reference made by one AArch64 instruction, updating tracked registers
+fn arm64_reference(w: u32, pc: u64, known: &mut [Option<u64>; 32]) -> Option<(u64, XrefKind)> {
+    let rd = (w & 0x1F) as usize;
+    let rn = ((w >> 5) & 0x1F) as usize;
+    let relative = |imm: i64| pc.wrapping_add((imm * 4) as u64);
+
+    // B / BL
+    if w & 0x7C000000 == 0x14000000 {
+        let is_call = w >> 31 == 1;
+        *known = [None; 32];
+        let kind = if is_call { XrefKind::Call } else { XrefKind::Jump };
+        return Some((relative(sign_extend(w & 0x03FF_FFFF, 26)), kind));
+    }
+    // B.cond, CBZ / CBNZ
+    if w & 0xFF000010 == 0x54000000 || w & 0x7E000000 == 0x34000000 {
+        return Some((relative(sign_extend((w >> 5) & 0x7FFFF, 19)), XrefKind::Jump));
+    }
+    // TBZ / TBNZ
+    if w & 0x7E000000 == 0x36000000 {
+        return Some((relative(sign_extend((w >> 5) & 0x3FFF, 14)), XrefKind::Jump));
+    }
+    // RET / BR / BLR
+    if w & 0xFF9FFC1F == 0xD61F0000 {
+        *known = [None; 32];
+        return None;
+    }
+    // ADR / ADRP
+    if w & 0x1F000000 == 0x10000000 {
+        let imm = sign_extend((((w >> 5) & 0x7FFFF) << 2) | ((w >> 29) & 3), 21);
+        if w >> 31 == 1 {
+            known[rd] = Some((pc & !0xFFF).wrapping_add((imm << 12) as u64));
+            return None;
+        }
+        let target = pc.wrapping_add(imm as u64);
+        known[rd] = Some(target);
+        return Some((target, XrefKind::Load));
+    }
+    // ADD (immediate, no flags)
+    if w & 0x7F800000 == 0x11000000 {
+        let imm = (((w >> 10) & 0xFFF) as u64) << if (w >> 22) & 1 == 1 { 12 } else { 0 };
+        let target = known[rn].map(|base| base.wrapping_add(imm));
+        known[rd] = target;
+        return target.map(|target| (target, XrefKind::Load));
+    }
+    // LDR (literal)
+    if w & 0x3B000000 == 0x18000000 {
+        known[rd] = None;
+        return Some((relative(sign_extend((w >> 5) & 0x7FFFF, 19)), XrefKind::Load));
+    }
+    // LDR / STR (unsigned immediate), including SIMD registers
+    if w & 0x3B000000 == 0x39000000 {
+        let is_simd = (w >> 26) & 1 == 1;
+        let opc = (w >> 22) & 3;
+        let scale = if is_simd && opc >= 2 { 4 } else { w >> 30 };
+        let target = known[rn].map(|base| base.wrapping_add((((w >> 10) & 0xFFF) as u64) << scale));
+        if opc != 0 && !is_simd {
+            known[rd] = None;
+        }
+        return target.map(|target| (target, XrefKind::Load));
+    }
+
+    // Most other data-processing instructions write Rd
+    known[rd] = None;
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sweep_x86() {
+        // call +5; lea rcx, [rip+0x20]; jne -0x0E
+        let code = [0xE8, 0x05, 0x00, 0x00, 0x00, 0x48, 0x8D, 0x0D, 0x20, 0x00, 0x00, 0x00, 0x75, 0xF2];
+        let index = XrefIndex::from_xrefs(sweep_x86(&code, 0x1000, 64));
+
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.to(Address::new(0x100A))[0].kind, XrefKind::Call);
+        assert_eq!(index.to(Address::new(0x102C))[0].kind, XrefKind::Load);
+        assert_eq!(index.to(Address::new(0x1000))[0].from, Address::new(0x100C));
+        assert_eq!(index.from_range(Address::new(0x1005), Address::new(0x100C)).len(), 1);
+    }
+
+    #[test]
+    fn test_sweep_arm64() {
+        let words: [u32; 5] = [
+            0x90000008, // adrp x8, 0x4000
+            0xF9400909, // ldr x9, [x8, #0x10]
+            0x91004108, // add x8, x8, #0x10
+            0x94000002, // bl +8
+            0xF9400909, // ldr x9, [x8, #0x10] (x8 clobbered by the call)
+        ];
+        let code: Vec<u8> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+        let index = XrefIndex::from_xrefs(sweep_arm64(&code, 0x4000));
+
+        let targets: Vec<(u64, u64, XrefKind)> =
+            index.iter().map(|x| (x.from.as_u64(), x.to.as_u64(), x.kind)).collect();
+        assert_eq!(
+            targets,
+            [(0x4004, 0x4010, XrefKind::Load), (0x4008, 0x4010, XrefKind::Load), (0x400C, 0x4014, XrefKind::Call)]
+        );
+    }
+
+    #[test]
+    fn test_sweep_arm64_negative_page() {
+        let words: [u32; 3] = [
+            0xF0FFFFE8, // adrp x8, -0x1000
+            0xF97FFD09, // ldr x9, [x8, #0x7FF8]
+            0x91400508, // add x8, x8, #1, lsl #12
+        ];
+        let code: Vec<u8> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+        let targets: Vec<(u64, u64)> = sweep_arm64(&code, 0).iter().map(|x| (x.from.as_u64(), x.to.as_u64())).collect();
+        assert_eq!(targets, [(0x4, 0x6FF8), (0x8, 0x0)]);
+    }
+}
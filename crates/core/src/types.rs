@@ -24,7 +24,7 @@ impl Address {
     }
 
     pub fn offset(&self, offset: i64) -> Self {
-        Self((self.0 as i64 + offset) as u64)
+        Self(self.0.wrapping_add(offset as u64))
     }
 }
 
@@ -96,6 +96,7 @@ pub struct DumpedMethod {
     pub id: Uuid,
     pub name: String,
     pub full_name: String,
+    /// Entry point relative to the image base, zero when unknown
     pub address: Address,
     pub return_type: String,
     pub parameters: Vec<MethodParameter>,
@@ -105,6 +106,9 @@ pub struct DumpedMethod {
     pub is_virtual: bool,
     pub is_abstract: bool,
     pub token: u32,
+    /// Code size in bytes, from the binary's function table
+    #[serde(default)]
+    pub size: Option<u64>,
 }
 
 /// Method parameter information
@@ -122,6 +126,8 @@ pub struct DumpedType {
     pub name: String,
     pub namespace: String,
     pub full_name: String,
+    #[serde(default)]
+    pub assembly: String,
     pub parent_type: Option<String>,
     pub interfaces: Vec<String>,
     pub fields: Vec<DumpedField>,
@@ -176,7 +176,10 @@ impl<T: SecureClear> ClearOnDrop<T> {
         Self { inner }
     }
 
-    pub fn into_inner(mut self) -> T {
+    pub fn into_inner(mut self) -> T
+    where
+        T: Default,
+    {
         // Take the inner value and replace with default
         std::mem::take(&mut self.inner)
     }
@@ -0,0 +1,23 @@
+//! Fuzz `Metadata::parse` and dumping the parsed metadata
+//!
+//! Run with `cargo fuzz run metadata_parse` from the crate directory; seeds live in
+//! `fuzz/corpus/metadata_parse`.
+
+#![no_main]
+
+use endfield_binary_parser::test_util::TestBinary;
+use endfield_il2cpp::{Il2CppDumper, Metadata};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(metadata) = Metadata::parse(data) else {
+        return;
+    };
+    for definition in metadata.type_definitions.iter().take(256) {
+        let _ = metadata.get_string(definition.name_index);
+    }
+    for index in 0..metadata.string_literals.len().min(256) {
+        let _ = metadata.get_string_literal(index);
+    }
+    let _ = Il2CppDumper::from_parts(Box::new(TestBinary::empty()), metadata).dump();
+});
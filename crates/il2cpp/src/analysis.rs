@@ -0,0 +1,574 @@
+//! Function analysis: basic blocks, control-flow graphs and call graphs
+//!
+//! Control-flow graphs are recovered per method by recursive descent from
+//! the entry, bounded by the next known function start. The call graph is
+//! built from direct call and tail-jump references between functions, where
+//! functions are the dumped methods plus the binary's function symbols.
+//! Imports are nodes at their IAT or GOT slots: a load of a slot is a call
+//! through it, and a call target that loads one is named as its PLT stub.
+
+use endfield_binary_parser::common::SymbolType;
+use endfield_binary_parser::{BinaryFile, Disassembler, FlowKind, Instruction, XrefIndex, XrefKind};
+use endfield_core::{Address, DumpResults, Result};
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Upper bound on a function's size when no following function is known
+const MAX_FUNCTION_SIZE: u64 = 0x10000;
+
+/// Bytes searched from a call target for the slot load of a PLT stub
+const PLT_STUB_SIZE: u64 = 16;
+
+/// Instructions decoded per basic block before giving up
+const MAX_BLOCK_INSTRUCTIONS: usize = 4096;
+
+/// Basic block
+#[derive(Debug, Clone, Serialize)]
+pub struct BasicBlock {
+    /// First instruction
+    pub start: Address,
+    /// Address after the last instruction
+    pub end: Address,
+    /// Decoded instructions
+    #[serde(serialize_with = "serialize_instructions")]
+    pub instructions: Vec<Instruction>,
+    /// Direct call targets made from this block
+    pub calls: Vec<Address>,
+}
+
+fn serialize_instructions<S: Serializer>(instructions: &[Instruction], serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    struct Line {
+        address: Address,
+        text: String,
+    }
+    serializer.collect_seq(instructions.iter().map(|i| Line { address: i.address, text: i.to_string() }))
+}
+
+/// Kind of control-flow edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum EdgeKind {
+    /// Fall-through into the next block
+    Fallthrough,
+    /// Unconditional jump
+    Jump,
+    /// Conditional jump taken
+    Taken,
+    /// Conditional jump not taken
+    NotTaken,
+}
+
+/// Control-flow edge between blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CfgEdge {
+    pub from: Address,
+    pub to: Address,
+    pub kind: EdgeKind,
+}
+
+/// Intra-procedural control-flow graph
+#[derive(Debug, Clone, Serialize)]
+pub struct ControlFlowGraph {
+    /// Function name
+    pub name: String,
+    /// Entry block address
+    pub entry: Address,
+    /// Blocks in address order
+    pub blocks: Vec<BasicBlock>,
+    /// Edges between blocks
+    pub edges: Vec<CfgEdge>,
+    /// Jumps leaving the function (tail calls)
+    pub tail_calls: Vec<Address>,
+}
+
+impl ControlFlowGraph {
+    /// Recover the CFG of the function at `entry`, bounded by `end`
+    pub fn build(binary: &dyn BinaryFile, name: impl Into<String>, entry: Address, end: Address) -> Result<Self> {
+        Self::from_disassembler(&Disassembler::new(binary), name, entry, end)
+    }
+
+    /// Recover the CFG with an existing disassembler, so that its symbol map is shared across functions
+    pub fn from_disassembler(
+        disassembler: &Disassembler<'_, dyn BinaryFile + '_>,
+        name: impl Into<String>,
+        entry: Address,
+        end: Address,
+    ) -> Result<Self> {
+        let in_range = |a: Address| a.as_u64() >= entry.as_u64() && a.as_u64() < end.as_u64();
+
+        // Decode everything reachable, collecting block leaders
+        let mut decoded: BTreeMap<u64, Instruction> = BTreeMap::new();
+        let mut leaders = BTreeSet::from([entry.as_u64()]);
+        let mut tail_calls = BTreeSet::new();
+        let mut queue = VecDeque::from([entry]);
+
+        while let Some(start) = queue.pop_front() {
+            if decoded.contains_key(&start.as_u64()) {
+                continue;
+            }
+            let block = disassembler
+                .disassemble_block(start, MAX_BLOCK_INSTRUCTIONS)
+                .map_err(|e| endfield_core::Error::parse(e.to_string()))?;
+
+            for instruction in block {
+                let address = instruction.address;
+                if !in_range(address) || decoded.contains_key(&address.as_u64()) {
+                    // Ran into code that is already decoded or belongs to another function
+                    if in_range(address) {
+                        leaders.insert(address.as_u64());
+                    }
+                    break;
+                }
+
+                if matches!(instruction.flow, FlowKind::Jump | FlowKind::ConditionalJump) {
+                    match instruction.target {
+                        Some(target) if in_range(target) => {
+                            leaders.insert(target.as_u64());
+                            queue.push_back(target);
+                        }
+                        Some(target) => {
+                            tail_calls.insert(target.as_u64());
+                        }
+                        None => {}
+                    }
+                    let next = instruction.next_address();
+                    if instruction.flow == FlowKind::ConditionalJump && in_range(next) {
+                        leaders.insert(next.as_u64());
+                        queue.push_back(next);
+                    }
+                }
+                decoded.insert(address.as_u64(), instruction);
+            }
+        }
+
+        // Split the decoded instructions into blocks at leaders
+        let mut blocks: Vec<BasicBlock> = Vec::new();
+        for instruction in decoded.into_values() {
+            let continues = blocks.last().is_some_and(|block| {
+                let last = block.instructions.last().expect("blocks are never empty");
+                block.end == instruction.address
+                    && !last.flow.ends_block()
+                    && !leaders.contains(&instruction.address.as_u64())
+            });
+            if !continues {
+                blocks.push(BasicBlock {
+                    start: instruction.address,
+                    end: instruction.address,
+                    instructions: Vec::new(),
+                    calls: Vec::new(),
+                });
+            }
+            let block = blocks.last_mut().expect("block was just pushed");
+            block.end = instruction.next_address();
+            if let (FlowKind::Call, Some(target)) = (instruction.flow, instruction.target) {
+                block.calls.push(target);
+            }
+            block.instructions.push(instruction);
+        }
+
+        let starts: BTreeSet<u64> = blocks.iter().map(|block| block.start.as_u64()).collect();
+        let mut edges = Vec::new();
+        for block in &blocks {
+            let last = block.instructions.last().expect("blocks are never empty");
+            let mut edge = |to: Address, kind| {
+                if starts.contains(&to.as_u64()) {
+                    edges.push(CfgEdge { from: block.start, to, kind });
+                }
+            };
+            match (last.flow, last.target) {
+                (FlowKind::Jump, Some(target)) => edge(target, EdgeKind::Jump),
+                (FlowKind::ConditionalJump, target) => {
+                    if let Some(target) = target {
+                        edge(target, EdgeKind::Taken);
+                    }
+                    edge(block.end, EdgeKind::NotTaken);
+                }
+                (flow, _) if !flow.ends_block() => edge(block.end, EdgeKind::Fallthrough),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            name: name.into(),
+            entry,
+            blocks,
+            edges,
+            tail_calls: tail_calls.into_iter().map(Address::new).collect(),
+        })
+    }
+
+    /// Export as Graphviz DOT
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        let _ = writeln!(dot, "digraph \"{}\" {{", escape(&self.name));
+        dot.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+        for block in &self.blocks {
+            let mut label = String::new();
+            for instruction in &block.instructions {
+                let _ = write!(label, "{:X}  {}\\l", instruction.address.as_u64(), escape(&instruction.to_string()));
+            }
+            let _ = writeln!(dot, "    \"{:X}\" [label=\"{}\"];", block.start.as_u64(), label);
+        }
+        for edge in &self.edges {
+            let color = match edge.kind {
+                EdgeKind::Taken => "green",
+                EdgeKind::NotTaken => "red",
+                EdgeKind::Jump | EdgeKind::Fallthrough => "black",
+            };
+            let _ = writeln!(dot, "    \"{:X}\" -> \"{:X}\" [color={}];", edge.from.as_u64(), edge.to.as_u64(), color);
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Export as JSON
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| endfield_core::Error::parse(e.to_string()))
+    }
+}
+
+/// Function in the call graph
+#[derive(Debug, Clone, Serialize)]
+pub struct CallGraphNode {
+    pub address: Address,
+    pub name: String,
+    /// Address after the function, as bounded by the next function start
+    pub end: Address,
+}
+
+/// Calls from one function to another
+#[derive(Debug, Clone, Serialize)]
+pub struct CallGraphEdge {
+    pub caller: Address,
+    pub callee: Address,
+    /// Call or tail-jump instructions
+    pub sites: Vec<Address>,
+}
+
+/// Whole-program call graph
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CallGraph {
+    /// Functions in address order
+    pub nodes: Vec<CallGraphNode>,
+    /// Edges sorted by caller, then callee
+    pub edges: Vec<CallGraphEdge>,
+}
+
+impl CallGraph {
+    /// Sweep the binary and build the call graph
+    pub fn build(binary: &dyn BinaryFile, results: &DumpResults) -> Self {
+        Self::from_index(&XrefIndex::build(binary), binary, results)
+    }
+
+    /// Build the call graph from an existing reference index
+    pub fn from_index(index: &XrefIndex, binary: &dyn BinaryFile, results: &DumpResults) -> Self {
+        let imports = import_slots(binary);
+        let stubs = plt_stubs(index, &imports);
+        let nodes = functions(binary, results, &stubs);
+        let starts: HashMap<u64, usize> =
+            nodes.iter().enumerate().map(|(i, node)| (node.address.as_u64(), i)).collect();
+
+        let mut sites: BTreeMap<(u64, u64), Vec<Address>> = BTreeMap::new();
+        for node in &nodes {
+            for xref in index.from_range(node.address, node.end) {
+                let is_call = xref.kind == XrefKind::Call;
+                let is_tail_jump = xref.kind == XrefKind::Jump && xref.to != node.address;
+                // `call [rip+X]`, `ldr x17, [x16, #X]` and the like are indexed as loads
+                let is_import_call = xref.kind == XrefKind::Load && imports.contains_key(&xref.to.as_u64());
+                if (is_call || is_tail_jump || is_import_call) && starts.contains_key(&xref.to.as_u64()) {
+                    sites.entry((node.address.as_u64(), xref.to.as_u64())).or_default().push(xref.from);
+                }
+            }
+        }
+
+        let edges = sites
+            .into_iter()
+            .map(|((caller, callee), sites)| CallGraphEdge {
+                caller: Address::new(caller),
+                callee: Address::new(callee),
+                sites,
+            })
+            .collect();
+        Self { nodes, edges }
+    }
+
+    /// Function starting at `address`
+    pub fn node(&self, address: Address) -> Option<&CallGraphNode> {
+        let index = self.nodes.binary_search_by_key(&address.as_u64(), |node| node.address.as_u64()).ok()?;
+        Some(&self.nodes[index])
+    }
+
+    /// Edges leaving `address`, found by binary search since edges are sorted by caller
+    pub fn edges_from(&self, address: Address) -> &[CallGraphEdge] {
+        let start = self.edges.partition_point(|edge| edge.caller.as_u64() < address.as_u64());
+        let end = self.edges.partition_point(|edge| edge.caller.as_u64() <= address.as_u64());
+        &self.edges[start..end]
+    }
+
+    /// Functions called by `address`
+    pub fn callees(&self, address: Address) -> Vec<Address> {
+        self.edges_from(address).iter().map(|edge| edge.callee).collect()
+    }
+
+    /// Functions calling `address`
+    pub fn callers(&self, address: Address) -> Vec<Address> {
+        self.edges.iter().filter(|edge| edge.callee == address).map(|edge| edge.caller).collect()
+    }
+
+    /// Shortest call chain from `from` to `to`, both included
+    pub fn path(&self, from: Address, to: Address) -> Option<Vec<Address>> {
+        let mut previous: HashMap<Address, Address> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        while let Some(current) = queue.pop_front() {
+            if current == to {
+                let mut path = vec![to];
+                while let Some(&prev) = previous.get(path.last().expect("path is never empty")) {
+                    path.push(prev);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for callee in self.edges_from(current).iter().map(|edge| edge.callee) {
+                if callee != from && !previous.contains_key(&callee) {
+                    previous.insert(callee, current);
+                    queue.push_back(callee);
+                }
+            }
+        }
+        None
+    }
+
+    /// Export as Graphviz DOT
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph calls {\n    node [shape=box];\n");
+        for node in &self.nodes {
+            let _ = writeln!(dot, "    \"{:X}\" [label=\"{}\"];", node.address.as_u64(), escape(&node.name));
+        }
+        for edge in &self.edges {
+            let _ = writeln!(dot, "    \"{:X}\" -> \"{:X}\";", edge.caller.as_u64(), edge.callee.as_u64());
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Export as JSON
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| endfield_core::Error::parse(e.to_string()))
+    }
+
+    /// Write DOT or JSON depending on the file extension
+    pub fn write_to_file(&self, path: &Path) -> Result<()> {
+        let content = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => self.to_json()?,
+            _ => self.to_dot(),
+        };
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+/// Function symbols outside executable sections: IAT and GOT slots of imports
+fn import_slots(binary: &dyn BinaryFile) -> HashMap<u64, &str> {
+    binary
+        .symbols()
+        .iter()
+        .filter(|symbol| symbol.symbol_type == SymbolType::Function && !symbol.name.is_empty())
+        .filter(|symbol| {
+            !binary
+                .executable_sections()
+                .iter()
+                .any(|section| section.contains_va(symbol.address.as_u64()))
+        })
+        .map(|symbol| (symbol.address.as_u64(), symbol.name.as_str()))
+        .collect()
+}
+
+/// Call targets loading an import slot right away, named `import@plt`
+fn plt_stubs(index: &XrefIndex, imports: &HashMap<u64, &str>) -> Vec<(u64, String)> {
+    if imports.is_empty() {
+        return Vec::new();
+    }
+    let targets: BTreeSet<u64> = index
+        .iter()
+        .filter(|xref| xref.kind == XrefKind::Call)
+        .map(|xref| xref.to.as_u64())
+        .collect();
+    targets
+        .into_iter()
+        .filter_map(|target| {
+            let end = Address::new(target.saturating_add(PLT_STUB_SIZE));
+            let slot = index
+                .from_range(Address::new(target), end)
+                .into_iter()
+                .find_map(|xref| imports.get(&xref.to.as_u64()).filter(|_| xref.kind == XrefKind::Load))?;
+            Some((target, format!("{}@plt", slot)))
+        })
+        .collect()
+}
+
+/// Dumped methods, function symbols and `stubs` with their bounds
+fn functions(binary: &dyn BinaryFile, results: &DumpResults, stubs: &[(u64, String)]) -> Vec<CallGraphNode> {
+    let mut starts: BTreeMap<u64, (String, Option<u64>)> = BTreeMap::new();
+    for (start, name) in stubs {
+        starts.insert(*start, (name.clone(), Some(PLT_STUB_SIZE)));
+    }
+    for function in binary.functions() {
+        let name = format!("sub_{:X}", function.start.as_u64());
+        starts.insert(function.start.as_u64(), (name, Some(function.size())));
+    }
+    for symbol in binary.symbols() {
+        if symbol.symbol_type == SymbolType::Function && symbol.address != Address::ZERO && !symbol.name.is_empty() {
+            let recovered = starts.get(&symbol.address.as_u64()).and_then(|(_, size)| *size);
+            starts.insert(symbol.address.as_u64(), (symbol.name.clone(), symbol.size.or(recovered)));
+        }
+    }
+    let image_base = binary.image_base().as_u64();
+    for method in &results.methods {
+        if method.address != Address::ZERO {
+            let va = image_base.wrapping_add(method.address.as_u64());
+            let size = method.size.or_else(|| starts.get(&va).and_then(|(_, size)| *size));
+            starts.insert(va, (method.full_name.clone(), size));
+        }
+    }
+
+    let addresses: Vec<u64> = starts.keys().copied().collect();
+    starts
+        .into_iter()
+        .enumerate()
+        .map(|(i, (start, (name, size)))| {
+            let next = addresses.get(i + 1).copied().unwrap_or(u64::MAX);
+            let end = start.saturating_add(size.unwrap_or(MAX_FUNCTION_SIZE)).min(next);
+            CallGraphNode { address: Address::new(start), name, end: Address::new(end) }
+        })
+        .collect()
+}
+
+/// Build the CFG of every dumped method with a known address
+pub fn method_cfgs(binary: &dyn BinaryFile, results: &DumpResults) -> Vec<ControlFlowGraph> {
+    let names: HashSet<&str> = results.methods.iter().map(|m| m.full_name.as_str()).collect();
+    let disassembler = Disassembler::new(binary);
+    functions(binary, results, &[])
+        .into_iter()
+        .filter(|node| names.contains(node.name.as_str()))
+        .filter_map(|node| ControlFlowGraph::from_disassembler(&disassembler, node.name, node.address, node.end).ok())
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endfield_binary_parser::common::{SectionFlags, Symbol};
+    use endfield_binary_parser::test_util::TestBinary;
+
+    #[test]
+    fn test_arm64_cfg() {
+        let binary = TestBinary::code(&[
+            0xB4000060, // cbz x0, 0x100C
+            0x940003FF, // bl 0x2000
+            0x14000002, // b 0x1010
+            0xD503201F, // nop
+            0xD65F03C0, // ret
+        ]);
+        let cfg = ControlFlowGraph::build(&binary, "f", Address::new(0x1000), Address::new(0x1014)).unwrap();
+
+        let blocks: Vec<(u64, u64)> = cfg.blocks.iter().map(|b| (b.start.as_u64(), b.end.as_u64())).collect();
+        assert_eq!(blocks, [(0x1000, 0x1004), (0x1004, 0x100C), (0x100C, 0x1010), (0x1010, 0x1014)]);
+        assert_eq!(cfg.blocks[1].calls, [Address::new(0x2000)]);
+
+        let edges: Vec<(u64, u64, EdgeKind)> =
+            cfg.edges.iter().map(|e| (e.from.as_u64(), e.to.as_u64(), e.kind)).collect();
+        assert_eq!(
+            edges,
+            [
+                (0x1000, 0x100C, EdgeKind::Taken),
+                (0x1000, 0x1004, EdgeKind::NotTaken),
+                (0x1004, 0x1010, EdgeKind::Jump),
+                (0x100C, 0x1010, EdgeKind::Fallthrough),
+            ]
+        );
+        assert!(cfg.to_dot().contains("\"1000\" -> \"100C\" [color=green];"));
+        assert!(cfg.to_json().unwrap().contains("\"text\": \"bl 0x2000\""));
+    }
+
+    #[test]
+    fn test_call_graph_imports() {
+        let mut binary = TestBinary::code(&[
+            0x94000005, // bl 0x1014
+            0xB0000008, // adrp x8, 0x2000
+            0xF9400908, // ldr x8, [x8, #0x10]
+            0xD63F0100, // blr x8
+            0xD65F03C0, // ret
+            0xB0000010, // adrp x16, 0x2000 (PLT stub)
+            0xF9400A11, // ldr x17, [x16, #0x10]
+            0xD61F0220, // br x17
+        ])
+        .section(".got", 0x2000, &[0; 0x18], SectionFlags::READ | SectionFlags::WRITE);
+        let symbol = |name: &str, address: u64, size: u64| Symbol {
+            name: name.to_string(),
+            address: Address::new(address),
+            size: Some(size),
+            symbol_type: SymbolType::Function,
+            type_name: None,
+        };
+        binary.symbols = vec![symbol("caller", 0x1000, 0x14), symbol("il2cpp_free", 0x2010, 8)];
+
+        let results = DumpResults {
+            timestamp: chrono::Utc::now(),
+            unity_version: None,
+            il2cpp_version: 29,
+            types: Vec::new(),
+            methods: Vec::new(),
+            string_literals: Vec::new(),
+            statistics: Default::default(),
+        };
+        let graph = CallGraph::from_index(&XrefIndex::build(&binary), &binary, &results);
+        let nodes: Vec<(u64, &str, u64)> =
+            graph.nodes.iter().map(|n| (n.address.as_u64(), n.name.as_str(), n.end.as_u64())).collect();
+        assert_eq!(
+            nodes,
+            [(0x1000, "caller", 0x1014), (0x1014, "il2cpp_free@plt", 0x1024), (0x2010, "il2cpp_free", 0x2018)]
+        );
+        let edges: Vec<(u64, u64, Vec<u64>)> = graph
+            .edges
+            .iter()
+            .map(|e| (e.caller.as_u64(), e.callee.as_u64(), e.sites.iter().map(|s| s.as_u64()).collect()))
+            .collect();
+        assert_eq!(
+            edges,
+            [(0x1000, 0x1014, vec![0x1000]), (0x1000, 0x2010, vec![0x1008]), (0x1014, 0x2010, vec![0x1018])]
+        );
+    }
+
+    #[test]
+    fn test_path_and_dot() {
+        let node = |address: u64, name: &str| CallGraphNode {
+            address: Address::new(address),
+            name: name.to_string(),
+            end: Address::new(address + 0x100),
+        };
+        let edge = |caller: u64, callee: u64| CallGraphEdge {
+            caller: Address::new(caller),
+            callee: Address::new(callee),
+            sites: vec![Address::new(caller + 4)],
+        };
+        let graph = CallGraph {
+            nodes: vec![node(0x100, "NetManager$$Send"), node(0x200, "Serialize"), node(0x300, "send")],
+            edges: vec![edge(0x100, 0x200), edge(0x200, 0x100), edge(0x200, 0x300), edge(0x300, 0x100)],
+        };
+
+        assert_eq!(graph.callees(Address::new(0x200)), [Address::new(0x100), Address::new(0x300)]);
+        assert!(graph.callees(Address::new(0x400)).is_empty());
+
+        let path = graph.path(Address::new(0x100), Address::new(0x300)).unwrap();
+        assert_eq!(path, [Address::new(0x100), Address::new(0x200), Address::new(0x300)]);
+        assert!(graph.path(Address::new(0x400), Address::new(0x100)).is_none());
+        assert!(graph.to_dot().contains("\"100\" -> \"200\";"));
+        assert!(graph.to_json().unwrap().contains("\"callee\": 512"));
+    }
+}
@@ -0,0 +1,1367 @@
+//! ECMA-335 dummy assembly writer
+//!
+//! Turns dump results into `.dll` files that contain only metadata, one per
+//! assembly, so they can be opened in dnSpy/ILSpy or referenced by Unity
+//! tooling. Method bodies are stubs (`ldnull; throw`) and every member carries
+//! the Il2CppDummyDll attributes (`Address`, `FieldOffset`, `Token`) that
+//! record where its native code or data lives.
+
+use endfield_binary_parser::BinaryFile;
+use endfield_core::{Address, DumpResults, DumpedField, DumpedMethod, DumpedType, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Metadata table numbers (ECMA-335 II.22)
+mod table {
+    pub const MODULE: usize = 0x00;
+    pub const TYPE_REF: usize = 0x01;
+    pub const TYPE_DEF: usize = 0x02;
+    pub const FIELD: usize = 0x04;
+    pub const METHOD_DEF: usize = 0x06;
+    pub const PARAM: usize = 0x08;
+    pub const INTERFACE_IMPL: usize = 0x09;
+    pub const MEMBER_REF: usize = 0x0A;
+    pub const CONSTANT: usize = 0x0B;
+    pub const CUSTOM_ATTRIBUTE: usize = 0x0C;
+    pub const ASSEMBLY: usize = 0x20;
+    pub const ASSEMBLY_REF: usize = 0x23;
+    pub const COUNT: usize = 0x2D;
+}
+
+/// Signature element types (ECMA-335 II.23.1.16)
+mod element_type {
+    pub const VOID: u8 = 0x01;
+    pub const BOOLEAN: u8 = 0x02;
+    pub const CHAR: u8 = 0x03;
+    pub const I1: u8 = 0x04;
+    pub const U1: u8 = 0x05;
+    pub const I2: u8 = 0x06;
+    pub const U2: u8 = 0x07;
+    pub const I4: u8 = 0x08;
+    pub const U4: u8 = 0x09;
+    pub const I8: u8 = 0x0A;
+    pub const U8: u8 = 0x0B;
+    pub const R4: u8 = 0x0C;
+    pub const R8: u8 = 0x0D;
+    pub const STRING: u8 = 0x0E;
+    pub const VALUETYPE: u8 = 0x11;
+    pub const CLASS: u8 = 0x12;
+    pub const OBJECT: u8 = 0x1C;
+}
+
+/// TypeDef flags (ECMA-335 II.23.1.15)
+mod type_flags {
+    pub const PUBLIC: u32 = 0x0000_0001;
+    pub const INTERFACE: u32 = 0x0000_0020;
+    pub const ABSTRACT: u32 = 0x0000_0080;
+    pub const SEALED: u32 = 0x0000_0100;
+    pub const BEFORE_FIELD_INIT: u32 = 0x0010_0000;
+}
+
+/// Field flags (ECMA-335 II.23.1.5)
+mod field_flags {
+    pub const PUBLIC: u16 = 0x0006;
+    pub const STATIC: u16 = 0x0010;
+    pub const LITERAL: u16 = 0x0040;
+    pub const SPECIAL_NAME: u16 = 0x0200;
+    pub const RT_SPECIAL_NAME: u16 = 0x0400;
+    pub const HAS_DEFAULT: u16 = 0x8000;
+}
+
+/// Method flags (ECMA-335 II.23.1.10)
+mod method_flags {
+    pub const PUBLIC: u16 = 0x0006;
+    pub const STATIC: u16 = 0x0010;
+    pub const VIRTUAL: u16 = 0x0040;
+    pub const HIDE_BY_SIG: u16 = 0x0080;
+    pub const NEW_SLOT: u16 = 0x0100;
+    pub const ABSTRACT: u16 = 0x0400;
+    pub const SPECIAL_NAME: u16 = 0x0800;
+    pub const RT_SPECIAL_NAME: u16 = 0x1000;
+}
+
+/// Signature calling convention flags
+const SIG_HASTHIS: u8 = 0x20;
+const SIG_FIELD: u8 = 0x06;
+
+/// Stub body shared by every concrete method: tiny header, `ldnull`, `throw`
+const STUB_BODY: [u8; 3] = [(2 << 2) | 0x2, 0x14, 0x7A];
+
+/// Public key token of mscorlib
+const MSCORLIB_TOKEN: [u8; 8] = [0xB7, 0x7A, 0x5C, 0x56, 0x19, 0x34, 0xE0, 0x89];
+
+/// Namespace of the attribute classes added to every assembly
+const ATTRIBUTE_NAMESPACE: &str = "Il2CppDummyDll";
+
+/// Attribute classes and the string fields they carry, in TypeDef order
+const ATTRIBUTES: [(&str, &[&str]); 3] = [
+    ("AddressAttribute", &["RVA", "Offset", "VA", "Slot"]),
+    ("FieldOffsetAttribute", &["Offset"]),
+    ("TokenAttribute", &["Token"]),
+];
+const ADDRESS_ATTRIBUTE: usize = 0;
+const FIELD_OFFSET_ATTRIBUTE: usize = 1;
+const TOKEN_ATTRIBUTE: usize = 2;
+
+/// PE layout constants
+const FILE_ALIGNMENT: u32 = 0x200;
+const SECTION_ALIGNMENT: u32 = 0x2000;
+const TEXT_RVA: u32 = 0x2000;
+const IMAGE_BASE: u32 = 0x1000_0000;
+const CLI_HEADER_SIZE: u32 = 72;
+
+/// ECMA-335 dummy DLL generator
+pub struct DummyDllGenerator;
+
+impl DummyDllGenerator {
+    /// Build one DLL per assembly, returned as `(file name, bytes)` sorted by name
+    ///
+    /// When `binary` is given, `Address` attributes include the file offset and
+    /// VA of each method in addition to its RVA.
+    pub fn generate(results: &DumpResults, binary: Option<&dyn BinaryFile>) -> Vec<(String, Vec<u8>)> {
+        let mut assemblies: Vec<&str> = results.types.iter().map(|t| t.assembly.as_str()).collect();
+        assemblies.sort_unstable();
+        assemblies.dedup();
+
+        let owners: HashMap<&str, &DumpedType> = results
+            .types
+            .iter()
+            .rev()
+            .map(|t| (t.full_name.as_str(), t))
+            .collect();
+        let methods: HashMap<Uuid, &DumpedMethod> = results.methods.iter().map(|m| (m.id, m)).collect();
+
+        assemblies
+            .into_iter()
+            .map(|assembly| {
+                let mut types: Vec<&DumpedType> =
+                    results.types.iter().filter(|t| t.assembly == assembly).collect();
+                types.sort_by(|a, b| {
+                    (&a.namespace, &a.name, a.token).cmp(&(&b.namespace, &b.name, b.token))
+                });
+
+                let name = Self::assembly_name(assembly);
+                let builder = AssemblyBuilder::new(&name, &owners, &methods, binary);
+                (format!("{}.dll", name), builder.build(&types))
+            })
+            .collect()
+    }
+
+    /// Write the DLLs into `dir`, returning the paths written
+    pub fn write_to_dir(
+        results: &DumpResults,
+        binary: Option<&dyn BinaryFile>,
+        dir: &Path,
+    ) -> Result<Vec<PathBuf>> {
+        std::fs::create_dir_all(dir)?;
+        let mut written = Vec::new();
+        for (file_name, bytes) in Self::generate(results, binary) {
+            let path = dir.join(file_name);
+            std::fs::write(&path, bytes)?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+
+    fn assembly_name(assembly: &str) -> String {
+        let name = assembly.strip_suffix(".dll").unwrap_or(assembly);
+        let name: String = name
+            .chars()
+            .map(|c| match c {
+                '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+                c if c.is_control() => '_',
+                c => c,
+            })
+            .collect();
+        if name.is_empty() {
+            "Unknown".to_string()
+        } else {
+            name
+        }
+    }
+}
+
+/// Coded index kinds (ECMA-335 II.24.2.6): tag width and tables in tag order
+#[derive(Clone, Copy)]
+enum Coded {
+    TypeDefOrRef,
+    HasConstant,
+    HasCustomAttribute,
+    MemberRefParent,
+    ResolutionScope,
+    CustomAttributeType,
+}
+
+impl Coded {
+    fn tag_bits(self) -> u32 {
+        match self {
+            Coded::TypeDefOrRef | Coded::HasConstant | Coded::ResolutionScope => 2,
+            Coded::MemberRefParent | Coded::CustomAttributeType => 3,
+            Coded::HasCustomAttribute => 5,
+        }
+    }
+
+    fn tables(self) -> &'static [usize] {
+        match self {
+            Coded::TypeDefOrRef => &[table::TYPE_DEF, table::TYPE_REF, 0x1B],
+            Coded::HasConstant => &[table::FIELD, table::PARAM, 0x17],
+            Coded::HasCustomAttribute => &[
+                table::METHOD_DEF, table::FIELD, table::TYPE_REF, table::TYPE_DEF, table::PARAM,
+                table::INTERFACE_IMPL, table::MEMBER_REF, table::MODULE, 0x0E, 0x17, 0x14, 0x11,
+                0x1A, 0x1B, table::ASSEMBLY, table::ASSEMBLY_REF, 0x26, 0x27, 0x28, 0x2A, 0x2C, 0x2B,
+            ],
+            Coded::MemberRefParent => &[table::TYPE_DEF, table::TYPE_REF, 0x1A, table::METHOD_DEF, 0x1B],
+            Coded::ResolutionScope => &[table::MODULE, 0x1A, table::ASSEMBLY_REF, table::TYPE_REF],
+            Coded::CustomAttributeType => &[0x3F, 0x3F, table::METHOD_DEF, table::MEMBER_REF],
+        }
+    }
+
+    /// Encode a reference to `row` (1-based) of `target`
+    fn encode(self, target: usize, row: u32) -> u32 {
+        let tag = self
+            .tables()
+            .iter()
+            .position(|&t| t == target)
+            .expect("table is not a member of the coded index") as u32;
+        (row << self.tag_bits()) | tag
+    }
+}
+
+/// A reference to a type from a signature or `extends` column
+#[derive(Clone, Copy)]
+enum TypeRefKind {
+    Primitive(u8),
+    Class { table: usize, row: u32, value_type: bool },
+}
+
+/// String, blob and GUID heaps
+#[derive(Default)]
+struct Heaps {
+    strings: Vec<u8>,
+    string_index: HashMap<String, u32>,
+    blobs: Vec<u8>,
+    blob_index: HashMap<Vec<u8>, u32>,
+    guids: Vec<u8>,
+}
+
+impl Heaps {
+    fn new() -> Self {
+        Self {
+            strings: vec![0],
+            blobs: vec![0],
+            ..Default::default()
+        }
+    }
+
+    fn string(&mut self, value: &str) -> u32 {
+        if value.is_empty() {
+            return 0;
+        }
+        if let Some(&index) = self.string_index.get(value) {
+            return index;
+        }
+        let index = self.strings.len() as u32;
+        self.strings.extend_from_slice(value.as_bytes());
+        self.strings.push(0);
+        self.string_index.insert(value.to_string(), index);
+        index
+    }
+
+    fn blob(&mut self, value: &[u8]) -> u32 {
+        if value.is_empty() {
+            return 0;
+        }
+        if let Some(&index) = self.blob_index.get(value) {
+            return index;
+        }
+        let index = self.blobs.len() as u32;
+        write_compressed(&mut self.blobs, value.len() as u32);
+        self.blobs.extend_from_slice(value);
+        self.blob_index.insert(value.to_vec(), index);
+        index
+    }
+
+    fn guid(&mut self, value: [u8; 16]) -> u32 {
+        self.guids.extend_from_slice(&value);
+        (self.guids.len() / 16) as u32
+    }
+}
+
+/// One metadata table column value
+#[derive(Clone, Copy)]
+enum Col {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    Str(u32),
+    Blob(u32),
+    Guid(u32),
+    Index(usize, u32),
+    Coded(Coded, u32),
+}
+
+/// Builds the metadata and PE image for a single assembly
+struct AssemblyBuilder<'a> {
+    name: &'a str,
+    owners: &'a HashMap<&'a str, &'a DumpedType>,
+    methods: &'a HashMap<Uuid, &'a DumpedMethod>,
+    binary: Option<&'a dyn BinaryFile>,
+    heaps: Heaps,
+    tables: Vec<Vec<Vec<Col>>>,
+    type_defs: HashMap<&'a str, u32>,
+    type_refs: HashMap<(u32, String), u32>,
+    assembly_refs: HashMap<String, u32>,
+    attribute_ctors: [u32; 3],
+    custom_attributes: Vec<(u32, u32, u32)>,
+}
+
+impl<'a> AssemblyBuilder<'a> {
+    fn new(
+        name: &'a str,
+        owners: &'a HashMap<&'a str, &'a DumpedType>,
+        methods: &'a HashMap<Uuid, &'a DumpedMethod>,
+        binary: Option<&'a dyn BinaryFile>,
+    ) -> Self {
+        Self {
+            name,
+            owners,
+            methods,
+            binary,
+            heaps: Heaps::new(),
+            tables: vec![Vec::new(); table::COUNT],
+            type_defs: HashMap::new(),
+            type_refs: HashMap::new(),
+            assembly_refs: HashMap::new(),
+            attribute_ctors: [0; 3],
+            custom_attributes: Vec::new(),
+        }
+    }
+
+    fn build(mut self, types: &[&'a DumpedType]) -> Vec<u8> {
+        let module_name = format!("{}.dll", self.name);
+        let row = vec![
+            Col::U16(0),
+            Col::Str(self.heaps.string(&module_name)),
+            Col::Guid(self.heaps.guid(Self::mvid(&module_name))),
+            Col::Guid(0),
+            Col::Guid(0),
+        ];
+        self.tables[table::MODULE].push(row);
+
+        let row = vec![
+            Col::U32(0x8004), // SHA1
+            Col::U16(0),
+            Col::U16(0),
+            Col::U16(0),
+            Col::U16(0),
+            Col::U32(0),
+            Col::Blob(0),
+            Col::Str(self.heaps.string(self.name)),
+            Col::Str(0),
+        ];
+        self.tables[table::ASSEMBLY].push(row);
+
+        // TypeDef rows are assigned up front so that types can reference each
+        // other regardless of order: <Module>, the attribute classes, then the dump
+        let first_dumped = 2 + ATTRIBUTES.len() as u32;
+        for (i, type_def) in types.iter().enumerate() {
+            self.type_defs.entry(type_def.full_name.as_str()).or_insert(first_dumped + i as u32);
+        }
+
+        self.add_type_def("", "<Module>", 0, None);
+        self.add_attribute_classes();
+        for (i, type_def) in types.iter().enumerate() {
+            self.add_dumped_type(first_dumped + i as u32, type_def);
+        }
+
+        // CustomAttribute must be sorted by parent
+        self.custom_attributes.sort_by_key(|&(parent, _, _)| parent);
+        for (parent, ctor, value) in std::mem::take(&mut self.custom_attributes) {
+            let row = vec![
+                Col::Coded(Coded::HasCustomAttribute, parent),
+                Col::Coded(Coded::CustomAttributeType, ctor),
+                Col::Blob(value),
+            ];
+            self.tables[table::CUSTOM_ATTRIBUTE].push(row);
+        }
+
+        let metadata = self.serialize_metadata();
+        write_pe(&metadata)
+    }
+
+    /// Deterministic module version id derived from the module name
+    fn mvid(name: &str) -> [u8; 16] {
+        let mut mvid = [0u8; 16];
+        for (half, seed) in [0xCBF2_9CE4_8422_2325u64, 0x6C62_272E_07BB_0142].iter().enumerate() {
+            let mut hash = *seed;
+            for byte in name.bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x0100_0000_01B3);
+            }
+            mvid[half * 8..half * 8 + 8].copy_from_slice(&hash.to_le_bytes());
+        }
+        // RFC 4122 version 4 / variant bits
+        mvid[7] = (mvid[7] & 0x0F) | 0x40;
+        mvid[8] = (mvid[8] & 0x3F) | 0x80;
+        mvid
+    }
+
+    fn add_type_def(&mut self, namespace: &str, name: &str, flags: u32, extends: Option<TypeRefKind>) -> u32 {
+        let extends = match extends {
+            Some(TypeRefKind::Class { table, row, .. }) => Coded::TypeDefOrRef.encode(table, row),
+            _ => 0,
+        };
+        let row = vec![
+            Col::U32(flags),
+            Col::Str(self.heaps.string(name)),
+            Col::Str(self.heaps.string(namespace)),
+            Col::Coded(Coded::TypeDefOrRef, extends),
+            Col::Index(table::FIELD, self.tables[table::FIELD].len() as u32 + 1),
+            Col::Index(table::METHOD_DEF, self.tables[table::METHOD_DEF].len() as u32 + 1),
+        ];
+        self.tables[table::TYPE_DEF].push(row);
+        self.tables[table::TYPE_DEF].len() as u32
+    }
+
+    fn add_attribute_classes(&mut self) {
+        let attribute = self.type_ref_in("mscorlib", "System", "Attribute");
+        let extends = TypeRefKind::Class { table: table::TYPE_REF, row: attribute, value_type: false };
+
+        for (i, (name, fields)) in ATTRIBUTES.iter().enumerate() {
+            self.add_type_def(
+                ATTRIBUTE_NAMESPACE,
+                name,
+                type_flags::PUBLIC | type_flags::BEFORE_FIELD_INIT,
+                Some(extends),
+            );
+            for field in fields.iter() {
+                let signature = self.heaps.blob(&[SIG_FIELD, element_type::STRING]);
+                let row = vec![
+                    Col::U16(field_flags::PUBLIC),
+                    Col::Str(self.heaps.string(field)),
+                    Col::Blob(signature),
+                ];
+                self.tables[table::FIELD].push(row);
+            }
+            self.attribute_ctors[i] = self.add_method(
+                ".ctor",
+                method_flags::PUBLIC
+                    | method_flags::HIDE_BY_SIG
+                    | method_flags::SPECIAL_NAME
+                    | method_flags::RT_SPECIAL_NAME,
+                &[SIG_HASTHIS, 0, element_type::VOID],
+                true,
+                &[],
+            );
+        }
+    }
+
+    fn add_dumped_type(&mut self, row: u32, type_def: &'a DumpedType) {
+        let is_value_type = matches!(
+            type_def.parent_type.as_deref(),
+            Some("System.ValueType") | Some("System.Enum")
+        );
+
+        let mut flags = type_flags::PUBLIC;
+        if type_def.is_interface {
+            flags |= type_flags::INTERFACE | type_flags::ABSTRACT;
+        } else {
+            if type_def.is_abstract {
+                flags |= type_flags::ABSTRACT;
+            }
+            if type_def.is_sealed || type_def.is_enum || is_value_type {
+                flags |= type_flags::SEALED;
+            }
+        }
+
+        let extends = if type_def.is_interface {
+            None
+        } else {
+            let parent = type_def.parent_type.as_deref().unwrap_or("System.Object");
+            Some(self.type_ref(parent))
+        };
+
+        let added = self.add_type_def(&type_def.namespace, &type_def.name, flags, extends);
+        debug_assert_eq!(added, row);
+        self.add_attribute(
+            Coded::HasCustomAttribute.encode(table::TYPE_DEF, row),
+            TOKEN_ATTRIBUTE,
+            &[("Token", format!("0x{:08X}", type_def.token))],
+        );
+
+        for interface in &type_def.interfaces {
+            if let TypeRefKind::Class { table, row: target, .. } = self.type_ref(interface) {
+                let interface_row = vec![
+                    Col::Index(table::TYPE_DEF, row),
+                    Col::Coded(Coded::TypeDefOrRef, Coded::TypeDefOrRef.encode(table, target)),
+                ];
+                self.tables[table::INTERFACE_IMPL].push(interface_row);
+            }
+        }
+
+        for field in &type_def.fields {
+            self.add_field(type_def, field);
+        }
+
+        for method in type_def.methods.iter().filter_map(|id| self.methods.get(id).copied()) {
+            self.add_dumped_method(type_def, method);
+        }
+    }
+
+    /// Default value of a literal field as (element type, blob)
+    ///
+    /// Enum members are encoded with their enum's underlying type (`int` when
+    /// the dump has no `value__`), constants with their own. Values that don't
+    /// parse as that type, and non-numeric constants, are dropped.
+    fn constant(owner: &DumpedType, field: &DumpedField) -> Option<(u8, Vec<u8>)> {
+        let type_name = if owner.is_enum && field.is_static {
+            owner
+                .fields
+                .iter()
+                .find(|f| !f.is_static && f.name == "value__")
+                .map_or("System.Int32", |f| f.type_name.as_str())
+        } else if field.is_const {
+            field.type_name.as_str()
+        } else {
+            return None;
+        };
+        let value = field.default_value.as_deref()?;
+
+        Some(match type_name {
+            "System.SByte" => (element_type::I1, value.parse::<i8>().ok()?.to_le_bytes().to_vec()),
+            "System.Byte" => (element_type::U1, value.parse::<u8>().ok()?.to_le_bytes().to_vec()),
+            "System.Int16" => (element_type::I2, value.parse::<i16>().ok()?.to_le_bytes().to_vec()),
+            "System.UInt16" => (element_type::U2, value.parse::<u16>().ok()?.to_le_bytes().to_vec()),
+            "System.Int32" => (element_type::I4, value.parse::<i32>().ok()?.to_le_bytes().to_vec()),
+            "System.UInt32" => (element_type::U4, value.parse::<u32>().ok()?.to_le_bytes().to_vec()),
+            "System.Int64" => (element_type::I8, value.parse::<i64>().ok()?.to_le_bytes().to_vec()),
+            "System.UInt64" => (element_type::U8, value.parse::<u64>().ok()?.to_le_bytes().to_vec()),
+            "System.Single" => (element_type::R4, value.parse::<f32>().ok()?.to_le_bytes().to_vec()),
+            "System.Double" => (element_type::R8, value.parse::<f64>().ok()?.to_le_bytes().to_vec()),
+            _ => return None,
+        })
+    }
+
+    fn add_field(&mut self, owner: &DumpedType, field: &DumpedField) {
+        let constant = Self::constant(owner, field);
+
+        let mut flags = field_flags::PUBLIC;
+        if field.is_static || constant.is_some() {
+            flags |= field_flags::STATIC;
+        }
+        if constant.is_some() {
+            flags |= field_flags::LITERAL | field_flags::HAS_DEFAULT;
+        }
+        if owner.is_enum && field.name == "value__" {
+            flags |= field_flags::SPECIAL_NAME | field_flags::RT_SPECIAL_NAME;
+        }
+
+        let mut signature = vec![SIG_FIELD];
+        if constant.is_some() && owner.is_enum {
+            // Enum members are typed as the enum itself
+            let own = self.type_ref(&owner.full_name);
+            self.encode_type(&mut signature, own);
+        } else if let Some((element, _)) = constant {
+            signature.push(element);
+        } else {
+            let field_type = self.type_ref(&field.type_name);
+            self.encode_type(&mut signature, field_type);
+        }
+
+        let row = vec![
+            Col::U16(flags),
+            Col::Str(self.heaps.string(&field.name)),
+            Col::Blob(self.heaps.blob(&signature)),
+        ];
+        self.tables[table::FIELD].push(row);
+        let field_row = self.tables[table::FIELD].len() as u32;
+
+        if let Some((element, value)) = constant {
+            let row = vec![
+                Col::U8(element),
+                Col::U8(0),
+                Col::Coded(Coded::HasConstant, Coded::HasConstant.encode(table::FIELD, field_row)),
+                Col::Blob(self.heaps.blob(&value)),
+            ];
+            self.tables[table::CONSTANT].push(row);
+        } else if owner.fields.iter().any(|f| f.offset != 0) {
+            // All zero means the dump resolved no offsets for this type
+            self.add_attribute(
+                Coded::HasCustomAttribute.encode(table::FIELD, field_row),
+                FIELD_OFFSET_ATTRIBUTE,
+                &[("Offset", format!("0x{:X}", field.offset))],
+            );
+        }
+    }
+
+    fn add_dumped_method(&mut self, owner: &DumpedType, method: &DumpedMethod) {
+        let mut flags = method_flags::PUBLIC | method_flags::HIDE_BY_SIG;
+        if method.is_static {
+            flags |= method_flags::STATIC;
+        }
+        if owner.is_interface {
+            flags |= method_flags::VIRTUAL | method_flags::NEW_SLOT | method_flags::ABSTRACT;
+        } else {
+            if method.is_virtual || method.is_abstract {
+                flags |= method_flags::VIRTUAL;
+            }
+            if method.is_abstract {
+                flags |= method_flags::ABSTRACT;
+            }
+        }
+        if method.name.starts_with('.') {
+            flags |= method_flags::SPECIAL_NAME | method_flags::RT_SPECIAL_NAME;
+        }
+
+        let mut signature = vec![if method.is_static { 0 } else { SIG_HASTHIS }];
+        write_compressed(&mut signature, method.parameters.len() as u32);
+        let return_type = self.type_ref(&method.return_type);
+        self.encode_type(&mut signature, return_type);
+        for param in &method.parameters {
+            let param_type = self.type_ref(&param.type_name);
+            self.encode_type(&mut signature, param_type);
+        }
+
+        let params: Vec<&str> = method.parameters.iter().map(|p| p.name.as_str()).collect();
+        let has_body = flags & method_flags::ABSTRACT == 0;
+        let row = self.add_method(&method.name, flags, &signature, has_body, &params);
+
+        let parent = Coded::HasCustomAttribute.encode(table::METHOD_DEF, row);
+        self.add_attribute(parent, TOKEN_ATTRIBUTE, &[("Token", format!("0x{:08X}", method.token))]);
+
+        let rva = method.address.as_u64();
+        if rva != 0 {
+            let mut named = vec![("RVA", format!("0x{:X}", rva))];
+            if let Some(binary) = self.binary {
+                let va = binary.image_base().as_u64().wrapping_add(rva);
+                if let Some(offset) = binary.va_to_offset(Address::new(va)) {
+                    named.push(("Offset", format!("0x{:X}", offset)));
+                }
+                named.push(("VA", format!("0x{:X}", va)));
+            }
+            self.add_attribute(parent, ADDRESS_ATTRIBUTE, &named);
+        }
+    }
+
+    fn add_method(&mut self, name: &str, flags: u16, signature: &[u8], has_body: bool, params: &[&str]) -> u32 {
+        let row = vec![
+            // Patched to the stub body's RVA once the layout is known
+            Col::U32(u32::from(has_body)),
+            Col::U16(0),
+            Col::U16(flags),
+            Col::Str(self.heaps.string(name)),
+            Col::Blob(self.heaps.blob(signature)),
+            Col::Index(table::PARAM, self.tables[table::PARAM].len() as u32 + 1),
+        ];
+        self.tables[table::METHOD_DEF].push(row);
+
+        for (i, param) in params.iter().enumerate() {
+            let row = vec![
+                Col::U16(0),
+                Col::U16(i as u16 + 1),
+                Col::Str(self.heaps.string(param)),
+            ];
+            self.tables[table::PARAM].push(row);
+        }
+
+        self.tables[table::METHOD_DEF].len() as u32
+    }
+
+    /// Queue a custom attribute using named string fields
+    fn add_attribute(&mut self, parent: u32, attribute: usize, fields: &[(&str, String)]) {
+        let mut value = vec![0x01, 0x00];
+        value.extend_from_slice(&(fields.len() as u16).to_le_bytes());
+        for (name, field_value) in fields {
+            value.push(0x53); // FIELD
+            value.push(element_type::STRING);
+            write_ser_string(&mut value, name);
+            write_ser_string(&mut value, field_value);
+        }
+
+        let ctor = Coded::CustomAttributeType.encode(table::METHOD_DEF, self.attribute_ctors[attribute]);
+        let blob = self.heaps.blob(&value);
+        self.custom_attributes.push((parent, ctor, blob));
+    }
+
+    /// Resolve a type by full name to a primitive, local TypeDef or TypeRef
+    fn type_ref(&mut self, full_name: &str) -> TypeRefKind {
+        let primitive = match full_name {
+            "System.Void" | "void" => Some(element_type::VOID),
+            "System.Boolean" => Some(element_type::BOOLEAN),
+            "System.Char" => Some(element_type::CHAR),
+            "System.SByte" => Some(element_type::I1),
+            "System.Byte" => Some(element_type::U1),
+            "System.Int16" => Some(element_type::I2),
+            "System.UInt16" => Some(element_type::U2),
+            "System.Int32" => Some(element_type::I4),
+            "System.UInt32" => Some(element_type::U4),
+            "System.Int64" => Some(element_type::I8),
+            "System.UInt64" => Some(element_type::U8),
+            "System.Single" => Some(element_type::R4),
+            "System.Double" => Some(element_type::R8),
+            "System.String" => Some(element_type::STRING),
+            "System.Object" => Some(element_type::OBJECT),
+            _ => None,
+        };
+        if let Some(primitive) = primitive {
+            return TypeRefKind::Primitive(primitive);
+        }
+
+        let owner = self.owners.get(full_name).copied();
+        let value_type = owner.is_some_and(|t| {
+            t.is_enum || matches!(t.parent_type.as_deref(), Some("System.ValueType") | Some("System.Enum"))
+        });
+
+        if let Some(&row) = self.type_defs.get(full_name) {
+            return TypeRefKind::Class { table: table::TYPE_DEF, row, value_type };
+        }
+
+        let (namespace, name) = full_name.rsplit_once('.').unwrap_or(("", full_name));
+        let scope = match owner {
+            Some(t) => DummyDllGenerator::assembly_name(&t.assembly),
+            None if namespace == "System" || namespace.starts_with("System.") => "mscorlib".to_string(),
+            // Unresolved (e.g. placeholder) types are surfaced as object
+            None => return TypeRefKind::Primitive(element_type::OBJECT),
+        };
+
+        let row = self.type_ref_in(&scope, namespace, name);
+        TypeRefKind::Class { table: table::TYPE_REF, row, value_type }
+    }
+
+    fn type_ref_in(&mut self, assembly: &str, namespace: &str, name: &str) -> u32 {
+        let scope = self.assembly_ref(assembly);
+        let key = (scope, format!("{}.{}", namespace, name));
+        if let Some(&row) = self.type_refs.get(&key) {
+            return row;
+        }
+
+        let row = vec![
+            Col::Coded(
+                Coded::ResolutionScope,
+                Coded::ResolutionScope.encode(table::ASSEMBLY_REF, scope),
+            ),
+            Col::Str(self.heaps.string(name)),
+            Col::Str(self.heaps.string(namespace)),
+        ];
+        self.tables[table::TYPE_REF].push(row);
+        let index = self.tables[table::TYPE_REF].len() as u32;
+        self.type_refs.insert(key, index);
+        index
+    }
+
+    fn assembly_ref(&mut self, assembly: &str) -> u32 {
+        if let Some(&row) = self.assembly_refs.get(assembly) {
+            return row;
+        }
+
+        let (version, token): ([u16; 4], &[u8]) = if assembly == "mscorlib" {
+            ([4, 0, 0, 0], &MSCORLIB_TOKEN)
+        } else {
+            ([0, 0, 0, 0], &[])
+        };
+        let mut row: Vec<Col> = version.iter().map(|&v| Col::U16(v)).collect();
+        row.push(Col::U32(0));
+        row.push(Col::Blob(self.heaps.blob(token)));
+        row.push(Col::Str(self.heaps.string(assembly)));
+        row.push(Col::Str(0));
+        row.push(Col::Blob(0));
+        self.tables[table::ASSEMBLY_REF].push(row);
+
+        let index = self.tables[table::ASSEMBLY_REF].len() as u32;
+        self.assembly_refs.insert(assembly.to_string(), index);
+        index
+    }
+
+    fn encode_type(&self, signature: &mut Vec<u8>, kind: TypeRefKind) {
+        match kind {
+            TypeRefKind::Primitive(element) => signature.push(element),
+            TypeRefKind::Class { table, row, value_type } => {
+                signature.push(if value_type { element_type::VALUETYPE } else { element_type::CLASS });
+                write_compressed(signature, Coded::TypeDefOrRef.encode(table, row));
+            }
+        }
+    }
+
+    /// Serialize the metadata root, `#~` stream and heaps
+    fn serialize_metadata(&mut self) -> Vec<u8> {
+        let row_counts: Vec<u32> = self.tables.iter().map(|t| t.len() as u32).collect();
+        let index_size = |table: usize| if row_counts[table] < 0x1_0000 { 2 } else { 4 };
+        let coded_size = |coded: Coded| {
+            let max = coded
+                .tables()
+                .iter()
+                .map(|&t| row_counts.get(t).copied().unwrap_or(0))
+                .max()
+                .unwrap_or(0);
+            if max < (1 << (16 - coded.tag_bits())) {
+                2
+            } else {
+                4
+            }
+        };
+
+        let mut tables = Vec::new();
+        tables.extend_from_slice(&0u32.to_le_bytes());
+        tables.push(2); // MajorVersion
+        tables.push(0); // MinorVersion
+        tables.push(0x07); // HeapSizes: 4-byte string, GUID and blob indexes
+        tables.push(1);
+        let valid = row_counts
+            .iter()
+            .enumerate()
+            .filter(|(_, &n)| n > 0)
+            .fold(0u64, |mask, (i, _)| mask | (1 << i));
+        tables.extend_from_slice(&valid.to_le_bytes());
+        tables.extend_from_slice(&0x0000_1600_3301_FA00u64.to_le_bytes());
+        for &count in row_counts.iter().filter(|&&n| n > 0) {
+            tables.extend_from_slice(&count.to_le_bytes());
+        }
+
+        for rows in &self.tables {
+            for row in rows {
+                for col in row {
+                    match *col {
+                        Col::U8(v) => tables.push(v),
+                        Col::U16(v) => tables.extend_from_slice(&v.to_le_bytes()),
+                        Col::U32(v) | Col::Str(v) | Col::Blob(v) | Col::Guid(v) => {
+                            tables.extend_from_slice(&v.to_le_bytes())
+                        }
+                        Col::Index(table, v) => write_sized(&mut tables, v, index_size(table)),
+                        Col::Coded(coded, v) => write_sized(&mut tables, v, coded_size(coded)),
+                    }
+                }
+            }
+        }
+
+        let streams: [(&str, Vec<u8>); 5] = [
+            ("#~", tables),
+            ("#Strings", std::mem::take(&mut self.heaps.strings)),
+            ("#US", vec![0]),
+            ("#GUID", std::mem::take(&mut self.heaps.guids)),
+            ("#Blob", std::mem::take(&mut self.heaps.blobs)),
+        ];
+
+        let version = b"v4.0.30319\0\0";
+        let header_size: usize = 16
+            + version.len()
+            + 4
+            + streams.iter().map(|(name, _)| 8 + align(name.len() + 1, 4)).sum::<usize>();
+
+        let mut metadata = Vec::new();
+        metadata.extend_from_slice(&0x424A_5342u32.to_le_bytes());
+        metadata.extend_from_slice(&1u16.to_le_bytes());
+        metadata.extend_from_slice(&1u16.to_le_bytes());
+        metadata.extend_from_slice(&0u32.to_le_bytes());
+        metadata.extend_from_slice(&(version.len() as u32).to_le_bytes());
+        metadata.extend_from_slice(version);
+        metadata.extend_from_slice(&0u16.to_le_bytes());
+        metadata.extend_from_slice(&(streams.len() as u16).to_le_bytes());
+
+        let mut offset = header_size;
+        for (name, data) in &streams {
+            let size = align(data.len(), 4);
+            metadata.extend_from_slice(&(offset as u32).to_le_bytes());
+            metadata.extend_from_slice(&(size as u32).to_le_bytes());
+            metadata.extend_from_slice(name.as_bytes());
+            metadata.resize(align(metadata.len() + 1, 4), 0);
+            offset += size;
+        }
+        for (_, data) in &streams {
+            metadata.extend_from_slice(data);
+            metadata.resize(align(metadata.len(), 4), 0);
+        }
+
+        metadata
+    }
+}
+
+/// Lay out a single-section IL-only PE32 image around the metadata
+///
+/// `.text` holds the CLI header, the shared stub method body and the metadata.
+/// MethodDef rows whose RVA column is 1 are pointed at the stub body.
+fn write_pe(metadata: &[u8]) -> Vec<u8> {
+    let body_rva = TEXT_RVA + CLI_HEADER_SIZE;
+    let metadata_rva = align((body_rva as usize) + STUB_BODY.len(), 4) as u32;
+    let text_size = metadata_rva - TEXT_RVA + metadata.len() as u32;
+    let text_raw_size = align(text_size as usize, FILE_ALIGNMENT as usize) as u32;
+    let size_of_image = TEXT_RVA + align(text_size as usize, SECTION_ALIGNMENT as usize) as u32;
+
+    let mut image = vec![0u8; FILE_ALIGNMENT as usize];
+
+    // DOS header, e_lfanew = 0x80
+    image[0..2].copy_from_slice(b"MZ");
+    image[0x3C..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+
+    let mut header = Vec::new();
+    header.extend_from_slice(b"PE\0\0");
+    // COFF header
+    header.extend_from_slice(&0x014Cu16.to_le_bytes()); // I386
+    header.extend_from_slice(&1u16.to_le_bytes()); // NumberOfSections
+    header.extend_from_slice(&0u32.to_le_bytes()); // TimeDateStamp
+    header.extend_from_slice(&0u32.to_le_bytes()); // PointerToSymbolTable
+    header.extend_from_slice(&0u32.to_le_bytes()); // NumberOfSymbols
+    header.extend_from_slice(&0xE0u16.to_le_bytes()); // SizeOfOptionalHeader
+    header.extend_from_slice(&0x2102u16.to_le_bytes()); // EXECUTABLE_IMAGE | 32BIT_MACHINE | DLL
+
+    // Optional header (PE32)
+    header.extend_from_slice(&0x10Bu16.to_le_bytes());
+    header.extend_from_slice(&[8, 0]); // Linker version
+    header.extend_from_slice(&text_raw_size.to_le_bytes()); // SizeOfCode
+    header.extend_from_slice(&0u32.to_le_bytes()); // SizeOfInitializedData
+    header.extend_from_slice(&0u32.to_le_bytes()); // SizeOfUninitializedData
+    header.extend_from_slice(&0u32.to_le_bytes()); // AddressOfEntryPoint
+    header.extend_from_slice(&TEXT_RVA.to_le_bytes()); // BaseOfCode
+    header.extend_from_slice(&0u32.to_le_bytes()); // BaseOfData
+    header.extend_from_slice(&IMAGE_BASE.to_le_bytes());
+    header.extend_from_slice(&SECTION_ALIGNMENT.to_le_bytes());
+    header.extend_from_slice(&FILE_ALIGNMENT.to_le_bytes());
+    header.extend_from_slice(&[4, 0, 0, 0]); // OS version
+    header.extend_from_slice(&[0, 0, 0, 0]); // Image version
+    header.extend_from_slice(&[4, 0, 0, 0]); // Subsystem version
+    header.extend_from_slice(&0u32.to_le_bytes()); // Win32VersionValue
+    header.extend_from_slice(&size_of_image.to_le_bytes());
+    header.extend_from_slice(&FILE_ALIGNMENT.to_le_bytes()); // SizeOfHeaders
+    header.extend_from_slice(&0u32.to_le_bytes()); // CheckSum
+    header.extend_from_slice(&3u16.to_le_bytes()); // WINDOWS_CUI
+    header.extend_from_slice(&0x8540u16.to_le_bytes()); // DYNAMIC_BASE | NX_COMPAT | NO_SEH | TS_AWARE
+    header.extend_from_slice(&0x0010_0000u32.to_le_bytes()); // SizeOfStackReserve
+    header.extend_from_slice(&0x1000u32.to_le_bytes()); // SizeOfStackCommit
+    header.extend_from_slice(&0x0010_0000u32.to_le_bytes()); // SizeOfHeapReserve
+    header.extend_from_slice(&0x1000u32.to_le_bytes()); // SizeOfHeapCommit
+    header.extend_from_slice(&0u32.to_le_bytes()); // LoaderFlags
+    header.extend_from_slice(&16u32.to_le_bytes()); // NumberOfRvaAndSizes
+    for index in 0..16 {
+        if index == 14 {
+            header.extend_from_slice(&TEXT_RVA.to_le_bytes());
+            header.extend_from_slice(&CLI_HEADER_SIZE.to_le_bytes());
+        } else {
+            header.extend_from_slice(&[0; 8]);
+        }
+    }
+
+    // Section header
+    header.extend_from_slice(b".text\0\0\0");
+    header.extend_from_slice(&text_size.to_le_bytes());
+    header.extend_from_slice(&TEXT_RVA.to_le_bytes());
+    header.extend_from_slice(&text_raw_size.to_le_bytes());
+    header.extend_from_slice(&FILE_ALIGNMENT.to_le_bytes());
+    header.extend_from_slice(&[0; 12]);
+    header.extend_from_slice(&0x6000_0020u32.to_le_bytes()); // CODE | EXECUTE | READ
+
+    image[0x80..0x80 + header.len()].copy_from_slice(&header);
+
+    // CLI header
+    let mut text = Vec::with_capacity(text_raw_size as usize);
+    text.extend_from_slice(&CLI_HEADER_SIZE.to_le_bytes());
+    text.extend_from_slice(&2u16.to_le_bytes());
+    text.extend_from_slice(&5u16.to_le_bytes());
+    text.extend_from_slice(&metadata_rva.to_le_bytes());
+    text.extend_from_slice(&(metadata.len() as u32).to_le_bytes());
+    text.extend_from_slice(&1u32.to_le_bytes()); // COMIMAGE_FLAGS_ILONLY
+    text.resize(CLI_HEADER_SIZE as usize, 0);
+
+    text.extend_from_slice(&STUB_BODY);
+    text.resize((metadata_rva - TEXT_RVA) as usize, 0);
+
+    let mut metadata = metadata.to_vec();
+    patch_method_rvas(&mut metadata, body_rva);
+    text.extend_from_slice(&metadata);
+    text.resize(text_raw_size as usize, 0);
+
+    image.extend_from_slice(&text);
+    image
+}
+
+/// Replace the placeholder RVA (1) of MethodDef rows with the stub body's RVA
+fn patch_method_rvas(metadata: &mut [u8], body_rva: u32) {
+    let Some(layout) = TablesLayout::read(metadata) else {
+        return;
+    };
+    for row in 0..layout.rows[table::METHOD_DEF] {
+        let offset = layout.row_offset(table::METHOD_DEF, row);
+        if metadata[offset..offset + 4] == 1u32.to_le_bytes() {
+            metadata[offset..offset + 4].copy_from_slice(&body_rva.to_le_bytes());
+        }
+    }
+}
+
+/// Row sizes and offsets of the tables in a serialized metadata root
+struct TablesLayout {
+    rows: [u32; table::COUNT],
+    row_sizes: [usize; table::COUNT],
+    table_offsets: [usize; table::COUNT],
+}
+
+impl TablesLayout {
+    fn read(metadata: &[u8]) -> Option<Self> {
+        let u32_at = |o: usize| Some(u32::from_le_bytes(metadata.get(o..o + 4)?.try_into().ok()?));
+        let version_len = u32_at(12)? as usize;
+        let streams_offset = 16 + version_len + 4;
+        // `#~` is always written first
+        let tables_offset = u32_at(streams_offset)? as usize;
+        let valid = u64::from_le_bytes(metadata.get(tables_offset + 8..tables_offset + 16)?.try_into().ok()?);
+
+        let mut rows = [0u32; table::COUNT];
+        let mut cursor = tables_offset + 24;
+        for (i, count) in rows.iter_mut().enumerate() {
+            if valid & (1 << i) != 0 {
+                *count = u32_at(cursor)?;
+                cursor += 4;
+            }
+        }
+
+        let index_size = |t: usize| if rows[t] < 0x1_0000 { 2 } else { 4 };
+        let coded_size = |c: Coded| {
+            let max = c.tables().iter().map(|&t| rows.get(t).copied().unwrap_or(0)).max().unwrap_or(0);
+            if max < (1 << (16 - c.tag_bits())) { 2 } else { 4 }
+        };
+
+        let mut row_sizes = [0usize; table::COUNT];
+        row_sizes[table::MODULE] = 2 + 4 + 4 * 3;
+        row_sizes[table::TYPE_REF] = coded_size(Coded::ResolutionScope) + 8;
+        row_sizes[table::TYPE_DEF] = 4 + 8 + coded_size(Coded::TypeDefOrRef)
+            + index_size(table::FIELD)
+            + index_size(table::METHOD_DEF);
+        row_sizes[table::FIELD] = 2 + 4 + 4;
+        row_sizes[table::METHOD_DEF] = 4 + 2 + 2 + 4 + 4 + index_size(table::PARAM);
+        row_sizes[table::PARAM] = 2 + 2 + 4;
+        row_sizes[table::INTERFACE_IMPL] = index_size(table::TYPE_DEF) + coded_size(Coded::TypeDefOrRef);
+        row_sizes[table::MEMBER_REF] = coded_size(Coded::MemberRefParent) + 8;
+        row_sizes[table::CONSTANT] = 2 + coded_size(Coded::HasConstant) + 4;
+        row_sizes[table::CUSTOM_ATTRIBUTE] =
+            coded_size(Coded::HasCustomAttribute) + coded_size(Coded::CustomAttributeType) + 4;
+        row_sizes[table::ASSEMBLY] = 4 + 8 + 4 + 4 + 4 + 4;
+        row_sizes[table::ASSEMBLY_REF] = 8 + 4 + 4 + 4 + 4 + 4;
+
+        let mut table_offsets = [0usize; table::COUNT];
+        for i in 0..table::COUNT {
+            table_offsets[i] = cursor;
+            cursor += row_sizes[i] * rows[i] as usize;
+        }
+
+        Some(Self { rows, row_sizes, table_offsets })
+    }
+
+    /// Offset of 0-based `row` of `table` within the metadata root
+    fn row_offset(&self, table: usize, row: u32) -> usize {
+        self.table_offsets[table] + self.row_sizes[table] * row as usize
+    }
+}
+
+fn align(value: usize, alignment: usize) -> usize {
+    value.div_ceil(alignment) * alignment
+}
+
+fn write_sized(out: &mut Vec<u8>, value: u32, size: usize) {
+    if size == 2 {
+        out.extend_from_slice(&(value as u16).to_le_bytes());
+    } else {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+/// ECMA-335 II.23.2 compressed unsigned integer
+fn write_compressed(out: &mut Vec<u8>, value: u32) {
+    if value < 0x80 {
+        out.push(value as u8);
+    } else if value < 0x4000 {
+        out.extend_from_slice(&(0x8000 | value as u16).to_be_bytes());
+    } else {
+        out.extend_from_slice(&(0xC000_0000 | value).to_be_bytes());
+    }
+}
+
+/// Length-prefixed UTF-8 string as used in custom attribute blobs
+fn write_ser_string(out: &mut Vec<u8>, value: &str) {
+    write_compressed(out, value.len() as u32);
+    out.extend_from_slice(value.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use endfield_binary_parser::pe::PeFile;
+    use endfield_core::{DumpStatistics, MethodParameter};
+
+    fn dumped_type(full_name: &str, assembly: &str, parent: Option<&str>) -> DumpedType {
+        let (namespace, name) = full_name.rsplit_once('.').unwrap_or(("", full_name));
+        DumpedType {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            namespace: namespace.to_string(),
+            full_name: full_name.to_string(),
+            assembly: assembly.to_string(),
+            parent_type: parent.map(str::to_string),
+            interfaces: Vec::new(),
+            fields: Vec::new(),
+            methods: Vec::new(),
+            properties: Vec::new(),
+            is_enum: false,
+            is_interface: false,
+            is_abstract: false,
+            is_sealed: false,
+            token: 0x02000002,
+        }
+    }
+
+    fn sample_results() -> DumpResults {
+        let send = DumpedMethod {
+            id: Uuid::new_v4(),
+            name: "Send".to_string(),
+            full_name: "Game.Net.NetManager$$Send".to_string(),
+            address: Address::new(0x1234),
+            return_type: "System.Boolean".to_string(),
+            parameters: vec![MethodParameter {
+                name: "cmd".to_string(),
+                type_name: "Game.Net.Cmd".to_string(),
+                index: 0,
+            }],
+            class_name: "NetManager".to_string(),
+            namespace: "Game.Net".to_string(),
+            is_static: false,
+            is_virtual: false,
+            is_abstract: false,
+            token: 0x06000010,
+            size: None,
+        };
+
+        let mut manager = dumped_type("Game.Net.NetManager", "Assembly-CSharp.dll", Some("Other.Base"));
+        manager.methods.push(send.id);
+        manager.fields.push(DumpedField {
+            name: "port".to_string(),
+            type_name: "System.Int32".to_string(),
+            offset: 0x18,
+            is_static: false,
+            is_const: false,
+            default_value: None,
+        });
+
+        let mut cmd = dumped_type("Game.Net.Cmd", "Assembly-CSharp.dll", Some("System.Enum"));
+        cmd.is_enum = true;
+        cmd.fields.push(DumpedField {
+            name: "Login".to_string(),
+            type_name: "Game.Net.Cmd".to_string(),
+            offset: 0,
+            is_static: true,
+            is_const: false,
+            default_value: Some("7".to_string()),
+        });
+
+        DumpResults {
+            timestamp: Utc::now(),
+            unity_version: None,
+            il2cpp_version: 29,
+            types: vec![manager, cmd, dumped_type("Other.Base", "Other.dll", None)],
+            methods: vec![send],
+            string_literals: Vec::new(),
+            statistics: DumpStatistics::default(),
+        }
+    }
+
+    /// Minimal reader for the tables this writer emits
+    struct Reader<'a> {
+        metadata: &'a [u8],
+        layout: TablesLayout,
+        strings: usize,
+        blobs: usize,
+    }
+
+    impl<'a> Reader<'a> {
+        fn new(image: &'a [u8]) -> Self {
+            let u32_at = |o: usize| u32::from_le_bytes(image[o..o + 4].try_into().unwrap());
+            let pe = u32_at(0x3C) as usize;
+            // Data directory 14 of a PE32 optional header
+            let cli_rva = u32_at(pe + 24 + 96 + 14 * 8);
+            let section = pe + 24 + 0xE0;
+            let to_offset = |rva: u32| (rva - u32_at(section + 12) + u32_at(section + 20)) as usize;
+
+            let cli = to_offset(cli_rva);
+            assert_eq!(u32_at(cli), CLI_HEADER_SIZE);
+            let metadata = &image[to_offset(u32_at(cli + 8))..][..u32_at(cli + 12) as usize];
+            assert_eq!(&metadata[..4], b"BSJB");
+
+            let mut streams = HashMap::new();
+            let version_len = u32::from_le_bytes(metadata[12..16].try_into().unwrap()) as usize;
+            let mut cursor = 16 + version_len + 4;
+            for _ in 0..u16::from_le_bytes(metadata[cursor - 2..cursor].try_into().unwrap()) {
+                let offset = u32::from_le_bytes(metadata[cursor..cursor + 4].try_into().unwrap());
+                let name_len = metadata[cursor + 8..].iter().position(|&b| b == 0).unwrap();
+                let name = std::str::from_utf8(&metadata[cursor + 8..cursor + 8 + name_len]).unwrap();
+                streams.insert(name.to_string(), offset as usize);
+                cursor += 8 + align(name_len + 1, 4);
+            }
+
+            Self {
+                metadata,
+                layout: TablesLayout::read(metadata).unwrap(),
+                strings: streams["#Strings"],
+                blobs: streams["#Blob"],
+            }
+        }
+
+        fn column(&self, table: usize, row: u32, offset: usize, size: usize) -> u32 {
+            let at = self.layout.row_offset(table, row) + offset;
+            let mut bytes = [0u8; 4];
+            bytes[..size].copy_from_slice(&self.metadata[at..at + size]);
+            u32::from_le_bytes(bytes)
+        }
+
+        fn string(&self, index: u32) -> &str {
+            let data = &self.metadata[self.strings + index as usize..];
+            std::str::from_utf8(&data[..data.iter().position(|&b| b == 0).unwrap()]).unwrap()
+        }
+
+        fn blob(&self, index: u32) -> &[u8] {
+            let data = &self.metadata[self.blobs + index as usize..];
+            // Blobs in the fixture are all shorter than 0x80 bytes
+            &data[1..1 + data[0] as usize]
+        }
+
+        fn type_names(&self) -> Vec<String> {
+            (0..self.layout.rows[table::TYPE_DEF])
+                .map(|row| {
+                    let name = self.string(self.column(table::TYPE_DEF, row, 4, 4));
+                    let namespace = self.string(self.column(table::TYPE_DEF, row, 8, 4));
+                    if namespace.is_empty() {
+                        name.to_string()
+                    } else {
+                        format!("{}.{}", namespace, name)
+                    }
+                })
+                .collect()
+        }
+
+        /// Named string arguments of every custom attribute on `parent`
+        fn attributes(&self, parent: u32) -> Vec<(String, String)> {
+            let mut named = Vec::new();
+            for row in 0..self.layout.rows[table::CUSTOM_ATTRIBUTE] {
+                if self.column(table::CUSTOM_ATTRIBUTE, row, 0, 2) != parent {
+                    continue;
+                }
+                let value = self.blob(self.column(table::CUSTOM_ATTRIBUTE, row, 4, 4));
+                assert_eq!(&value[..2], &[1, 0]);
+                let mut cursor = 4;
+                for _ in 0..u16::from_le_bytes([value[2], value[3]]) {
+                    assert_eq!(&value[cursor..cursor + 2], &[0x53, element_type::STRING]);
+                    cursor += 2;
+                    let mut read = || {
+                        let len = value[cursor] as usize;
+                        let s = std::str::from_utf8(&value[cursor + 1..cursor + 1 + len]).unwrap();
+                        cursor += 1 + len;
+                        s.to_string()
+                    };
+                    let name = read();
+                    named.push((name, read()));
+                }
+            }
+            named
+        }
+    }
+
+    #[test]
+    fn test_one_dll_per_assembly() {
+        let dlls = DummyDllGenerator::generate(&sample_results(), None);
+        let names: Vec<&str> = dlls.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["Assembly-CSharp.dll", "Other.dll"]);
+
+        // The output is a valid PE as far as the binary parser is concerned
+        for (_, image) in &dlls {
+            PeFile::parse(image).unwrap();
+        }
+        assert_eq!(dlls, DummyDllGenerator::generate(&sample_results(), None));
+    }
+
+    #[test]
+    fn test_tables_round_trip() {
+        let dlls = DummyDllGenerator::generate(&sample_results(), None);
+        let reader = Reader::new(&dlls[0].1);
+
+        assert_eq!(
+            reader.type_names(),
+            [
+                "<Module>",
+                "Il2CppDummyDll.AddressAttribute",
+                "Il2CppDummyDll.FieldOffsetAttribute",
+                "Il2CppDummyDll.TokenAttribute",
+                "Game.Net.Cmd",
+                "Game.Net.NetManager",
+            ]
+        );
+
+        // Rows are sorted by parent, so consecutive rows share it
+        let parents: Vec<u32> = (0..reader.layout.rows[table::CUSTOM_ATTRIBUTE])
+            .map(|row| reader.column(table::CUSTOM_ATTRIBUTE, row, 0, 2))
+            .collect();
+        assert!(parents.windows(2).all(|w| w[0] <= w[1]));
+
+        // Send is the fourth method: three attribute constructors come first
+        let send = Coded::HasCustomAttribute.encode(table::METHOD_DEF, 4);
+        assert_eq!(reader.string(reader.column(table::METHOD_DEF, 3, 8, 4)), "Send");
+        assert_eq!(
+            reader.attributes(send),
+            [
+                ("Token".to_string(), "0x06000010".to_string()),
+                ("RVA".to_string(), "0x1234".to_string()),
+            ]
+        );
+        let body = reader.column(table::METHOD_DEF, 3, 0, 4);
+        assert_eq!(body, TEXT_RVA + CLI_HEADER_SIZE);
+
+        // Field rows: 4 + 1 + 1 attribute fields, then Login and port
+        let port = Coded::HasCustomAttribute.encode(table::FIELD, 8);
+        assert_eq!(reader.string(reader.column(table::FIELD, 7, 2, 4)), "port");
+        assert_eq!(reader.attributes(port), [("Offset".to_string(), "0x18".to_string())]);
+
+        assert_eq!(reader.layout.rows[table::CONSTANT], 1);
+        assert_eq!(reader.blob(reader.column(table::CONSTANT, 0, 4, 4)), 7i32.to_le_bytes());
+
+        // mscorlib and Other.dll are both referenced
+        assert_eq!(reader.layout.rows[table::ASSEMBLY_REF], 2);
+    }
+
+    #[test]
+    fn test_constant_element_types() {
+        let field = |name: &str, type_name: &str, is_static, value: Option<&str>| DumpedField {
+            name: name.to_string(),
+            type_name: type_name.to_string(),
+            offset: 0,
+            is_static,
+            is_const: !is_static && value.is_some(),
+            default_value: value.map(str::to_string),
+        };
+        let mut results = sample_results();
+        let mut flags = dumped_type("Game.Net.Flags", "Assembly-CSharp.dll", Some("System.Enum"));
+        flags.is_enum = true;
+        flags.fields = vec![
+            field("value__", "System.Byte", false, None),
+            field("All", "Game.Net.Flags", true, Some("255")),
+        ];
+        results.types[0].fields.extend([
+            field("Big", "System.Int64", false, Some("5000000000")),
+            field("Mask", "System.UInt32", false, Some("4294967295")),
+            field("Scale", "System.Int16", false, Some("-2")),
+        ]);
+        results.types.push(flags);
+
+        let dlls = DummyDllGenerator::generate(&results, None);
+        let reader = Reader::new(&dlls[0].1);
+        let constants: Vec<(u32, Vec<u8>)> = (0..reader.layout.rows[table::CONSTANT])
+            .map(|row| {
+                let blob = reader.blob(reader.column(table::CONSTANT, row, 4, 4));
+                (reader.column(table::CONSTANT, row, 0, 1), blob.to_vec())
+            })
+            .collect();
+        for expected in [
+            (element_type::I4, 7i32.to_le_bytes().to_vec()),
+            (element_type::U1, vec![255]),
+            (element_type::I8, 5_000_000_000i64.to_le_bytes().to_vec()),
+            (element_type::U4, u32::MAX.to_le_bytes().to_vec()),
+            (element_type::I2, (-2i16).to_le_bytes().to_vec()),
+        ] {
+            assert!(constants.contains(&(expected.0 as u32, expected.1.clone())), "missing {:?}", expected);
+        }
+        assert_eq!(constants.len(), 5);
+    }
+}
@@ -1,9 +1,11 @@
 //! IL2CPP dumper - extracts and organizes IL2CPP metadata
 
 use crate::metadata::Metadata;
+use crate::registration::Registrations;
 use crate::search;
 use crate::types::*;
-use endfield_binary_parser::{self, BinaryFile};
+use endfield_binary_parser::common::SymbolType;
+use endfield_binary_parser::{self, BinaryFile, Disassembler, Instruction, Package};
 use endfield_core::{
     Address, DumpResults, DumpStatistics, DumpedField, DumpedMethod, DumpedProperty,
     DumpedType, MethodParameter, Result, StringLiteral,
@@ -26,10 +28,65 @@ impl Il2CppDumper {
         let binary = endfield_binary_parser::load_binary(binary_path)
             .map_err(|e| endfield_core::Error::parse(e.to_string()))?;
 
+        Self::with_binary(binary, metadata_path)
+    }
+
+    /// Create a new dumper, selecting the `arch` slice (e.g. `arm64e`) of a
+    /// universal Mach-O binary
+    pub fn new_with_arch(binary_path: &Path, metadata_path: &Path, arch: &str) -> Result<Self> {
+        info!("Loading {} slice of binary from {:?}", arch, binary_path);
+        let binary = endfield_binary_parser::load_binary_arch(binary_path, arch)
+            .map_err(|e| endfield_core::Error::parse(e.to_string()))?;
+
+        Self::with_binary(binary, metadata_path)
+    }
+
+    /// Create a new dumper from an already parsed binary and metadata
+    pub fn from_parts(binary: Box<dyn BinaryFile>, metadata: Metadata) -> Self {
+        Self { binary, metadata }
+    }
+
+    /// Create a new dumper from in-memory binary and metadata bytes
+    pub fn from_bytes(binary: &[u8], metadata: &[u8]) -> Result<Self> {
+        let binary = endfield_binary_parser::parse_binary(binary)
+            .map_err(|e| endfield_core::Error::parse(e.to_string()))?;
+        let metadata = Metadata::parse(metadata)?;
+
+        Ok(Self::from_parts(binary, metadata))
+    }
+
+    /// Create a new dumper from an APK, XAPK or IPA package
+    ///
+    /// `abi` selects the Android ABI or Mach-O slice; `None` picks the default.
+    pub fn from_package(package_path: &Path, abi: Option<&str>) -> Result<Self> {
+        info!("Loading package from {:?}", package_path);
+        let mut package = Package::open(package_path)
+            .map_err(|e| endfield_core::Error::parse(e.to_string()))?;
+        let binary = package
+            .binary(abi)
+            .map_err(|e| endfield_core::Error::parse(e.to_string()))?;
+        let metadata = package
+            .extract_metadata()
+            .map_err(|e| endfield_core::Error::parse(e.to_string()))?;
+
+        Ok(Self::from_parts(binary, Metadata::parse(&metadata)?))
+    }
+
+    fn with_binary(binary: Box<dyn BinaryFile>, metadata_path: &Path) -> Result<Self> {
         info!("Loading metadata from {:?}", metadata_path);
         let metadata = Metadata::parse(&std::fs::read(metadata_path)?)?;
 
-        Ok(Self { binary, metadata })
+        Ok(Self::from_parts(binary, metadata))
+    }
+
+    /// Merge symbols from a PDB or a DWARF-carrying binary into the binary,
+    /// returning how many were new
+    pub fn load_debug_symbols(&mut self, path: &Path) -> Result<usize> {
+        info!("Loading debug symbols from {:?}", path);
+        let added = endfield_binary_parser::load_debug_symbols(self.binary.as_mut(), path)
+            .map_err(|e| endfield_core::Error::parse(e.to_string()))?;
+        debug!("Added {} debug symbols", added);
+        Ok(added)
     }
 
     /// Perform the dump
@@ -37,15 +94,52 @@ impl Il2CppDumper {
         info!("Starting IL2CPP dump");
 
         // Search for registration structures
-        let _search_result = search::search_registrations(
+        let search_result = search::search_registrations(
             self.binary.as_ref(),
             self.metadata.type_definitions.len(),
             self.metadata.method_definitions.len(),
         );
+        let mut addresses = Vec::new();
+        let mut string_slots = Vec::new();
+        let mut field_offsets = Vec::new();
+        if let Some(found) = &search_result {
+            match Registrations::read(self.binary.as_ref(), found, &self.metadata) {
+                Ok(registrations) => {
+                    info!("Read registrations with the v{} layout", registrations.version);
+                    addresses = registrations.method_addresses(self.binary.as_ref(), &self.metadata);
+                    string_slots = registrations.string_literal_slots(self.binary.as_ref(), &self.metadata);
+                    field_offsets = registrations.field_offsets(self.binary.as_ref(), &self.metadata);
+                }
+                Err(e) => warn!(
+                    "Registrations at {} / {} failed validation: {}",
+                    found.code_registration, found.metadata_registration, e
+                ),
+            }
+        }
 
         // Convert metadata to dumped types and methods
-        let (types, methods) = self.process_types_and_methods();
-        let string_literals = self.process_string_literals();
+        let (types, mut methods) = self.process_types_and_methods(&field_offsets);
+        let image_base = self.binary.image_base().as_u64();
+        for (method, &va) in methods.iter_mut().zip(&addresses) {
+            // Pointers outside the image, below its base included, are garbage
+            if va != Address::ZERO && self.binary.va_to_offset(va).is_some() {
+                method.address = Address::new(va.as_u64().wrapping_sub(image_base));
+                method.size = self.method_size(va);
+            }
+        }
+        debug!(
+            "Resolved {} method addresses",
+            methods.iter().filter(|m| m.address != Address::ZERO).count()
+        );
+        let (agreed, disagreed) = self.cross_check_names(&methods);
+        if agreed + disagreed > 0 {
+            info!("{} of {} resolved methods match the binary's symbol names", agreed, agreed + disagreed);
+        }
+        let string_literals = self.process_string_literals(&string_slots);
+        debug!(
+            "Resolved {} string literal slots",
+            string_literals.iter().filter(|s| s.address != Address::ZERO).count()
+        );
 
         let statistics = DumpStatistics {
             total_types: types.len(),
@@ -71,7 +165,26 @@ impl Il2CppDumper {
         })
     }
 
-    fn process_types_and_methods(&self) -> (Vec<DumpedType>, Vec<DumpedMethod>) {
+    /// Disassemble code at `address`, naming targets after dumped methods
+    pub fn disassemble(
+        &self,
+        results: &DumpResults,
+        address: Address,
+        max_instructions: usize,
+    ) -> Result<Vec<Instruction>> {
+        let image_base = self.binary.image_base().as_u64();
+        let names = results
+            .methods
+            .iter()
+            .filter(|m| m.address != Address::ZERO)
+            .map(|m| (Address::new(image_base.wrapping_add(m.address.as_u64())), m.full_name.clone()));
+        Disassembler::new(self.binary.as_ref())
+            .with_names(names)
+            .disassemble(address, max_instructions)
+            .map_err(|e| endfield_core::Error::parse(e.to_string()))
+    }
+
+    fn process_types_and_methods(&self, field_offsets: &[u32]) -> (Vec<DumpedType>, Vec<DumpedMethod>) {
         let mut types = Vec::with_capacity(self.metadata.type_definitions.len());
         let mut methods = Vec::with_capacity(self.metadata.method_definitions.len());
         let mut method_map = std::collections::HashMap::new();
@@ -83,9 +196,24 @@ impl Il2CppDumper {
             methods.push(method);
         }
 
+        // Map each type to the image that defines it
+        let mut assemblies = vec![""; self.metadata.type_definitions.len()];
+        for image in &self.metadata.image_definitions {
+            let name = self.metadata.get_string(image.name_index).unwrap_or("");
+            if image.type_start < 0 {
+                continue;
+            }
+            let start = image.type_start as usize;
+            let end = start.saturating_add(image.type_count as usize).min(assemblies.len());
+            for slot in assemblies.iter_mut().take(end).skip(start) {
+                *slot = name;
+            }
+        }
+
         // Process all types
         for (idx, type_def) in self.metadata.type_definitions.iter().enumerate() {
-            let dumped_type = self.process_type(idx, type_def, &method_map);
+            let mut dumped_type = self.process_type(idx, type_def, &method_map, field_offsets);
+            dumped_type.assembly = assemblies[idx].to_string();
             types.push(dumped_type);
         }
 
@@ -97,6 +225,7 @@ impl Il2CppDumper {
         _idx: usize,
         type_def: &Il2CppTypeDefinition,
         method_map: &std::collections::HashMap<usize, Uuid>,
+        field_offsets: &[u32],
     ) -> DumpedType {
         let name = self
             .metadata
@@ -127,7 +256,7 @@ impl Il2CppDumper {
         let interfaces = self.get_interfaces(type_def);
 
         // Get fields
-        let fields = self.get_fields(type_def);
+        let fields = self.get_fields(type_def, field_offsets);
 
         // Get methods
         let method_ids: Vec<Uuid> = if type_def.method_start >= 0 {
@@ -148,6 +277,7 @@ impl Il2CppDumper {
             name,
             namespace,
             full_name,
+            assembly: String::new(),
             parent_type,
             interfaces,
             fields,
@@ -168,24 +298,27 @@ impl Il2CppDumper {
             .unwrap_or("<unknown>")
             .to_string();
 
-        let class_name = if method_def.declaring_type >= 0 {
-            let type_def = &self.metadata.type_definitions[method_def.declaring_type as usize];
-            self.metadata
+        // Corrupt metadata can point past the type table
+        let declaring_type = usize::try_from(method_def.declaring_type)
+            .ok()
+            .and_then(|index| self.metadata.type_definitions.get(index));
+
+        let class_name = match declaring_type {
+            Some(type_def) => self
+                .metadata
                 .get_string(type_def.name_index)
                 .unwrap_or("<unknown>")
-                .to_string()
-        } else {
-            String::new()
+                .to_string(),
+            None => String::new(),
         };
 
-        let namespace = if method_def.declaring_type >= 0 {
-            let type_def = &self.metadata.type_definitions[method_def.declaring_type as usize];
-            self.metadata
+        let namespace = match declaring_type {
+            Some(type_def) => self
+                .metadata
                 .get_string(type_def.namespace_index)
                 .unwrap_or("")
-                .to_string()
-        } else {
-            String::new()
+                .to_string(),
+            None => String::new(),
         };
 
         let return_type = self.get_type_name_by_index(method_def.return_type);
@@ -203,12 +336,13 @@ impl Il2CppDumper {
         );
 
         let flags = method_def.flags;
+        let address = Address::ZERO; // Resolved from the code registration in dump()
 
         DumpedMethod {
             id: Uuid::new_v4(),
             name,
             full_name,
-            address: Address::ZERO, // Would be filled from binary analysis
+            address,
             return_type,
             parameters,
             class_name,
@@ -217,9 +351,57 @@ impl Il2CppDumper {
             is_virtual: (flags & method_attributes::VIRTUAL) != 0,
             is_abstract: (flags & method_attributes::ABSTRACT) != 0,
             token: method_def.token,
+            size: None,
         }
     }
 
+    /// Compare resolved methods with the function symbols at their addresses,
+    /// returning how many agree and how many don't
+    ///
+    /// IL2CPP emits `Class_Method_m<hash>` with every other character turned
+    /// into `_`, so a symbol agrees when it starts with that prefix.
+    fn cross_check_names(&self, methods: &[DumpedMethod]) -> (usize, usize) {
+        let names: std::collections::HashMap<Address, &str> = self
+            .binary
+            .symbols()
+            .iter()
+            .filter(|symbol| symbol.symbol_type == SymbolType::Function)
+            .map(|symbol| (symbol.address, symbol.name.as_str()))
+            .collect();
+        let mangle = |name: &str| -> String {
+            name.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect()
+        };
+        let image_base = self.binary.image_base().as_u64();
+
+        let (mut agreed, mut disagreed) = (0, 0);
+        for method in methods.iter().filter(|m| m.address != Address::ZERO) {
+            let va = Address::new(image_base.wrapping_add(method.address.as_u64()));
+            let Some(&symbol) = names.get(&va) else {
+                continue;
+            };
+            let prefix = format!("{}_{}_", mangle(&method.class_name), mangle(&method.name));
+            // Mach-O symbols carry an extra leading underscore
+            if symbol.starts_with(&prefix) || symbol.strip_prefix('_').is_some_and(|s| s.starts_with(&prefix)) {
+                agreed += 1;
+            } else {
+                debug!("{} resolved to {} but the symbol there is {}", method.full_name, va, symbol);
+                disagreed += 1;
+            }
+        }
+        (agreed, disagreed)
+    }
+
+    /// Size of the function starting exactly at the virtual address `address`
+    fn method_size(&self, address: Address) -> Option<u64> {
+        if address == Address::ZERO {
+            return None;
+        }
+        self.binary
+            .function_at(address)
+            .filter(|function| function.start == address)
+            .map(|function| function.size())
+    }
+
     fn get_type_name(&self, idx: usize) -> Option<String> {
         let type_def = self.metadata.type_definitions.get(idx)?;
         let name = self.metadata.get_string(type_def.name_index)?;
@@ -262,7 +444,8 @@ impl Il2CppDumper {
             .collect()
     }
 
-    fn get_fields(&self, type_def: &Il2CppTypeDefinition) -> Vec<DumpedField> {
+    /// Fields of a type, at the offsets resolved from the binary (0 when unknown)
+    fn get_fields(&self, type_def: &Il2CppTypeDefinition, field_offsets: &[u32]) -> Vec<DumpedField> {
         if type_def.field_start < 0 || type_def.field_count == 0 {
             return Vec::new();
         }
@@ -283,7 +466,7 @@ impl Il2CppDumper {
                 Some(DumpedField {
                     name,
                     type_name,
-                    offset: 0, // Would be filled from field offsets in binary
+                    offset: field_offsets.get(start + i).copied().unwrap_or(0),
                     is_static: false, // Would be determined from type flags
                     is_const: false,
                     default_value: None,
@@ -346,15 +529,26 @@ impl Il2CppDumper {
             .collect()
     }
 
-    fn process_string_literals(&self) -> Vec<StringLiteral> {
+    /// String literals with the image-relative address of their slot, taken
+    /// from `slots` (virtual addresses indexed like the metadata's literals)
+    fn process_string_literals(&self, slots: &[Address]) -> Vec<StringLiteral> {
+        let image_base = self.binary.image_base().as_u64();
         self.metadata
             .string_literals
             .iter()
             .enumerate()
             .filter_map(|(idx, _)| {
                 let value = self.metadata.get_string_literal(idx)?;
+                // Slots usually sit in .bss, which has no file bytes to map
+                let slot = slots.get(idx).copied().unwrap_or(Address::ZERO);
+                let in_image = self.binary.sections().iter().any(|s| s.contains_va(slot.as_u64()));
+                let address = if slot != Address::ZERO && in_image {
+                    Address::new(slot.as_u64().wrapping_sub(image_base))
+                } else {
+                    Address::ZERO
+                };
                 Some(StringLiteral {
-                    address: Address::ZERO, // Would be filled from binary
+                    address,
                     value,
                     index: idx as u32,
                 })
@@ -366,6 +560,265 @@ impl Il2CppDumper {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use endfield_binary_parser::common::SectionFlags;
+    use endfield_binary_parser::test_util::TestBinary;
+    use endfield_binary_parser::{Function, Symbol};
+    use crate::xref::CrossReferences;
+    use endfield_core::Architecture;
+
+    const BASE: u64 = 0x10000;
+
+    /// v29 metadata with one image holding `Game.Player` and its two methods,
+    /// whose tokens run opposite to their definition order, and the UTF-16
+    /// literal data `hellohi` at 0x180
+    fn sample_metadata() -> Metadata {
+        let strings = b"Assembly-CSharp.dll\0Game\0Player\0Update\0Start\0";
+        let literals: Vec<u8> = "hellohi".encode_utf16().flat_map(|c| c.to_le_bytes()).collect();
+        let mut data = vec![0u8; 0x200];
+        data[..4].copy_from_slice(&METADATA_MAGIC.to_le_bytes());
+        data[4..8].copy_from_slice(&29u32.to_le_bytes());
+        data[0x100..0x100 + strings.len()].copy_from_slice(strings);
+        data[0x180..0x180 + literals.len()].copy_from_slice(&literals);
+
+        let mut metadata = Metadata::parse(&data).unwrap();
+        metadata.header.string_offset = 0x100;
+        metadata.header.string_literal_data_offset = 0x180;
+        metadata.image_definitions =
+            vec![Il2CppImageDefinition { name_index: 0, type_start: 0, type_count: 1, ..Default::default() }];
+        metadata.type_definitions = vec![Il2CppTypeDefinition {
+            name_index: 25,
+            namespace_index: 20,
+            method_start: 0,
+            method_count: 2,
+            ..Default::default()
+        }];
+        metadata.method_definitions = vec![
+            Il2CppMethodDefinition { name_index: 32, token: 0x0600_0002, ..Default::default() },
+            Il2CppMethodDefinition { name_index: 39, token: 0x0600_0001, ..Default::default() },
+        ];
+        metadata
+    }
+
+    /// Data section at 0x10000 holding v29.1 registrations for
+    /// [`sample_metadata`], with `Start` at RVA 0x1000 and `Update` at RVA 0x1040
+    fn sample_binary() -> TestBinary {
+        sample_binary_with([BASE + 0x1000, BASE + 0x1040])
+    }
+
+    /// [`sample_binary`] with its method pointers replaced and code mapped at
+    /// RVA 0x1000
+    fn sample_binary_with(method_pointers: [u64; 2]) -> TestBinary {
+        binary_from_words(&sample_words(method_pointers))
+    }
+
+    /// Words of the [`sample_binary_with`] data section
+    fn sample_words(method_pointers: [u64; 2]) -> Vec<u64> {
+        let word = |index: u64| BASE + index * 8;
+        let mut words = vec![0u64; 40];
+        // CodeRegistration: codeGenModulesCount and codeGenModules
+        words[15] = 1;
+        words[16] = word(31);
+        // MetadataRegistration: typeDefinitionsSizesCount and typeDefinitionsSizes
+        words[29] = 1;
+        words[30] = word(0);
+        // codeGenModules[0] -> { moduleName, methodPointerCount, methodPointers }
+        words[31] = word(32);
+        words[32..35].copy_from_slice(&[word(35), 2, word(38)]);
+        for (i, chunk) in b"Assembly-CSharp.dll\0\0\0\0\0".chunks(8).enumerate() {
+            words[35 + i] = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        words[38..40].copy_from_slice(&method_pointers);
+        words
+    }
+
+    /// Registrations data section from words, with code mapped at RVA 0x1000
+    fn binary_from_words(words: &[u64]) -> TestBinary {
+        let word = |index: u64| BASE + index * 8;
+        let mut binary = TestBinary::words(BASE, words, Architecture::Arm64).section(
+            ".text",
+            BASE + 0x1000,
+            &[0; 0x80],
+            SectionFlags::READ | SectionFlags::EXECUTE,
+        );
+        let symbol = |name: &str, address| Symbol {
+            name: name.to_string(),
+            address: Address::new(address),
+            size: None,
+            symbol_type: SymbolType::Object,
+            type_name: None,
+        };
+        binary.symbols = vec![symbol("g_CodeRegistration", word(0)), symbol("g_MetadataRegistration", word(17))];
+        binary
+    }
+
+    #[test]
+    fn test_resolve_method_addresses() {
+        let results = Il2CppDumper::from_parts(Box::new(sample_binary()), sample_metadata()).dump().unwrap();
+        let methods: Vec<(&str, u64)> = results.methods.iter().map(|m| (m.full_name.as_str(), m.address.as_u64())).collect();
+        assert_eq!(methods, [("Game.Player$$Update", 0x1040), ("Game.Player$$Start", 0x1000)]);
+    }
+
+    /// v24.5 metadata and registrations with `hello` and `hi` loaded through
+    /// the metadataUsages slots at words 37 and 36, and `Start` at RVA 0x1000
+    /// loading `hello`'s slot
+    fn string_literal_sample() -> (Metadata, TestBinary) {
+        let mut metadata = sample_metadata();
+        metadata.version = 24;
+        metadata.string_literals = vec![
+            Il2CppStringLiteral { length: 5, data_index: 0 },
+            Il2CppStringLiteral { length: 2, data_index: 10 },
+        ];
+        let pair = |destination_index, usage: u32, index: u32| Il2CppMetadataUsagePair {
+            destination_index,
+            encoded_source_index: (usage << 29) | index,
+        };
+        // hi and hello through metadataUsages[0] and [1], then a type usage
+        metadata.metadata_usage_pairs = vec![pair(1, 5, 0), pair(0, 5, 1), pair(0, 1, 0)];
+
+        // v24.5 registrations: CodeRegistration at word 0, MetadataRegistration
+        // at word 17, metadataUsages at word 34 pointing at slots 36 and 37
+        let word = |index: u64| BASE + index * 8;
+        let mut words = vec![0u64; 49];
+        words[15..17].copy_from_slice(&[1, word(40)]);
+        words[29..33].copy_from_slice(&[1, word(0), 2, word(34)]);
+        words[34..36].copy_from_slice(&[word(36), word(37)]);
+        words[40..44].copy_from_slice(&[word(41), word(44), 2, word(47)]);
+        for (i, chunk) in b"Assembly-CSharp.dll\0\0\0\0\0".chunks(8).enumerate() {
+            words[44 + i] = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        words[47] = BASE + 0x1000;
+        let code: Vec<u8> = [
+            0xF0FF_FFE0u32, // adrp x0, BASE
+            0xF940_9400,    // ldr x0, [x0, #0x128]
+            0xD65F_03C0,    // ret
+        ]
+        .iter()
+        .flat_map(|w| w.to_le_bytes())
+        .collect();
+        let mut binary = TestBinary::words(BASE, &words, Architecture::Arm64).section(
+            ".text",
+            BASE + 0x1000,
+            &code,
+            SectionFlags::READ | SectionFlags::EXECUTE,
+        );
+        let symbol = |name: &str, address| Symbol {
+            name: name.to_string(),
+            address: Address::new(address),
+            size: None,
+            symbol_type: SymbolType::Object,
+            type_name: None,
+        };
+        binary.symbols = vec![symbol("g_CodeRegistration", word(0)), symbol("g_MetadataRegistration", word(17))];
+        (metadata, binary)
+    }
+
+    #[test]
+    fn test_resolve_string_literal_slots() {
+        let (metadata, binary) = string_literal_sample();
+        let results = Il2CppDumper::from_parts(Box::new(binary), metadata).dump().unwrap();
+        let literals: Vec<(&str, u64)> =
+            results.string_literals.iter().map(|s| (s.value.as_str(), s.address.as_u64())).collect();
+        assert_eq!(literals, [("hello", 37 * 8), ("hi", 36 * 8)]);
+    }
+
+    #[test]
+    fn test_string_literal_uses() {
+        let (metadata, binary) = string_literal_sample();
+        let results = Il2CppDumper::from_parts(Box::new(binary), metadata).dump().unwrap();
+        let (_, binary) = string_literal_sample();
+        let xrefs = CrossReferences::build(&binary, &results);
+
+        let uses = xrefs.uses_of_string(&results.string_literals[0]);
+        assert_eq!(uses.len(), 1);
+        assert_eq!(uses[0].address, Address::new(BASE + 0x1004));
+        assert_eq!(uses[0].method_name.as_deref(), Some("Game.Player$$Start"));
+        assert!(xrefs.uses_of_string(&results.string_literals[1]).is_empty());
+    }
+
+    #[test]
+    fn test_resolve_field_offsets() {
+        let mut metadata = sample_metadata();
+        metadata.type_definitions[0].field_count = 2;
+        metadata.field_definitions = vec![
+            Il2CppFieldDefinition { name_index: 32, type_index: 0, token: 0x0400_0001 },
+            Il2CppFieldDefinition { name_index: 39, type_index: 0, token: 0x0400_0002 },
+        ];
+
+        // fieldOffsets -> { fieldOffsets[0] } -> { 0x10, 0x18 }
+        let mut words = sample_words([BASE + 0x1000, BASE + 0x1040]);
+        words[27..29].copy_from_slice(&[1, BASE + 40 * 8]);
+        words.extend([BASE + 41 * 8, 0x18 << 32 | 0x10]);
+
+        let results = Il2CppDumper::from_parts(Box::new(binary_from_words(&words)), metadata).dump().unwrap();
+        let fields: Vec<(&str, u32)> =
+            results.types[0].fields.iter().map(|f| (f.name.as_str(), f.offset)).collect();
+        assert_eq!(fields, [("Update", 0x10), ("Start", 0x18)]);
+    }
+
+    #[test]
+    fn test_unmapped_method_pointers() {
+        // One pointer below the image base, one past the end of the code
+        let binary = sample_binary_with([BASE - 0x1000, BASE + 0x1080]);
+        let results = Il2CppDumper::from_parts(Box::new(binary), sample_metadata()).dump().unwrap();
+        assert!(results.methods.iter().all(|m| m.address == Address::ZERO));
+    }
+
+    #[test]
+    fn test_method_sizes() {
+        let function = |start: u64, end: u64| Function { start: Address::new(start), end: Address::new(end) };
+        let mut binary = sample_binary();
+        // Start's function, then one beginning inside Update
+        binary.functions = vec![function(BASE + 0x1000, BASE + 0x1030), function(BASE + 0x1044, BASE + 0x1080)];
+
+        let results = Il2CppDumper::from_parts(Box::new(binary), sample_metadata()).dump().unwrap();
+        let sizes: Vec<Option<u64>> = results.methods.iter().map(|m| m.size).collect();
+        assert_eq!(sizes, [None, Some(0x30)]);
+    }
+
+    #[test]
+    fn test_cross_check_names() {
+        let symbol = |name: &str, address| Symbol {
+            name: name.to_string(),
+            address: Address::new(address),
+            size: None,
+            symbol_type: SymbolType::Function,
+            type_name: None,
+        };
+        let mut binary = sample_binary();
+        binary.symbols.push(symbol("Player_Start_m5A3C0F12", BASE + 0x1000));
+        binary.symbols.push(symbol("Enemy_Update_m9B01D4E7", BASE + 0x1040));
+
+        let dumper = Il2CppDumper::from_parts(Box::new(binary), sample_metadata());
+        let results = dumper.dump().unwrap();
+        assert_eq!(dumper.cross_check_names(&results.methods), (1, 1));
+    }
+
+    #[test]
+    fn test_out_of_range_indices() {
+        let mut metadata = sample_metadata();
+        metadata.method_definitions[0].declaring_type = 7;
+        metadata.type_definitions[0].field_start = i32::MAX;
+        metadata.type_definitions[0].field_count = u16::MAX;
+        metadata.image_definitions[0].type_count = u32::MAX;
+
+        let results = Il2CppDumper::from_parts(Box::new(TestBinary::empty()), metadata).dump().unwrap();
+        assert_eq!(results.methods[0].full_name, "$$Update");
+        assert!(results.types[0].fields.is_empty());
+        assert_eq!(results.types[0].assembly, "Assembly-CSharp.dll");
+    }
+
+    #[test]
+    fn test_dump_from_parts() {
+        let mut data = vec![0u8; 0x200];
+        data[..4].copy_from_slice(&METADATA_MAGIC.to_le_bytes());
+        data[4..8].copy_from_slice(&24u32.to_le_bytes());
+        let metadata = Metadata::parse(&data).unwrap();
+
+        let results = Il2CppDumper::from_parts(Box::new(TestBinary::empty()), metadata).dump().unwrap();
+        assert_eq!(results.il2cpp_version, 24);
+        assert!(results.types.is_empty());
+        assert!(results.methods.is_empty());
+    }
 
     #[test]
     fn test_full_name_generation() {
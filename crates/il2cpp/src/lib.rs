@@ -7,10 +7,17 @@ pub mod metadata;
 pub mod types;
 pub mod dumper;
 pub mod search;
+pub mod registration;
 pub mod output;
+pub mod dummy_dll;
+pub mod xref;
+pub mod analysis;
 
 pub use metadata::Metadata;
 pub use dumper::Il2CppDumper;
+pub use xref::{CrossReferences, XrefSite};
+pub use registration::Registrations;
+pub use analysis::{CallGraph, ControlFlowGraph};
 pub use types::*;
 
 use endfield_core::{DumpResults, Result};
@@ -27,3 +34,9 @@ pub fn dump(binary_path: &Path, metadata_path: &Path) -> Result<DumpResults> {
     let dumper = Il2CppDumper::new(binary_path, metadata_path)?;
     dumper.dump()
 }
+
+/// Dump IL2CPP information from in-memory binary and metadata bytes
+pub fn dump_bytes(binary: &[u8], metadata: &[u8]) -> Result<DumpResults> {
+    let dumper = Il2CppDumper::from_bytes(binary, metadata)?;
+    dumper.dump()
+}
@@ -3,7 +3,7 @@
 use crate::types::*;
 use byteorder::{LittleEndian, ReadBytesExt};
 use endfield_core::{Error, Result};
-use std::io::Cursor;
+use std::io::{Cursor, Read};
 use tracing::{debug, info, warn};
 
 /// Parsed IL2CPP metadata
@@ -40,6 +40,8 @@ pub struct Metadata {
     pub interfaces: Vec<i32>,
     /// Nested types
     pub nested_types: Vec<i32>,
+    /// Metadata usage pairs (v19 to v24)
+    pub metadata_usage_pairs: Vec<Il2CppMetadataUsagePair>,
 }
 
 impl Metadata {
@@ -86,6 +88,7 @@ impl Metadata {
         let string_literals = Self::read_string_literals(data, &header)?;
         let interfaces = Self::read_interfaces(data, &header)?;
         let nested_types = Self::read_nested_types(data, &header)?;
+        let metadata_usage_pairs = Self::read_metadata_usage_pairs(data, &header)?;
 
         info!(
             "Parsed {} types, {} methods, {} fields",
@@ -111,6 +114,7 @@ impl Metadata {
             string_literals,
             interfaces,
             nested_types,
+            metadata_usage_pairs,
         })
     }
 
@@ -167,6 +171,13 @@ impl Metadata {
         header.assemblies_size = cursor.read_u32::<LittleEndian>()?;
 
         // Version-specific fields
+        if (19..=24).contains(&version) {
+            header.metadata_usage_lists_offset = cursor.read_u32::<LittleEndian>()?;
+            header.metadata_usage_lists_size = cursor.read_u32::<LittleEndian>()?;
+            header.metadata_usage_pairs_offset = cursor.read_u32::<LittleEndian>()?;
+            header.metadata_usage_pairs_size = cursor.read_u32::<LittleEndian>()?;
+        }
+
         if version >= 19 {
             header.field_refs_offset = cursor.read_u32::<LittleEndian>()?;
             header.field_refs_size = cursor.read_u32::<LittleEndian>()?;
@@ -218,7 +229,7 @@ impl Metadata {
 
     fn read_type_definitions(data: &[u8], header: &Il2CppGlobalMetadataHeader, version: u32) -> Result<Vec<Il2CppTypeDefinition>> {
         let type_size = Self::type_def_size(version);
-        let count = header.type_definitions_size as usize / type_size;
+        let count = (header.type_definitions_size as usize).min(data.len()) / type_size;
         let mut result = Vec::with_capacity(count);
 
         let offset = header.type_definitions_offset as usize;
@@ -268,7 +279,7 @@ impl Metadata {
 
     fn read_method_definitions(data: &[u8], header: &Il2CppGlobalMetadataHeader, version: u32) -> Result<Vec<Il2CppMethodDefinition>> {
         let method_size = if version >= 24 { 24 } else { 20 };
-        let count = header.methods_size as usize / method_size;
+        let count = (header.methods_size as usize).min(data.len()) / method_size;
         let mut result = Vec::with_capacity(count);
 
         let offset = header.methods_offset as usize;
@@ -305,7 +316,7 @@ impl Metadata {
 
     fn read_field_definitions(data: &[u8], header: &Il2CppGlobalMetadataHeader) -> Result<Vec<Il2CppFieldDefinition>> {
         let field_size = 12;
-        let count = header.fields_size as usize / field_size;
+        let count = (header.fields_size as usize).min(data.len()) / field_size;
         let mut result = Vec::with_capacity(count);
 
         let offset = header.fields_offset as usize;
@@ -331,7 +342,7 @@ impl Metadata {
 
     fn read_parameter_definitions(data: &[u8], header: &Il2CppGlobalMetadataHeader) -> Result<Vec<Il2CppParameterDefinition>> {
         let param_size = 12;
-        let count = header.parameters_size as usize / param_size;
+        let count = (header.parameters_size as usize).min(data.len()) / param_size;
         let mut result = Vec::with_capacity(count);
 
         let offset = header.parameters_offset as usize;
@@ -357,7 +368,7 @@ impl Metadata {
 
     fn read_property_definitions(data: &[u8], header: &Il2CppGlobalMetadataHeader) -> Result<Vec<Il2CppPropertyDefinition>> {
         let prop_size = 20;
-        let count = header.properties_size as usize / prop_size;
+        let count = (header.properties_size as usize).min(data.len()) / prop_size;
         let mut result = Vec::with_capacity(count);
 
         let offset = header.properties_offset as usize;
@@ -385,7 +396,7 @@ impl Metadata {
 
     fn read_event_definitions(data: &[u8], header: &Il2CppGlobalMetadataHeader) -> Result<Vec<Il2CppEventDefinition>> {
         let event_size = 24;
-        let count = header.events_size as usize / event_size;
+        let count = (header.events_size as usize).min(data.len()) / event_size;
         let mut result = Vec::with_capacity(count);
 
         let offset = header.events_offset as usize;
@@ -414,7 +425,7 @@ impl Metadata {
 
     fn read_image_definitions(data: &[u8], header: &Il2CppGlobalMetadataHeader, version: u32) -> Result<Vec<Il2CppImageDefinition>> {
         let image_size = if version >= 24 { 40 } else { 24 };
-        let count = header.images_size as usize / image_size;
+        let count = (header.images_size as usize).min(data.len()) / image_size;
         let mut result = Vec::with_capacity(count);
 
         let offset = header.images_offset as usize;
@@ -450,7 +461,7 @@ impl Metadata {
 
     fn read_assembly_definitions(data: &[u8], header: &Il2CppGlobalMetadataHeader, version: u32) -> Result<Vec<Il2CppAssemblyDefinition>> {
         let asm_size = if version >= 24 { 68 } else { 64 };
-        let count = header.assemblies_size as usize / asm_size;
+        let count = (header.assemblies_size as usize).min(data.len()) / asm_size;
         let mut result = Vec::with_capacity(count);
 
         let offset = header.assemblies_offset as usize;
@@ -493,7 +504,7 @@ impl Metadata {
 
     fn read_generic_containers(data: &[u8], header: &Il2CppGlobalMetadataHeader) -> Result<Vec<Il2CppGenericContainer>> {
         let container_size = 16;
-        let count = header.generic_containers_size as usize / container_size;
+        let count = (header.generic_containers_size as usize).min(data.len()) / container_size;
         let mut result = Vec::with_capacity(count);
 
         let offset = header.generic_containers_offset as usize;
@@ -520,7 +531,7 @@ impl Metadata {
 
     fn read_generic_parameters(data: &[u8], header: &Il2CppGlobalMetadataHeader) -> Result<Vec<Il2CppGenericParameter>> {
         let param_size = 16;
-        let count = header.generic_parameters_size as usize / param_size;
+        let count = (header.generic_parameters_size as usize).min(data.len()) / param_size;
         let mut result = Vec::with_capacity(count);
 
         let offset = header.generic_parameters_offset as usize;
@@ -549,7 +560,7 @@ impl Metadata {
 
     fn read_string_literals(data: &[u8], header: &Il2CppGlobalMetadataHeader) -> Result<Vec<Il2CppStringLiteral>> {
         let literal_size = 8;
-        let count = header.string_literal_size as usize / literal_size;
+        let count = (header.string_literal_size as usize).min(data.len()) / literal_size;
         let mut result = Vec::with_capacity(count);
 
         let offset = header.string_literal_offset as usize;
@@ -573,7 +584,7 @@ impl Metadata {
     }
 
     fn read_interfaces(data: &[u8], header: &Il2CppGlobalMetadataHeader) -> Result<Vec<i32>> {
-        let count = header.interfaces_size as usize / 4;
+        let count = (header.interfaces_size as usize).min(data.len()) / 4;
         let mut result = Vec::with_capacity(count);
 
         let offset = header.interfaces_offset as usize;
@@ -591,8 +602,33 @@ impl Metadata {
         Ok(result)
     }
 
+    fn read_metadata_usage_pairs(data: &[u8], header: &Il2CppGlobalMetadataHeader) -> Result<Vec<Il2CppMetadataUsagePair>> {
+        let pair_size = 8;
+        let count = (header.metadata_usage_pairs_size as usize).min(data.len()) / pair_size;
+        let mut result = Vec::with_capacity(count);
+
+        let offset = header.metadata_usage_pairs_offset as usize;
+
+        for i in 0..count {
+            let pos = offset + i * pair_size;
+            if pos + pair_size > data.len() {
+                break;
+            }
+
+            let mut cursor = Cursor::new(&data[pos..]);
+            let mut pair = Il2CppMetadataUsagePair::default();
+
+            pair.destination_index = cursor.read_u32::<LittleEndian>()?;
+            pair.encoded_source_index = cursor.read_u32::<LittleEndian>()?;
+
+            result.push(pair);
+        }
+
+        Ok(result)
+    }
+
     fn read_nested_types(data: &[u8], header: &Il2CppGlobalMetadataHeader) -> Result<Vec<i32>> {
-        let count = header.nested_types_size as usize / 4;
+        let count = (header.nested_types_size as usize).min(data.len()) / 4;
         let mut result = Vec::with_capacity(count);
 
         let offset = header.nested_types_offset as usize;
@@ -645,3 +681,29 @@ impl Metadata {
         String::from_utf16(&utf16).ok()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Seed corpus of the `metadata_parse` fuzz target
+    const SEEDS: [&[u8]; 2] = [
+        include_bytes!("../fuzz/corpus/metadata_parse/v24"),
+        include_bytes!("../fuzz/corpus/metadata_parse/v29"),
+    ];
+
+    #[test]
+    fn test_seed_corpus() {
+        for seed in SEEDS {
+            let metadata = Metadata::parse(seed).unwrap();
+            assert_eq!(metadata.type_definitions.len(), 1);
+            assert_eq!(metadata.get_string(metadata.method_definitions[0].name_index), Some("Update"));
+            assert_eq!(metadata.get_string_literal(0).as_deref(), Some("hello"));
+
+            // Oversized table sizes are clamped to the data instead of allocated
+            let mut data = seed.to_vec();
+            data[8 + 19 * 8 + 4..8 + 19 * 8 + 8].copy_from_slice(&u32::MAX.to_le_bytes());
+            assert_eq!(Metadata::parse(&data).unwrap().type_definitions.len(), 1);
+        }
+    }
+}
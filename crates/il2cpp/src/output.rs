@@ -1,9 +1,17 @@
 //! Output generators for IL2CPP dump results
 
-use endfield_core::{DumpResults, DumpedMethod, DumpedType, Result, StringLiteral};
+use base64::Engine as _;
+use endfield_binary_parser::BinaryFile;
+use endfield_core::{Address, DumpResults, DumpedField, DumpedMethod, DumpedType, Result};
 use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::Write as _;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Version of the generators, stamped into every emitted script
+pub const GENERATOR_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 /// JSON script output format (compatible with IDA/Ghidra scripts)
 #[derive(Debug, Serialize)]
@@ -112,100 +120,321 @@ impl ScriptJson {
     }
 }
 
+/// Pointer size assumed by [`HeaderGenerator::generate`]
+pub const DEFAULT_POINTER_SIZE: usize = 8;
+
+/// Fixed part of the header: runtime object header and the `Il2CppClass`
+/// fields before and after `static_fields` and `rgctx_data` (layout of
+/// metadata v24+)
+const HEADER_PRELUDE: &str = "\
+typedef struct Il2CppObject {
+    struct Il2CppClass* klass;
+    void* monitor;
+} Il2CppObject;
+
+typedef struct Il2CppClass_1 {
+    void* image;
+    void* gc_desc;
+    const char* name;
+    const char* namespaze;
+    void* byval_arg[2];
+    void* this_arg[2];
+    void* element_class;
+    void* castClass;
+    void* declaringType;
+    void* parent;
+    void* generic_class;
+    void* typeMetadataHandle;
+    void* interopData;
+    void* klass;
+    void* fields;
+    void* events;
+    void* properties;
+    void* methods;
+    void* nestedTypes;
+    void* implementedInterfaces;
+    void* interfaceOffsets;
+} Il2CppClass_1;
+
+typedef struct Il2CppClass_2 {
+    struct Il2CppClass** typeHierarchy;
+    void* unity_user_data;
+    uint32_t initializationExceptionGCHandle;
+    uint32_t cctor_started;
+    uint32_t cctor_finished;
+    uint64_t cctor_thread;
+    void* genericContainerHandle;
+    uint32_t instance_size;
+    uint32_t actualSize;
+    uint32_t element_size;
+    int32_t native_size;
+    uint32_t static_fields_size;
+    uint32_t thread_static_fields_size;
+    int32_t thread_static_fields_offset;
+    uint32_t flags;
+    uint32_t token;
+    uint16_t method_count;
+    uint16_t property_count;
+    uint16_t field_count;
+    uint16_t event_count;
+    uint16_t nested_type_count;
+    uint16_t vtable_count;
+    uint16_t interfaces_count;
+    uint16_t interface_offsets_count;
+    uint8_t typeHierarchyDepth;
+    uint8_t genericRecursionDepth;
+    uint8_t rank;
+    uint8_t minimumAlignment;
+    uint8_t naturalAligment;
+    uint8_t packingSize;
+    uint8_t bitflags1;
+    uint8_t bitflags2;
+} Il2CppClass_2;
+
+";
+
 /// C/C++ header output
+///
+/// Every class `X` (mangled from its full name) produces il2cpp-style structs:
+/// `X_Fields` with inherited instance fields flattened in and explicit padding to
+/// the real field offsets, `X_StaticFields`, the class object `X_c` and the
+/// instance `X_o`. The result compiles as C11.
+///
+/// `X_c` ends after `Il2CppClass_2`: the dump records no vtable slots, so the
+/// `VirtualInvokeData` entries that follow it in memory are left out.
 pub struct HeaderGenerator;
 
 impl HeaderGenerator {
-    /// Generate C header from dump results
+    /// Generate C header from dump results, laid out for 64-bit targets
     pub fn generate(results: &DumpResults) -> String {
+        Self::generate_for_pointer_size(results, DEFAULT_POINTER_SIZE)
+    }
+
+    /// Generate C header from dump results for the given pointer size (4 or 8)
+    pub fn generate_for_pointer_size(results: &DumpResults, pointer_size: usize) -> String {
         let mut output = String::new();
 
         output.push_str("// Auto-generated IL2CPP header\n");
         output.push_str("// Do not edit manually\n\n");
         output.push_str("#pragma once\n\n");
+        output.push_str("#include <stdbool.h>\n");
         output.push_str("#include <stdint.h>\n\n");
+        output.push_str(HEADER_PRELUDE);
+
+        let names = Self::mangle_type_names(results);
+        let types = CTypes::new(results, &names, pointer_size);
 
         // Forward declarations
         output.push_str("// Forward declarations\n");
-        for type_def in &results.types {
-            if !type_def.is_interface {
-                output.push_str(&format!("struct {};\n", Self::sanitize_name(&type_def.name)));
+        for (type_def, name) in results.types.iter().zip(&names) {
+            if !type_def.is_interface && !type_def.is_enum {
+                let _ = writeln!(output, "struct {}_c;", name);
+                let _ = writeln!(output, "struct {}_StaticFields;", name);
+            }
+        }
+        output.push('\n');
+
+        // Value types are embedded by value, so their fields are complete
+        // types ahead of every use
+        let value_types = types.value_types_in_dependency_order();
+        if !value_types.is_empty() {
+            output.push_str("// Value type fields\n");
+            for index in value_types {
+                let _ = writeln!(output, "struct {}_Fields {{", names[index]);
+                let fields = types.instance_fields(&results.types[index]);
+                Self::write_layout(&mut output, &fields, 2 * pointer_size as u32, &types);
+                output.push_str("};\n");
             }
+            output.push('\n');
         }
-        output.push_str("\n");
 
         // Type definitions
-        for type_def in &results.types {
-            output.push_str(&Self::generate_type(type_def));
-            output.push_str("\n");
+        let mut enum_members = NameScope::default();
+        for (type_def, name) in results.types.iter().zip(&names) {
+            let _ = writeln!(output, "// {}", type_def.full_name);
+            if type_def.is_enum {
+                Self::generate_enum(&mut output, type_def, name, &mut enum_members);
+            } else if type_def.is_interface {
+                let _ = writeln!(output, "// Interface: {}", type_def.full_name);
+            } else {
+                Self::generate_class(&mut output, type_def, name, &types);
+            }
+            output.push('\n');
         }
 
         output
     }
 
-    fn generate_type(type_def: &DumpedType) -> String {
-        let mut output = String::new();
-
-        // Comment with full name
-        output.push_str(&format!("// {}\n", type_def.full_name));
+    fn generate_enum(
+        output: &mut String,
+        type_def: &DumpedType,
+        name: &str,
+        members: &mut NameScope,
+    ) {
+        let constants: Vec<&DumpedField> = type_def.fields.iter().filter(|f| f.is_static).collect();
+        if constants.is_empty() {
+            return;
+        }
 
-        if type_def.is_enum {
-            output.push_str(&format!("enum {} {{\n", Self::sanitize_name(&type_def.name)));
-            for field in &type_def.fields {
-                if let Some(ref value) = field.default_value {
-                    output.push_str(&format!("    {} = {},\n", Self::sanitize_name(&field.name), value));
-                } else {
-                    output.push_str(&format!("    {},\n", Self::sanitize_name(&field.name)));
+        let _ = writeln!(output, "enum {} {{", name);
+        for field in constants {
+            let member = members.claim(&format!("{}_{}", name, Self::sanitize_name(&field.name)));
+            match field.default_value.as_deref().filter(|v| Self::is_c_integer(v)) {
+                Some(value) => {
+                    let _ = writeln!(output, "    {} = {},", member, value);
                 }
-            }
-            output.push_str("};\n");
-        } else if type_def.is_interface {
-            output.push_str(&format!("// Interface: {}\n", type_def.name));
-        } else {
-            output.push_str(&format!("struct {} {{\n", Self::sanitize_name(&type_def.name)));
-
-            // IL2CPP object header
-            output.push_str("    void* klass;  // Il2CppClass*\n");
-            output.push_str("    void* monitor;  // MonitorData*\n");
-
-            // Fields
-            for field in &type_def.fields {
-                if !field.is_static {
-                    output.push_str(&format!(
-                        "    {} {};  // Offset: 0x{:X}\n",
-                        Self::type_to_c(&field.type_name),
-                        Self::sanitize_name(&field.name),
-                        field.offset
-                    ));
+                None => {
+                    let _ = writeln!(output, "    {},", member);
                 }
             }
+        }
+        output.push_str("};\n");
+    }
+
+    fn generate_class(output: &mut String, type_def: &DumpedType, name: &str, types: &CTypes) {
+        let header_size = 2 * types.pointer_size as u32;
+
+        // Static fields
+        let static_fields: Vec<&DumpedField> = type_def
+            .fields
+            .iter()
+            .filter(|f| f.is_static && !f.is_const)
+            .collect();
+        if !static_fields.is_empty() {
+            let _ = writeln!(output, "struct {}_StaticFields {{", name);
+            Self::write_layout(output, &static_fields, 0, types);
+            output.push_str("};\n");
+        }
 
+        // Instance fields, base class first; value types had theirs emitted up front
+        let instance_fields = types.instance_fields(type_def);
+        let has_fields = !instance_fields.is_empty();
+        let is_value_type = is_value_type(type_def);
+        if has_fields && !is_value_type {
+            let _ = writeln!(output, "struct {}_Fields {{", name);
+            Self::write_layout(output, &instance_fields, header_size, types);
             output.push_str("};\n");
+        }
 
-            // Static fields
-            let static_fields: Vec<_> = type_def.fields.iter().filter(|f| f.is_static).collect();
-            if !static_fields.is_empty() {
-                output.push_str(&format!("\n// Static fields for {}\n", type_def.name));
-                for field in static_fields {
-                    output.push_str(&format!(
-                        "// static {} {};\n",
-                        Self::type_to_c(&field.type_name),
-                        Self::sanitize_name(&field.name)
-                    ));
+        // Class object
+        let _ = writeln!(output, "struct {}_c {{", name);
+        output.push_str("    Il2CppClass_1 _1;\n");
+        let _ = writeln!(output, "    struct {}_StaticFields* static_fields;", name);
+        output.push_str("    void* rgctx_data;\n");
+        output.push_str("    Il2CppClass_2 _2;\n");
+        output.push_str("};\n");
+
+        // Instance; unboxed value types carry no object header
+        if is_value_type {
+            if has_fields {
+                let _ = writeln!(output, "struct {}_o {{", name);
+                let _ = writeln!(output, "    struct {}_Fields fields;", name);
+                output.push_str("};\n");
+            }
+            return;
+        }
+        let _ = writeln!(output, "struct {}_o {{", name);
+        let _ = writeln!(output, "    struct {}_c* klass;", name);
+        output.push_str("    void* monitor;\n");
+        if has_fields {
+            let _ = writeln!(output, "    struct {}_Fields fields;", name);
+        }
+        output.push_str("};\n");
+    }
+
+    /// Emit struct members at their recorded offsets, relative to `base`
+    ///
+    /// When no field carries an offset (all zero) the fields are laid out
+    /// sequentially and the compiler's natural alignment applies. Fields that
+    /// overlap an earlier one (explicit layout unions) are kept as comments.
+    fn write_layout(output: &mut String, fields: &[&DumpedField], base: u32, types: &CTypes) {
+        let layout = types.place(fields, base, 0);
+        let mut members = NameScope::default();
+        let mut pad_index = 0;
+
+        for placed in &layout.members {
+            let c_type = &placed.c_type.name;
+            let member = members.claim(&Self::sanitize_name(&placed.field.name));
+            match placed.padding {
+                _ if layout.sequential => {
+                    let _ = writeln!(output, "    {} {};", c_type, member);
+                }
+                None => {
+                    let _ = writeln!(
+                        output,
+                        "    // {} {};  // Offset: 0x{:X} (overlaps)",
+                        c_type, member, placed.field.offset
+                    );
+                }
+                Some(padding) => {
+                    if padding > 0 {
+                        let _ = writeln!(output, "    uint8_t _pad{}[0x{:X}];", pad_index, padding);
+                        pad_index += 1;
+                    }
+                    let _ = writeln!(output, "    {} {};  // Offset: 0x{:X}", c_type, member, placed.field.offset);
                 }
             }
         }
+    }
 
-        output
+    /// Unique C identifiers for every type, mangled from the full name
+    fn mangle_type_names(results: &DumpResults) -> Vec<String> {
+        let mut scope = NameScope::default();
+        for reserved in ["Il2CppObject", "Il2CppClass", "Il2CppClass_1", "Il2CppClass_2"] {
+            scope.claim(reserved);
+        }
+        results
+            .types
+            .iter()
+            .map(|t| scope.claim(&Self::sanitize_name(&t.full_name)))
+            .collect()
     }
 
-    fn sanitize_name(name: &str) -> String {
-        name.chars()
-            .map(|c| if c.is_alphanumeric() || c == '_' { c } else { '_' })
+    fn c_type_size(c_type: &str, pointer_size: usize) -> u64 {
+        match c_type {
+            "bool" | "uint8_t" | "int8_t" => 1,
+            "int16_t" | "uint16_t" => 2,
+            "int32_t" | "uint32_t" | "float" => 4,
+            "int64_t" | "uint64_t" | "double" => 8,
+            _ => pointer_size as u64,
+        }
+    }
+
+    fn is_c_integer(value: &str) -> bool {
+        let digits = value.strip_prefix('-').unwrap_or(value);
+        !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+    }
+
+    /// C identifier used for a method's function symbol (`Namespace_Class__Method`)
+    pub fn function_name(method: &DumpedMethod) -> String {
+        Self::sanitize_name(&method.full_name.replace("$$", "__"))
+    }
+
+    /// C prototype of every method, keyed by method id, in the calling
+    /// convention IL2CPP emits and spelled with the types [`Self::generate`]
+    /// declares
+    pub fn function_signatures(results: &DumpResults) -> HashMap<Uuid, String> {
+        let names = Self::mangle_type_names(results);
+        let types = CTypes::new(results, &names, DEFAULT_POINTER_SIZE);
+        results
+            .methods
+            .iter()
+            .map(|method| (method.id, types.signature(method)))
             .collect()
     }
 
-    fn type_to_c(type_name: &str) -> &str {
+    fn sanitize_name(name: &str) -> String {
+        let mut sanitized: String = name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+            .collect();
+        if sanitized.is_empty() || sanitized.starts_with(|c: char| c.is_ascii_digit()) {
+            sanitized.insert(0, '_');
+        }
+        sanitized
+    }
+
+    pub(crate) fn type_to_c(type_name: &str) -> &str {
         match type_name {
             "System.Void" | "void" => "void",
             "System.Boolean" | "bool" => "bool",
@@ -234,130 +463,410 @@ impl HeaderGenerator {
     }
 }
 
-/// C# dummy assembly generator
-pub struct DummyAssemblyGenerator;
+/// Deepest value-type nesting [`CTypes`] resolves before falling back to a pointer
+const MAX_VALUE_TYPE_NESTING: usize = 16;
 
-impl DummyAssemblyGenerator {
-    /// Generate C# source code from dump results
-    pub fn generate(results: &DumpResults) -> String {
-        let mut output = String::new();
+/// Check whether instances of a type are stored unboxed
+fn is_value_type(type_def: &DumpedType) -> bool {
+    type_def.is_enum || matches!(type_def.parent_type.as_deref(), Some("System.ValueType") | Some("System.Enum"))
+}
 
-        output.push_str("// Auto-generated IL2CPP dummy assembly\n");
-        output.push_str("// Do not edit manually\n\n");
+/// C spelling, size and alignment of a field type
+struct CType {
+    name: String,
+    size: u64,
+    align: u64,
+}
 
-        // Group types by namespace
-        let mut namespaces: std::collections::HashMap<String, Vec<&DumpedType>> =
-            std::collections::HashMap::new();
+/// Field placed by [`CTypes::place`]
+struct PlacedField<'f> {
+    field: &'f DumpedField,
+    c_type: CType,
+    /// Padding before the field, `None` if it overlaps an earlier one
+    padding: Option<u64>,
+}
 
-        for type_def in &results.types {
-            namespaces
-                .entry(type_def.namespace.clone())
-                .or_default()
-                .push(type_def);
-        }
+/// Struct members placed by [`CTypes::place`]
+struct StructLayout<'f> {
+    members: Vec<PlacedField<'f>>,
+    /// No field carries an offset, so the compiler lays the members out
+    sequential: bool,
+    size: u64,
+    align: u64,
+}
 
-        // Generate by namespace
-        for (namespace, types) in &namespaces {
-            if !namespace.is_empty() {
-                output.push_str(&format!("namespace {} {{\n\n", namespace));
-            }
+/// Field types of the header resolved against the dumped types
+///
+/// Instance field offsets of value types are the boxed ones reported by the
+/// runtime, so they start after the object header like those of classes.
+struct CTypes<'a> {
+    types: &'a [DumpedType],
+    names: &'a [String],
+    by_full_name: HashMap<&'a str, usize>,
+    pointer_size: usize,
+}
 
-            for type_def in types {
-                output.push_str(&Self::generate_type(type_def, results));
-                output.push_str("\n");
-            }
+impl<'a> CTypes<'a> {
+    fn new(results: &'a DumpResults, names: &'a [String], pointer_size: usize) -> Self {
+        let mut by_full_name = HashMap::with_capacity(results.types.len());
+        for (index, type_def) in results.types.iter().enumerate() {
+            by_full_name.entry(type_def.full_name.as_str()).or_insert(index);
+        }
+        Self { types: &results.types, names, by_full_name, pointer_size }
+    }
+
+    fn get(&self, full_name: &str) -> Option<usize> {
+        self.by_full_name.get(full_name).copied()
+    }
 
-            if !namespace.is_empty() {
-                output.push_str("}\n\n");
+    /// Instance fields of a type and all of its parents, outermost base class first
+    fn instance_fields(&self, type_def: &'a DumpedType) -> Vec<&'a DumpedField> {
+        let mut chain = vec![type_def];
+        let mut current = type_def;
+        while let Some(parent) = current
+            .parent_type
+            .as_deref()
+            .and_then(|p| self.get(p))
+            .map(|index| &self.types[index])
+        {
+            // Guard against malformed metadata with inheritance cycles
+            if chain.len() > 64 || chain.iter().any(|t| std::ptr::eq(*t, parent)) {
+                break;
             }
+            chain.push(parent);
+            current = parent;
         }
 
-        output
+        chain
+            .iter()
+            .rev()
+            .flat_map(|t| t.fields.iter())
+            .filter(|f| !f.is_static && !f.is_const)
+            .collect()
     }
 
-    fn generate_type(type_def: &DumpedType, results: &DumpResults) -> String {
-        let mut output = String::new();
+    /// Indices of the value types with instance fields, each after the value
+    /// types it embeds
+    fn value_types_in_dependency_order(&self) -> Vec<usize> {
+        let mut visited = vec![false; self.types.len()];
+        let mut order = Vec::new();
+        for index in 0..self.types.len() {
+            self.visit(index, &mut visited, &mut order);
+        }
+        order
+    }
 
-        // Attributes
-        output.push_str(&format!("    // Token: 0x{:08X}\n", type_def.token));
+    fn visit(&self, index: usize, visited: &mut [bool], order: &mut Vec<usize>) {
+        let type_def = &self.types[index];
+        if visited[index] || type_def.is_enum || !is_value_type(type_def) {
+            return;
+        }
+        // Marked before recursing, so malformed self-embedding terminates
+        visited[index] = true;
+        let fields = self.instance_fields(type_def);
+        if fields.is_empty() {
+            return;
+        }
+        for field in &fields {
+            if let Some(embedded) = self.get(&field.type_name) {
+                self.visit(embedded, visited, order);
+            }
+        }
+        order.push(index);
+    }
 
-        // Type declaration
-        let modifiers = Self::get_type_modifiers(type_def);
-        let kind = if type_def.is_enum {
-            "enum"
-        } else if type_def.is_interface {
-            "interface"
-        } else {
-            "class"
+    /// C type of a field declared as `type_name`
+    ///
+    /// Enums use their underlying integer type and value types are embedded
+    /// by value; everything else is a primitive or a pointer.
+    fn resolve(&self, type_name: &str, depth: usize) -> CType {
+        let primitive = HeaderGenerator::type_to_c(type_name);
+        let index = self
+            .get(type_name)
+            .filter(|_| primitive == "void*" && depth < MAX_VALUE_TYPE_NESTING);
+        let Some(index) = index else {
+            return self.primitive(primitive);
         };
 
-        output.push_str(&format!("    {} {} {}", modifiers, kind, type_def.name));
+        let type_def = &self.types[index];
+        if type_def.is_enum {
+            let underlying = type_def
+                .fields
+                .iter()
+                .find(|f| !f.is_static && f.name == "value__")
+                .map_or("int32_t", |f| HeaderGenerator::type_to_c(&f.type_name));
+            return self.primitive(underlying);
+        }
+        if !is_value_type(type_def) {
+            return self.primitive(primitive);
+        }
 
-        // Inheritance
-        let mut inheritance = Vec::new();
-        if let Some(ref parent) = type_def.parent_type {
-            if parent != "System.Object" && parent != "System.ValueType" && parent != "System.Enum" {
-                inheritance.push(parent.clone());
+        // A value type without fields still occupies a byte
+        let fields = self.instance_fields(type_def);
+        if fields.is_empty() {
+            return self.primitive("uint8_t");
+        }
+        let layout = self.place(&fields, 2 * self.pointer_size as u32, depth + 1);
+        CType {
+            name: format!("struct {}_Fields", self.names[index]),
+            size: layout.size,
+            align: layout.align,
+        }
+    }
+
+    /// C type of a parameter or return value declared as `type_name`
+    ///
+    /// Classes are passed as pointers to their `X_o` instance, value types as
+    /// `X_o` by value and enums as the `enum` the header declares for them.
+    /// Types the header has no struct for stay `void*`.
+    fn parameter(&self, type_name: &str) -> String {
+        let primitive = HeaderGenerator::type_to_c(type_name);
+        let Some(index) = self.get(type_name).filter(|_| primitive == "void*") else {
+            return primitive.to_string();
+        };
+
+        let type_def = &self.types[index];
+        let name = &self.names[index];
+        if type_def.is_enum {
+            if type_def.fields.iter().any(|f| f.is_static) {
+                return format!("enum {}", name);
             }
+            return self.resolve(type_name, 0).name;
         }
-        inheritance.extend(type_def.interfaces.clone());
+        if type_def.is_interface {
+            return primitive.to_string();
+        }
+        if is_value_type(type_def) {
+            if self.instance_fields(type_def).is_empty() {
+                return "uint8_t".to_string();
+            }
+            return format!("struct {}_o", name);
+        }
+        format!("struct {}_o*", name)
+    }
 
-        if !inheritance.is_empty() {
-            output.push_str(&format!(" : {}", inheritance.join(", ")));
+    /// C type of `__this` for instance methods of `full_name`, a pointer to
+    /// its `X_o` where the header declares one
+    fn this_parameter(&self, full_name: &str) -> String {
+        match self.get(full_name) {
+            Some(index) if self.has_instance_struct(&self.types[index]) => {
+                format!("struct {}_o*", self.names[index])
+            }
+            _ => "void*".to_string(),
         }
+    }
 
-        output.push_str(" {\n");
+    /// Whether the header declares `X_o` for a type
+    fn has_instance_struct(&self, type_def: &'a DumpedType) -> bool {
+        !type_def.is_interface
+            && !type_def.is_enum
+            && (!is_value_type(type_def) || !self.instance_fields(type_def).is_empty())
+    }
 
-        // Fields
-        for field in &type_def.fields {
-            output.push_str(&format!(
-                "        {} {} {};\n",
-                if field.is_static { "static" } else { "public" },
-                field.type_name,
-                field.name
+    /// C prototype for a method
+    fn signature(&self, method: &DumpedMethod) -> String {
+        let mut names = NameScope::default();
+        let mut params = Vec::with_capacity(method.parameters.len() + 2);
+        if !method.is_static {
+            let declaring_type = method.full_name.rsplit_once("$$").map_or("", |(t, _)| t);
+            params.push(format!("{} {}", self.this_parameter(declaring_type), names.claim("__this")));
+        }
+        for param in &method.parameters {
+            params.push(format!(
+                "{} {}",
+                self.parameter(&param.type_name),
+                names.claim(&HeaderGenerator::sanitize_name(&param.name))
             ));
         }
+        params.push(format!("const void* {}", names.claim("method")));
 
-        if !type_def.fields.is_empty() {
-            output.push_str("\n");
-        }
+        format!(
+            "{} {}({});",
+            self.parameter(&method.return_type),
+            HeaderGenerator::function_name(method),
+            params.join(", ")
+        )
+    }
 
-        // Methods
-        for method_id in &type_def.methods {
-            if let Some(method) = results.methods.iter().find(|m| &m.id == method_id) {
-                output.push_str(&Self::generate_method(method));
-            }
+    fn primitive(&self, c_type: &str) -> CType {
+        let size = HeaderGenerator::c_type_size(c_type, self.pointer_size);
+        CType { name: c_type.to_string(), size, align: size }
+    }
+
+    /// Place fields at their offsets relative to `base`, or sequentially
+    /// when none carries one, and size the resulting struct as C would
+    fn place<'f>(&self, fields: &[&'f DumpedField], base: u32, depth: usize) -> StructLayout<'f> {
+        let sequential = fields.iter().all(|f| f.offset == 0);
+        let mut members = Vec::with_capacity(fields.len());
+        let mut cursor = 0u64;
+        let mut align = 1u64;
+
+        for field in fields {
+            let c_type = self.resolve(&field.type_name, depth);
+            align = align.max(c_type.align);
+            let padding = if sequential {
+                cursor = cursor.next_multiple_of(c_type.align) + c_type.size;
+                Some(0)
+            } else {
+                match u64::from(field.offset).checked_sub(u64::from(base)) {
+                    Some(offset) if offset >= cursor => {
+                        let padding = offset - cursor;
+                        cursor = offset + c_type.size;
+                        Some(padding)
+                    }
+                    _ => None,
+                }
+            };
+            members.push(PlacedField { field, c_type, padding });
         }
 
-        output.push_str("    }\n");
+        StructLayout { members, sequential, size: cursor.next_multiple_of(align), align }
+    }
+}
 
-        output
+/// How [`DummyAssemblyGenerator::write_tree`] splits types into source files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsFileLayout {
+    /// `<Assembly>/<Namespace>/<Type>.cs`
+    PerType,
+    /// `<Assembly>/<Namespace>.cs`
+    PerNamespace,
+}
+
+/// Folder and file name used for types in the global namespace
+const GLOBAL_NAMESPACE_NAME: &str = "_Global";
+
+/// Attribute definitions referenced by the generated sources (as in Il2CppDummyDll)
+const DUMMY_ATTRIBUTES: &str = "\
+// Auto-generated IL2CPP dummy assembly
+// Do not edit manually
+
+using System;
+
+namespace Il2CppDummyDll
+{
+    [AttributeUsage(AttributeTargets.Method | AttributeTargets.Constructor)]
+    public class AddressAttribute : Attribute
+    {
+        public string RVA;
+        public string Offset;
+        public string VA;
+        public string Slot;
     }
 
-    fn generate_method(method: &DumpedMethod) -> String {
-        let mut output = String::new();
+    [AttributeUsage(AttributeTargets.Field)]
+    public class FieldOffsetAttribute : Attribute
+    {
+        public string Offset;
+    }
+
+    [AttributeUsage(AttributeTargets.All)]
+    public class TokenAttribute : Attribute
+    {
+        public string Token;
+    }
+}
+";
 
-        output.push_str(&format!("        // RVA: 0x{:X}\n", method.address.as_u64()));
-        output.push_str(&format!("        // Token: 0x{:08X}\n", method.token));
+/// C# dummy assembly generator
+///
+/// Output follows Il2CppDumper's conventions: methods carry
+/// `[Address(RVA = ..., Offset = ..., VA = ...)]`, fields of types whose offsets
+/// the dump resolved `[FieldOffset(Offset = ...)]` and every member its metadata
+/// `[Token]`. Types are emitted sorted by assembly, namespace and name so that
+/// output is stable across runs.
+pub struct DummyAssemblyGenerator;
 
-        let modifiers = Self::get_method_modifiers(method);
-        let params = method
-            .parameters
-            .iter()
-            .map(|p| format!("{} {}", p.type_name, p.name))
-            .collect::<Vec<_>>()
-            .join(", ");
+impl DummyAssemblyGenerator {
+    /// Generate C# source code from dump results as a single file
+    pub fn generate(results: &DumpResults) -> String {
+        let writer = CsWriter::new(results, None);
+        let mut body = String::new();
+        let mut imports = BTreeSet::new();
 
-        output.push_str(&format!(
-            "        {} {} {}({}) {{ }}\n\n",
-            modifiers, method.return_type, method.name, params
-        ));
+        for group in writer.groups() {
+            let _ = writeln!(body, "// Assembly: {}", group[0].assembly);
+            body.push_str(&writer.render_namespace(&group, &mut imports));
+            body.push('\n');
+        }
 
+        let mut output = String::new();
+        output.push_str("// Auto-generated IL2CPP dummy assembly\n");
+        output.push_str("// Do not edit manually\n\n");
+        CsWriter::write_usings(&mut output, &imports, "");
+        output.push_str(&body);
         output
     }
 
+    /// Write one `.cs` file per type or per namespace under `<dir>/<Assembly>/`
+    ///
+    /// When `binary` is given, method attributes include the file offset and
+    /// VA in addition to the RVA. Returns the paths written, in order.
+    pub fn write_tree(
+        results: &DumpResults,
+        binary: Option<&dyn BinaryFile>,
+        dir: &Path,
+        layout: CsFileLayout,
+    ) -> Result<Vec<PathBuf>> {
+        let writer = CsWriter::new(results, binary);
+        let mut taken = HashSet::new();
+        let mut written = Vec::new();
+
+        let attributes_path = dir.join("Il2CppDummyDll.cs");
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(&attributes_path, DUMMY_ATTRIBUTES)?;
+        taken.insert(attributes_path.to_string_lossy().to_lowercase());
+        written.push(attributes_path);
+
+        for group in writer.groups() {
+            let assembly_dir = dir.join(path_component(
+                group[0].assembly.trim_end_matches(".dll"),
+            ));
+            let namespace = if group[0].namespace.is_empty() {
+                GLOBAL_NAMESPACE_NAME
+            } else {
+                &group[0].namespace
+            };
+
+            let files: Vec<(PathBuf, Vec<&DumpedType>)> = match layout {
+                CsFileLayout::PerNamespace => {
+                    vec![(assembly_dir.join(path_component(namespace)), group.clone())]
+                }
+                CsFileLayout::PerType => group
+                    .iter()
+                    .map(|t| {
+                        let path = assembly_dir.join(path_component(namespace)).join(path_component(&t.name));
+                        (path, vec![*t])
+                    })
+                    .collect(),
+            };
+
+            for (stem, types) in files {
+                let path = unique_path(&stem, "cs", &mut taken);
+                let mut imports = BTreeSet::new();
+                let body = writer.render_namespace(&types, &mut imports);
+
+                let mut content = String::new();
+                content.push_str("// Auto-generated IL2CPP dummy assembly\n");
+                content.push_str("// Do not edit manually\n");
+                let _ = writeln!(content, "// Assembly: {}\n", types[0].assembly);
+                CsWriter::write_usings(&mut content, &imports, &types[0].namespace);
+                content.push_str(&body);
+
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&path, content)?;
+                written.push(path);
+            }
+        }
+
+        Ok(written)
+    }
+
     fn get_type_modifiers(type_def: &DumpedType) -> &'static str {
-        if type_def.is_interface {
+        // Value types are sealed implicitly and can't say so
+        if type_def.is_interface || is_value_type(type_def) {
             "public"
         } else if type_def.is_abstract && type_def.is_sealed {
             "public static"
@@ -389,3 +898,1548 @@ impl DummyAssemblyGenerator {
         Ok(())
     }
 }
+
+/// Shared state for rendering C# sources
+struct CsWriter<'a> {
+    results: &'a DumpResults,
+    binary: Option<&'a dyn BinaryFile>,
+    methods: HashMap<Uuid, &'a DumpedMethod>,
+    /// Number of distinct types sharing each short name
+    short_names: HashMap<&'a str, usize>,
+}
+
+impl<'a> CsWriter<'a> {
+    fn new(results: &'a DumpResults, binary: Option<&'a dyn BinaryFile>) -> Self {
+        let methods = results.methods.iter().map(|m| (m.id, m)).collect();
+        let mut short_names = HashMap::new();
+        for type_def in &results.types {
+            *short_names.entry(type_def.name.as_str()).or_insert(0) += 1;
+        }
+        Self {
+            results,
+            binary,
+            methods,
+            short_names,
+        }
+    }
+
+    /// Types sorted deterministically and grouped by assembly and namespace
+    fn groups(&self) -> Vec<Vec<&'a DumpedType>> {
+        let mut types: Vec<&DumpedType> = self.results.types.iter().collect();
+        types.sort_by(|a, b| {
+            (&a.assembly, &a.namespace, &a.name, a.token)
+                .cmp(&(&b.assembly, &b.namespace, &b.name, b.token))
+        });
+
+        let mut groups: Vec<Vec<&DumpedType>> = Vec::new();
+        for type_def in types {
+            match groups.last_mut() {
+                Some(group)
+                    if group[0].assembly == type_def.assembly
+                        && group[0].namespace == type_def.namespace =>
+                {
+                    group.push(type_def)
+                }
+                _ => groups.push(vec![type_def]),
+            }
+        }
+        groups
+    }
+
+    fn write_usings(output: &mut String, imports: &BTreeSet<String>, namespace: &str) {
+        output.push_str("using Il2CppDummyDll;\n");
+        for import in imports {
+            if import != namespace && import != "Il2CppDummyDll" {
+                let _ = writeln!(output, "using {};", import);
+            }
+        }
+        output.push('\n');
+    }
+
+    /// Render types that share a namespace, wrapped in a `namespace` block
+    fn render_namespace(&self, types: &[&DumpedType], imports: &mut BTreeSet<String>) -> String {
+        let namespace = &types[0].namespace;
+        let indent = if namespace.is_empty() { "" } else { "    " };
+        let mut output = String::new();
+
+        if !namespace.is_empty() {
+            let _ = writeln!(output, "namespace {}\n{{", namespace);
+        }
+        for (i, type_def) in types.iter().enumerate() {
+            if i > 0 {
+                output.push('\n');
+            }
+            self.render_type(&mut output, type_def, indent, imports);
+        }
+        if !namespace.is_empty() {
+            output.push_str("}\n");
+        }
+
+        output
+    }
+
+    fn render_type(
+        &self,
+        output: &mut String,
+        type_def: &DumpedType,
+        indent: &str,
+        imports: &mut BTreeSet<String>,
+    ) {
+        let kind = if type_def.is_enum {
+            "enum"
+        } else if type_def.is_interface {
+            "interface"
+        } else if is_value_type(type_def) {
+            "struct"
+        } else {
+            "class"
+        };
+
+        let _ = writeln!(output, "{}[Token(Token = \"0x{:08X}\")]", indent, type_def.token);
+        let _ = write!(
+            output,
+            "{}{} {} {}",
+            indent,
+            DummyAssemblyGenerator::get_type_modifiers(type_def),
+            kind,
+            type_def.name
+        );
+
+        // Inheritance
+        let mut inheritance = Vec::new();
+        if let Some(ref parent) = type_def.parent_type {
+            if parent != "System.Object" && parent != "System.ValueType" && parent != "System.Enum" {
+                inheritance.push(self.type_ref(parent, imports));
+            }
+        }
+        inheritance.extend(type_def.interfaces.iter().map(|i| self.type_ref(i, imports)));
+        if !inheritance.is_empty() {
+            let _ = write!(output, " : {}", inheritance.join(", "));
+        }
+        let _ = writeln!(output, "\n{}{{", indent);
+
+        let member_indent = format!("{}    ", indent);
+        if type_def.is_enum {
+            for field in type_def.fields.iter().filter(|f| f.is_static) {
+                match field.default_value {
+                    Some(ref value) => {
+                        let _ = writeln!(output, "{}{} = {},", member_indent, field.name, value);
+                    }
+                    None => {
+                        let _ = writeln!(output, "{}{},", member_indent, field.name);
+                    }
+                }
+            }
+        } else {
+            self.render_fields(output, type_def, &member_indent, imports);
+            self.render_methods(output, type_def, &member_indent, imports);
+        }
+
+        let _ = writeln!(output, "{}}}", indent);
+    }
+
+    fn render_fields(
+        &self,
+        output: &mut String,
+        type_def: &DumpedType,
+        indent: &str,
+        imports: &mut BTreeSet<String>,
+    ) {
+        if type_def.fields.is_empty() {
+            return;
+        }
+
+        // All zero means the dump resolved no offsets for this type
+        let has_offsets = type_def.fields.iter().any(|f| f.offset != 0);
+        let _ = writeln!(output, "{}// Fields", indent);
+        for field in &type_def.fields {
+            let type_name = self.type_ref(&field.type_name, imports);
+            if field.is_const {
+                let value = field.default_value.as_deref().unwrap_or("default");
+                let _ = writeln!(
+                    output,
+                    "{}public const {} {} = {};",
+                    indent, type_name, field.name, value
+                );
+                continue;
+            }
+
+            if has_offsets {
+                let _ = writeln!(output, "{}[FieldOffset(Offset = \"0x{:X}\")]", indent, field.offset);
+            }
+            let _ = writeln!(
+                output,
+                "{}{} {} {};",
+                indent,
+                if field.is_static { "public static" } else { "public" },
+                type_name,
+                field.name
+            );
+        }
+    }
+
+    fn render_methods(
+        &self,
+        output: &mut String,
+        type_def: &DumpedType,
+        indent: &str,
+        imports: &mut BTreeSet<String>,
+    ) {
+        let methods: Vec<&DumpedMethod> = type_def
+            .methods
+            .iter()
+            .filter_map(|id| self.methods.get(id).copied())
+            .collect();
+        if methods.is_empty() {
+            return;
+        }
+
+        if !type_def.fields.is_empty() {
+            output.push('\n');
+        }
+        let _ = writeln!(output, "{}// Methods", indent);
+        for method in methods {
+            let _ = writeln!(output, "{}[Token(Token = \"0x{:08X}\")]", indent, method.token);
+            if let Some(address) = self.address_attribute(method) {
+                let _ = writeln!(output, "{}{}", indent, address);
+            }
+
+            let params = method
+                .parameters
+                .iter()
+                .map(|p| format!("{} {}", self.type_ref(&p.type_name, imports), p.name))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let modifiers = if type_def.is_interface {
+                String::new()
+            } else {
+                format!("{} ", DummyAssemblyGenerator::get_method_modifiers(method))
+            };
+            let body = if type_def.is_interface || method.is_abstract {
+                ";"
+            } else {
+                " { }"
+            };
+
+            let _ = writeln!(
+                output,
+                "{}{}{} {}({}){}",
+                indent,
+                modifiers,
+                self.type_ref(&method.return_type, imports),
+                method.name,
+                params,
+                body
+            );
+        }
+    }
+
+    /// `[Address(...)]` for a method with code, resolving offset and VA through the binary
+    fn address_attribute(&self, method: &DumpedMethod) -> Option<String> {
+        let rva = method.address.as_u64();
+        if rva == 0 {
+            return None;
+        }
+
+        let mut attribute = format!("[Address(RVA = \"0x{:X}\"", rva);
+        if let Some(binary) = self.binary {
+            let va = binary.image_base().as_u64().wrapping_add(rva);
+            if let Some(offset) = binary.va_to_offset(Address::new(va)) {
+                let _ = write!(attribute, ", Offset = \"0x{:X}\"", offset);
+            }
+            let _ = write!(attribute, ", VA = \"0x{:X}\"", va);
+        }
+        attribute.push_str(")]");
+        Some(attribute)
+    }
+
+    /// C# spelling of a type reference, importing its namespace when the short
+    /// name is unambiguous
+    fn type_ref(&self, full_name: &str, imports: &mut BTreeSet<String>) -> String {
+        if let Some(alias) = Self::keyword_alias(full_name) {
+            return alias.to_string();
+        }
+
+        match full_name.rsplit_once('.') {
+            Some((namespace, name)) if self.short_names.get(name).copied() == Some(1) => {
+                imports.insert(namespace.to_string());
+                name.to_string()
+            }
+            _ => full_name.to_string(),
+        }
+    }
+
+    fn keyword_alias(full_name: &str) -> Option<&'static str> {
+        Some(match full_name {
+            "System.Void" => "void",
+            "System.Boolean" => "bool",
+            "System.Byte" => "byte",
+            "System.SByte" => "sbyte",
+            "System.Int16" => "short",
+            "System.UInt16" => "ushort",
+            "System.Int32" => "int",
+            "System.UInt32" => "uint",
+            "System.Int64" => "long",
+            "System.UInt64" => "ulong",
+            "System.Single" => "float",
+            "System.Double" => "double",
+            "System.Decimal" => "decimal",
+            "System.Char" => "char",
+            "System.String" => "string",
+            "System.Object" => "object",
+            _ => return None,
+        })
+    }
+}
+
+/// Make a name safe to use as a single path component on all platforms
+fn path_component(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let trimmed = sanitized.trim_matches(|c| c == '.' || c == ' ');
+    if trimmed.is_empty() {
+        "_".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// `<stem>.<extension>`, suffixed `_1`, `_2`, ... if the path (compared
+/// case-insensitively, for Windows and macOS) has already been used
+///
+/// The extension is appended rather than set, since namespace stems contain dots.
+fn unique_path(stem: &Path, extension: &str, taken: &mut HashSet<String>) -> PathBuf {
+    let with_suffix = |suffix: &str| {
+        let mut name = stem.as_os_str().to_owned();
+        name.push(format!("{}.{}", suffix, extension));
+        PathBuf::from(name)
+    };
+    let mut path = with_suffix("");
+    let mut suffix = 0;
+    while !taken.insert(path.to_string_lossy().to_lowercase()) {
+        suffix += 1;
+        path = with_suffix(&format!("_{}", suffix));
+    }
+    path
+}
+
+/// IDA Python script generator (equivalent of Il2CppDumper's `ida_with_struct_py3.py`)
+///
+/// The generated script embeds the method table, string literals and the C
+/// header, so it can be run from `File > Script file...` without any other files.
+pub struct IdaScriptGenerator;
+
+impl IdaScriptGenerator {
+    /// Generate the IDA Python script from dump results
+    pub fn generate(results: &DumpResults) -> String {
+        let mut output = String::new();
+
+        write_script_preamble(&mut output, results);
+        output.push_str("import idaapi\n");
+        output.push_str("import idc\n\n");
+        write_script_tables(&mut output, results);
+
+        output.push_str(
+            r#"
+def set_name(addr, name):
+    if not idc.set_name(addr, name, idc.SN_NOWARN | idc.SN_NOCHECK):
+        idc.set_name(addr, name + "_" + hex(addr), idc.SN_NOWARN | idc.SN_NOCHECK)
+
+
+def make_function(addr):
+    if idaapi.get_func(addr) is None:
+        idc.create_insn(addr)
+        idaapi.add_func(addr)
+
+
+def main():
+    image_base = idaapi.get_imagebase()
+
+    print("Importing IL2CPP structures...")
+    errors = idc.parse_decls(HEADER, idc.PT_SILENT)
+    if errors:
+        print("Header imported with %d errors" % errors)
+
+    print("Applying %d methods..." % len(METHODS))
+    for rva, name, signature in METHODS:
+        addr = image_base + rva
+        make_function(addr)
+        set_name(addr, name)
+        # None when the declaration doesn't parse, False when IDA refuses it
+        if not idc.SetType(addr, signature):
+            print("Failed to apply signature at 0x%X: %s" % (addr, signature))
+
+    print("Applying %d string literals..." % len(STRINGS))
+    for rva, index, value in STRINGS:
+        addr = image_base + rva
+        set_name(addr, "StringLiteral_%d" % index)
+        idc.set_cmt(addr, value, 1)
+
+    print("Done")
+
+
+main()
+"#,
+        );
+
+        output
+    }
+
+    /// Write script to file
+    pub fn write_to_file(results: &DumpResults, path: &Path) -> Result<()> {
+        let content = Self::generate(results);
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+/// Ghidra script generator (equivalent of Il2CppDumper's `ghidra_with_struct.py`)
+///
+/// The generated script targets the Jython 2.7 interpreter bundled with Ghidra
+/// and also runs under PyGhidra. Like the IDA script it is self-contained.
+pub struct GhidraScriptGenerator;
+
+impl GhidraScriptGenerator {
+    /// Generate the Ghidra script from dump results
+    pub fn generate(results: &DumpResults) -> String {
+        let mut output = String::new();
+
+        write_script_preamble(&mut output, results);
+        output.push_str("#@category IL2CPP\n\n");
+        output.push_str("from ghidra.app.cmd.function import ApplyFunctionSignatureCmd\n");
+        output.push_str("from ghidra.app.util.cparser.C import CParser, CParserUtils\n");
+        output.push_str("from ghidra.program.model.symbol import SourceType\n\n");
+        write_script_tables(&mut output, results);
+
+        output.push_str(
+            r#"
+def set_name(addr, name):
+    createLabel(addr, name.replace(" ", "-"), True, SourceType.USER_DEFINED)
+
+
+def make_function(addr):
+    if getFunctionAt(addr) is None:
+        disassemble(addr)
+        createFunction(addr, None)
+
+
+def set_signature(addr, signature):
+    try:
+        sig = CParserUtils.parseSignature(None, currentProgram, signature, False)
+    except Exception:
+        sig = None
+    if sig is None:
+        print("Failed to parse signature at %s: %s" % (addr, signature))
+        return
+    ApplyFunctionSignatureCmd(addr, sig, SourceType.USER_DEFINED).applyTo(currentProgram)
+
+
+def main():
+    image_base = currentProgram.getImageBase()
+
+    monitor.setMessage("Importing IL2CPP structures")
+    try:
+        CParser(currentProgram.getDataTypeManager(), True, None).parse(HEADER)
+    except Exception as e:
+        print("Header import failed: %s" % e)
+
+    monitor.setMessage("Applying methods")
+    monitor.initialize(len(METHODS))
+    for rva, name, signature in METHODS:
+        monitor.checkCanceled()
+        addr = image_base.add(rva)
+        make_function(addr)
+        set_name(addr, name)
+        set_signature(addr, signature)
+        monitor.incrementProgress(1)
+
+    monitor.setMessage("Applying string literals")
+    for rva, index, value in STRINGS:
+        addr = image_base.add(rva)
+        set_name(addr, "StringLiteral_%d" % index)
+        setEOLComment(addr, value)
+
+    print("Done")
+
+
+main()
+"#,
+        );
+
+        output
+    }
+
+    /// Write script to file
+    pub fn write_to_file(results: &DumpResults, path: &Path) -> Result<()> {
+        let content = Self::generate(results);
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+/// radare2 script generator
+///
+/// Run with `r2 -i il2cpp.r2 <binary>` or `. il2cpp.r2` from an open session.
+/// Addresses are relative to `$B`, the base address r2 loaded the binary at.
+pub struct Radare2ScriptGenerator;
+
+impl Radare2ScriptGenerator {
+    /// Generate the r2 command script from dump results
+    pub fn generate(results: &DumpResults) -> String {
+        let mut output = String::new();
+
+        output.push_str("# Auto-generated IL2CPP radare2 script\n");
+        output.push_str("# Do not edit manually\n");
+        let _ = writeln!(output, "# Generator: endfield-il2cpp {}", GENERATOR_VERSION);
+        let _ = writeln!(output, "# Dump timestamp: {}", results.timestamp.to_rfc3339());
+        let _ = writeln!(output, "# IL2CPP metadata version: {}\n", results.il2cpp_version);
+
+        let signatures = HeaderGenerator::function_signatures(results);
+        output.push_str("fs il2cpp.methods\n");
+        for (method, name) in unique_function_names(results) {
+            let addr = format!("$B+0x{:X}", method.address.as_u64());
+            let _ = writeln!(output, "af @ {}", addr);
+            let _ = writeln!(output, "afn {} @ {}", name, addr);
+            let _ = writeln!(
+                output,
+                "CCu base64:{} @ {}",
+                base64::engine::general_purpose::STANDARD
+                    .encode(&signatures[&method.id]),
+                addr
+            );
+        }
+
+        output.push_str("\nfs il2cpp.strings\n");
+        for literal in results.string_literals.iter().filter(|s| s.address.as_u64() != 0) {
+            let addr = format!("$B+0x{:X}", literal.address.as_u64());
+            let _ = writeln!(output, "f il2cpp.StringLiteral_{} @ {}", literal.index, addr);
+            let _ = writeln!(
+                output,
+                "CCu base64:{} @ {}",
+                base64::engine::general_purpose::STANDARD.encode(&literal.value),
+                addr
+            );
+        }
+
+        output.push_str("\nfs *\n");
+        output
+    }
+
+    /// Write script to file
+    pub fn write_to_file(results: &DumpResults, path: &Path) -> Result<()> {
+        let content = Self::generate(results);
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+/// Functions, string literals and the C header as JSON, for Binary Ninja
+///
+/// [`BINARY_NINJA_LOADER`] imports `header` and applies each function and
+/// string literal at the image base plus its RVA.
+#[derive(Debug, Serialize)]
+pub struct BinaryNinjaJson {
+    pub generator: String,
+    pub generator_version: String,
+    pub il2cpp_version: u32,
+    pub unity_version: Option<String>,
+    /// C header to import with `parse_types_from_string`
+    pub header: String,
+    pub functions: Vec<BinaryNinjaFunction>,
+    pub strings: Vec<BinaryNinjaString>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BinaryNinjaFunction {
+    /// Address relative to the image base
+    pub rva: u64,
+    pub name: String,
+    pub full_name: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BinaryNinjaString {
+    /// Address relative to the image base
+    pub rva: u64,
+    pub index: u32,
+    pub value: String,
+}
+
+impl BinaryNinjaJson {
+    /// Create from dump results
+    pub fn from_results(results: &DumpResults) -> Self {
+        let signatures = HeaderGenerator::function_signatures(results);
+        let functions = unique_function_names(results)
+            .into_iter()
+            .map(|(method, name)| BinaryNinjaFunction {
+                rva: method.address.as_u64(),
+                name,
+                full_name: method.full_name.clone(),
+                signature: signatures[&method.id].clone(),
+            })
+            .collect();
+
+        let strings = results
+            .string_literals
+            .iter()
+            .filter(|s| s.address.as_u64() != 0)
+            .map(|s| BinaryNinjaString {
+                rva: s.address.as_u64(),
+                index: s.index,
+                value: s.value.clone(),
+            })
+            .collect();
+
+        Self {
+            generator: "endfield-il2cpp".to_string(),
+            generator_version: GENERATOR_VERSION.to_string(),
+            il2cpp_version: results.il2cpp_version,
+            unity_version: results.unity_version.clone(),
+            header: script_header(results),
+            functions,
+            strings,
+        }
+    }
+
+    /// Write to JSON file
+    pub fn write_to_file(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| endfield_core::Error::parse(e.to_string()))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Write [`BINARY_NINJA_LOADER`] to file
+    pub fn write_loader(path: &Path) -> Result<()> {
+        std::fs::write(path, BINARY_NINJA_LOADER)?;
+        Ok(())
+    }
+}
+
+/// Binary Ninja plugin applying a [`BinaryNinjaJson`] file to the open view
+pub const BINARY_NINJA_LOADER: &str = r#"# -*- coding: utf-8 -*-
+# Binary Ninja loader for the JSON written by endfield-il2cpp
+#
+# Copy into the Binary Ninja plugins folder and run "IL2CPP\Load dump JSON",
+# or run it with File > Run Script on an open view.
+import json
+
+from binaryninja import PluginCommand, Symbol, SymbolType
+from binaryninja.interaction import get_open_filename_input
+
+
+def load(bv, path=None):
+    if path is None:
+        path = get_open_filename_input("IL2CPP dump JSON", "*.json")
+        if not path:
+            return
+    if isinstance(path, bytes):
+        path = path.decode("utf-8")
+    with open(path, "rb") as f:
+        dump = json.loads(f.read().decode("utf-8"))
+
+    image_base = getattr(bv, "image_base", bv.start)
+
+    print("Importing IL2CPP structures...")
+    try:
+        parsed = bv.parse_types_from_string(dump["header"])
+        for name, parsed_type in parsed.types.items():
+            bv.define_user_type(name, parsed_type)
+    except SyntaxError as e:
+        print("Failed to import the header: %s" % e)
+
+    print("Applying %d functions..." % len(dump["functions"]))
+    for function in dump["functions"]:
+        addr = image_base + function["rva"]
+        bv.add_function(addr)
+        bv.define_user_symbol(
+            Symbol(SymbolType.FunctionSymbol, addr, function["name"], function["full_name"])
+        )
+        func = bv.get_function_at(addr)
+        if func is None:
+            continue
+        try:
+            func.type = bv.parse_type_string(function["signature"].rstrip(";"))[0]
+        except SyntaxError:
+            print("Failed to apply signature at 0x%X: %s" % (addr, function["signature"]))
+
+    print("Applying %d string literals..." % len(dump["strings"]))
+    for literal in dump["strings"]:
+        addr = image_base + literal["rva"]
+        bv.define_user_symbol(
+            Symbol(SymbolType.DataSymbol, addr, "StringLiteral_%d" % literal["index"])
+        )
+        bv.set_comment_at(addr, literal["value"])
+
+    print("Done")
+
+
+if "bv" in globals():
+    load(bv)
+else:
+    PluginCommand.register(
+        "IL2CPP\\Load dump JSON", "Apply an endfield-il2cpp dump to this view", load
+    )
+"#;
+
+/// Frida TypeScript module generator
+///
+/// Every method becomes an exported `NativeFunction` bound at
+/// `Module.findBaseAddress(MODULE_NAME) + RVA`, typed from its C signature.
+pub struct FridaGenerator;
+
+impl FridaGenerator {
+    /// Generate the TypeScript module for the given native module name
+    /// (e.g. `GameAssembly.dll` or `libil2cpp.so`)
+    pub fn generate(results: &DumpResults, module_name: &str) -> String {
+        let mut output = String::new();
+
+        output.push_str("// Auto-generated IL2CPP Frida module\n");
+        output.push_str("// Do not edit manually\n");
+        let _ = writeln!(output, "// Generator: endfield-il2cpp {}", GENERATOR_VERSION);
+        let _ = writeln!(output, "// Dump timestamp: {}", results.timestamp.to_rfc3339());
+        let _ = writeln!(output, "// IL2CPP metadata version: {}\n", results.il2cpp_version);
+
+        let _ = writeln!(
+            output,
+            "export const MODULE_NAME = {};\n",
+            serde_json::to_string(module_name).unwrap_or_default()
+        );
+        output.push_str(
+            "export const base: NativePointer = (() => {\n\
+             \x20   const address = Module.findBaseAddress(MODULE_NAME);\n\
+             \x20   if (address === null) {\n\
+             \x20       throw new Error(`${MODULE_NAME} is not loaded`);\n\
+             \x20   }\n\
+             \x20   return address;\n\
+             })();\n\n",
+        );
+
+        let signatures = HeaderGenerator::function_signatures(results);
+        for (method, name) in unique_function_names(results) {
+            let mut native_args = Vec::new();
+            let mut ts_args = Vec::new();
+            if !method.is_static {
+                native_args.push("\"pointer\"");
+                ts_args.push("NativePointerValue");
+            }
+            for param in &method.parameters {
+                let (native, _, ts) = Self::frida_type(HeaderGenerator::type_to_c(&param.type_name));
+                native_args.push(native);
+                ts_args.push(ts);
+            }
+            // Trailing `const MethodInfo* method`
+            native_args.push("\"pointer\"");
+            ts_args.push("NativePointerValue");
+
+            let (native_ret, ts_ret, _) =
+                Self::frida_type(HeaderGenerator::type_to_c(&method.return_type));
+
+            let _ = writeln!(
+                output,
+                "/** {} @ RVA 0x{:X} */",
+                signatures[&method.id],
+                method.address.as_u64()
+            );
+            let _ = writeln!(
+                output,
+                "export const {} = new NativeFunction<{}, [{}]>(base.add(0x{:X}), {}, [{}]);\n",
+                name,
+                ts_ret,
+                ts_args.join(", "),
+                method.address.as_u64(),
+                native_ret,
+                native_args.join(", ")
+            );
+        }
+
+        output
+    }
+
+    /// Frida native type plus the TypeScript return and argument types for a C type
+    fn frida_type(c_type: &str) -> (&'static str, &'static str, &'static str) {
+        match c_type {
+            "void" => ("\"void\"", "void", "void"),
+            "bool" => ("\"bool\"", "number", "number"),
+            "int8_t" => ("\"int8\"", "number", "number"),
+            "uint8_t" => ("\"uint8\"", "number", "number"),
+            "int16_t" => ("\"int16\"", "number", "number"),
+            "uint16_t" => ("\"uint16\"", "number", "number"),
+            "int32_t" => ("\"int32\"", "number", "number"),
+            "uint32_t" => ("\"uint32\"", "number", "number"),
+            "int64_t" => ("\"int64\"", "Int64", "number | Int64"),
+            "uint64_t" => ("\"uint64\"", "UInt64", "number | UInt64"),
+            "float" => ("\"float\"", "number", "number"),
+            "double" => ("\"double\"", "number", "number"),
+            _ => ("\"pointer\"", "NativePointer", "NativePointerValue"),
+        }
+    }
+
+    /// Write module to file
+    pub fn write_to_file(results: &DumpResults, module_name: &str, path: &Path) -> Result<()> {
+        let content = Self::generate(results, module_name);
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+/// Methods with a known address paired with a unique C identifier,
+/// suffixing overloads with `_1`, `_2`, ... in dump order
+fn unique_function_names(results: &DumpResults) -> Vec<(&DumpedMethod, String)> {
+    let mut scope = NameScope::default();
+    results
+        .methods
+        .iter()
+        .filter(|m| m.address.as_u64() != 0)
+        .map(|method| (method, scope.claim(&HeaderGenerator::function_name(method))))
+        .collect()
+}
+
+/// C keywords that cannot be used as identifiers
+const C_KEYWORDS: &[&str] = &[
+    "auto", "bool", "break", "case", "char", "const", "continue", "default", "do", "double",
+    "else", "enum", "extern", "float", "for", "goto", "if", "inline", "int", "long", "register",
+    "restrict", "return", "short", "signed", "sizeof", "static", "struct", "switch", "typedef",
+    "union", "unsigned", "void", "volatile", "while", "_Alignas", "_Alignof", "_Atomic",
+    "_Bool", "_Complex", "_Generic", "_Imaginary", "_Noreturn", "_Static_assert",
+    "_Thread_local", "true", "false",
+];
+
+/// Set of identifiers already used in one C scope
+#[derive(Default)]
+struct NameScope {
+    used: HashSet<String>,
+    next_suffix: HashMap<String, usize>,
+}
+
+impl NameScope {
+    /// Reserve `name`, suffixing `_1`, `_2`, ... if it is taken or a keyword
+    fn claim(&mut self, name: &str) -> String {
+        let base = if C_KEYWORDS.contains(&name) {
+            format!("{}_", name)
+        } else {
+            name.to_string()
+        };
+
+        let mut candidate = base.clone();
+        while self.used.contains(&candidate) {
+            let suffix = self.next_suffix.entry(base.clone()).or_insert(0);
+            *suffix += 1;
+            candidate = format!("{}_{}", base, suffix);
+        }
+        self.used.insert(candidate.clone());
+        candidate
+    }
+}
+
+/// Comment block identifying the generator and the dump a script was built from
+fn write_script_preamble(output: &mut String, results: &DumpResults) {
+    output.push_str("# -*- coding: utf-8 -*-\n");
+    output.push_str("# Auto-generated IL2CPP script\n");
+    output.push_str("# Do not edit manually\n");
+    let _ = writeln!(output, "# Generator: endfield-il2cpp {}", GENERATOR_VERSION);
+    let _ = writeln!(output, "# Dump timestamp: {}", results.timestamp.to_rfc3339());
+    let _ = writeln!(output, "# IL2CPP metadata version: {}", results.il2cpp_version);
+    if let Some(ref unity_version) = results.unity_version {
+        let _ = writeln!(output, "# Unity version: {}", unity_version);
+    }
+    output.push('\n');
+}
+
+/// Embedded `HEADER`, `METHODS` and `STRINGS` tables shared by the Python scripts
+///
+/// Methods and string literals are listed by RVA, the latter at the slot their
+/// users load, once the dump resolved them.
+fn write_script_tables(output: &mut String, results: &DumpResults) {
+    let _ = writeln!(
+        output,
+        "GENERATOR_VERSION = {}\n",
+        py_string(GENERATOR_VERSION)
+    );
+    let _ = writeln!(output, "HEADER = {}\n", py_string(&script_header(results)));
+
+    let signatures = HeaderGenerator::function_signatures(results);
+    output.push_str("METHODS = [\n");
+    for method in results.methods.iter().filter(|m| m.address.as_u64() != 0) {
+        let _ = writeln!(
+            output,
+            "    (0x{:X}, {}, {}),",
+            method.address.as_u64(),
+            py_string(&method.full_name),
+            py_string(&signatures[&method.id])
+        );
+    }
+    output.push_str("]\n\n");
+
+    output.push_str("STRINGS = [\n");
+    for literal in results.string_literals.iter().filter(|s| s.address.as_u64() != 0) {
+        let _ = writeln!(
+            output,
+            "    (0x{:X}, {}, {}),",
+            literal.address.as_u64(),
+            literal.index,
+            py_string(&literal.value)
+        );
+    }
+    output.push_str("]\n");
+}
+
+/// Fixed-width integer typedefs standing in for `<stdint.h>`, which neither
+/// IDA's nor Ghidra's C parser can include
+const SCRIPT_HEADER_PRELUDE: &str = "\
+typedef signed char int8_t;
+typedef unsigned char uint8_t;
+typedef short int16_t;
+typedef unsigned short uint16_t;
+typedef int int32_t;
+typedef unsigned int uint32_t;
+typedef long long int64_t;
+typedef unsigned long long uint64_t;
+";
+
+/// C header as embedded in the scripts, with preprocessor lines replaced by typedefs
+fn script_header(results: &DumpResults) -> String {
+    let mut header = String::from(SCRIPT_HEADER_PRELUDE);
+    for line in HeaderGenerator::generate(results).lines() {
+        if !line.starts_with('#') {
+            header.push_str(line);
+            header.push('\n');
+        }
+    }
+    header
+}
+
+/// Quote a string as a Python literal that is valid in both Python 2 and 3
+fn py_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 3);
+    quoted.push_str("u\"");
+    for c in value.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            ' '..='~' => quoted.push(c),
+            c if (c as u32) <= 0xFFFF => {
+                let _ = write!(quoted, "\\u{:04x}", c as u32);
+            }
+            c => {
+                let _ = write!(quoted, "\\U{:08x}", c as u32);
+            }
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use endfield_core::{Address, DumpStatistics, DumpedField, MethodParameter, StringLiteral};
+    use uuid::Uuid;
+
+    fn sample_results() -> DumpResults {
+        DumpResults {
+            timestamp: Utc::now(),
+            unity_version: None,
+            il2cpp_version: 29,
+            types: Vec::new(),
+            methods: vec![DumpedMethod {
+                id: Uuid::new_v4(),
+                name: "Send".to_string(),
+                full_name: "Game.Net.NetManager$$Send".to_string(),
+                address: Address::new(0x1234),
+                return_type: "System.Boolean".to_string(),
+                parameters: vec![MethodParameter {
+                    name: "cmd".to_string(),
+                    type_name: "System.Int32".to_string(),
+                    index: 0,
+                }],
+                class_name: "NetManager".to_string(),
+                namespace: "Game.Net".to_string(),
+                is_static: false,
+                is_virtual: false,
+                is_abstract: false,
+                token: 0x06000001,
+                size: None,
+            }],
+            string_literals: vec![StringLiteral {
+                address: Address::new(0x5000),
+                value: "say \"hi\"\n\u{00e9}".to_string(),
+                index: 3,
+            }],
+            statistics: DumpStatistics::default(),
+        }
+    }
+
+    fn field(name: &str, type_name: &str, offset: u32, is_static: bool) -> DumpedField {
+        DumpedField {
+            name: name.to_string(),
+            type_name: type_name.to_string(),
+            offset,
+            is_static,
+            is_const: false,
+            default_value: None,
+        }
+    }
+
+    fn class(full_name: &str, parent: Option<&str>, fields: Vec<DumpedField>) -> DumpedType {
+        let (namespace, name) = full_name.rsplit_once('.').unwrap_or(("", full_name));
+        DumpedType {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            namespace: namespace.to_string(),
+            full_name: full_name.to_string(),
+            assembly: "Assembly-CSharp.dll".to_string(),
+            parent_type: parent.map(str::to_string),
+            interfaces: Vec::new(),
+            fields,
+            methods: Vec::new(),
+            properties: Vec::new(),
+            is_enum: false,
+            is_interface: false,
+            is_abstract: false,
+            is_sealed: false,
+            token: 0x02000000,
+        }
+    }
+
+    fn enumeration(full_name: &str, members: &[(&str, &str)]) -> DumpedType {
+        let mut fields = vec![field("value__", "System.Int32", 0x10, false)];
+        fields.extend(members.iter().map(|(name, value)| DumpedField {
+            default_value: Some(value.to_string()),
+            ..field(name, full_name, 0, true)
+        }));
+        DumpedType {
+            is_enum: true,
+            ..class(full_name, Some("System.Enum"), fields)
+        }
+    }
+
+    /// Type hierarchy exercising inheritance, padding, overlaps and name clashes
+    fn header_results() -> DumpResults {
+        let mut results = sample_results();
+        results.types = vec![
+            class("Game.Entity", None, vec![
+                field("id", "System.Int64", 0x10, false),
+                field("flags", "System.Byte", 0x18, false),
+            ]),
+            class("Game.Player", Some("Game.Entity"), vec![
+                field("hp", "System.Int32", 0x1C, false),
+                field("name", "System.String", 0x28, false),
+                field("int", "System.Int32", 0x30, false),
+                field("hpAlias", "System.Single", 0x1C, false),
+                field("flags", "System.Boolean", 0x34, false),
+                field("instance", "Game.Player", 0x0, true),
+                field("count", "System.Int32", 0x8, true),
+            ]),
+            class("Other.Player", None, Vec::new()),
+            class("Game.Player", None, vec![field("x", "System.Int32", 0, false)]),
+            class("<Module>", None, vec![
+                field("a", "System.Int32", 0, false),
+                field("b", "System.Double", 0, false),
+            ]),
+            enumeration("Game.State", &[("None", "0"), ("Alive", "1")]),
+            enumeration("Game.Mode", &[("None", "0"), ("Debug", "-1")]),
+        ];
+        results
+    }
+
+    /// Minimal checker for the C subset the header generator emits. It rejects
+    /// unbalanced braces, redefinitions, by-value use of incomplete structs,
+    /// duplicate members or enumerators and keywords used as identifiers.
+    fn check_c_declarations(source: &str) -> std::result::Result<(), String> {
+        let mut tokens = Vec::new();
+        for line in source.lines().filter(|l| !l.trim_start().starts_with('#')) {
+            let line = line.split("//").next().unwrap_or("");
+            let mut chars = line.char_indices().peekable();
+            while let Some((start, c)) = chars.next() {
+                if c.is_whitespace() {
+                    continue;
+                }
+                let mut end = start + c.len_utf8();
+                if c.is_ascii_alphanumeric() || c == '_' {
+                    while let Some(&(i, n)) = chars.peek() {
+                        if !(n.is_ascii_alphanumeric() || n == '_') {
+                            break;
+                        }
+                        end = i + 1;
+                        chars.next();
+                    }
+                }
+                tokens.push(&line[start..end]);
+            }
+        }
+
+        let is_ident = |t: &str| {
+            t.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                && !C_KEYWORDS.contains(&t)
+        };
+        let mut types: HashSet<String> = [
+            "void", "bool", "char", "float", "double", "int8_t", "uint8_t", "int16_t", "uint16_t",
+            "int32_t", "uint32_t", "int64_t", "uint64_t",
+        ]
+        .iter()
+        .map(|t| t.to_string())
+        .collect();
+        let mut complete: HashSet<String> = HashSet::new();
+        let mut enumerators: HashSet<String> = HashSet::new();
+
+        let mut pos = 0;
+        let next = |pos: &mut usize| -> std::result::Result<&str, String> {
+            let token = tokens.get(*pos).copied().ok_or("unexpected end of input")?;
+            *pos += 1;
+            Ok(token)
+        };
+        let expect = |pos: &mut usize, want: &str| -> std::result::Result<(), String> {
+            let token = tokens.get(*pos).copied().unwrap_or("<eof>");
+            *pos += 1;
+            if token == want {
+                Ok(())
+            } else {
+                Err(format!("expected `{}`, found `{}`", want, token))
+            }
+        };
+
+        while pos < tokens.len() {
+            let typedef = tokens[pos] == "typedef";
+            if typedef {
+                pos += 1;
+            }
+            match next(&mut pos)? {
+                "struct" => {
+                    let tag = next(&mut pos)?.to_string();
+                    if !is_ident(&tag) {
+                        return Err(format!("invalid struct tag `{}`", tag));
+                    }
+                    if tokens.get(pos) == Some(&"{") {
+                        pos += 1;
+                        let mut members = HashSet::new();
+                        while tokens.get(pos) != Some(&"}") {
+                            if tokens.get(pos) == Some(&"const") {
+                                pos += 1;
+                            }
+                            let (ty, by_tag) = match next(&mut pos)? {
+                                "struct" => (format!("struct {}", next(&mut pos)?), true),
+                                ty if types.contains(ty) => (ty.to_string(), false),
+                                ty => return Err(format!("unknown type `{}`", ty)),
+                            };
+                            let mut pointer = false;
+                            while tokens.get(pos) == Some(&"*") {
+                                pointer = true;
+                                pos += 1;
+                            }
+                            if by_tag && !pointer && !complete.contains(&ty) {
+                                return Err(format!("incomplete type `{}` used by value", ty));
+                            }
+                            let member = next(&mut pos)?;
+                            if !is_ident(member) || !members.insert(member.to_string()) {
+                                return Err(format!("invalid or duplicate member `{}`", member));
+                            }
+                            if tokens.get(pos) == Some(&"[") {
+                                pos += 1;
+                                next(&mut pos)?;
+                                expect(&mut pos, "]")?;
+                            }
+                            expect(&mut pos, ";")?;
+                        }
+                        pos += 1;
+                        if !complete.insert(format!("struct {}", tag)) {
+                            return Err(format!("redefinition of struct `{}`", tag));
+                        }
+                    }
+                    if typedef {
+                        let alias = next(&mut pos)?;
+                        if !is_ident(alias) || !types.insert(alias.to_string()) {
+                            return Err(format!("invalid typedef `{}`", alias));
+                        }
+                    }
+                    expect(&mut pos, ";")?;
+                }
+                "enum" => {
+                    let tag = next(&mut pos)?;
+                    if !is_ident(tag) || !complete.insert(format!("enum {}", tag)) {
+                        return Err(format!("invalid or duplicate enum `{}`", tag));
+                    }
+                    expect(&mut pos, "{")?;
+                    while tokens.get(pos) != Some(&"}") {
+                        let name = next(&mut pos)?;
+                        if !is_ident(name) || !enumerators.insert(name.to_string()) {
+                            return Err(format!("invalid or duplicate enumerator `{}`", name));
+                        }
+                        if tokens.get(pos) == Some(&"=") {
+                            pos += 1;
+                            if tokens.get(pos) == Some(&"-") {
+                                pos += 1;
+                            }
+                            next(&mut pos)?;
+                        }
+                        if tokens.get(pos) == Some(&",") {
+                            pos += 1;
+                        }
+                    }
+                    pos += 1;
+                    expect(&mut pos, ";")?;
+                }
+                other => return Err(format!("unexpected `{}` at top level", other)),
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_header_parses() {
+        let results = header_results();
+        for pointer_size in [4, 8] {
+            let header = HeaderGenerator::generate_for_pointer_size(&results, pointer_size);
+            if let Err(e) = check_c_declarations(&header) {
+                panic!("{}\n{}", e, header);
+            }
+        }
+    }
+
+    /// Header hierarchy plus a value type and a byte-sized enum embedded in a class
+    fn value_type_results() -> DumpResults {
+        let mut results = header_results();
+        let mut kind = enumeration("Game.Kind", &[("Static", "0"), ("Dynamic", "1")]);
+        kind.fields[0].type_name = "System.Byte".to_string();
+        results.types.extend([
+            class("Game.Body", None, vec![
+                field("position", "Game.Vec2", 0x10, false),
+                field("kind", "Game.Kind", 0x18, false),
+                field("mass", "System.Single", 0x1C, false),
+                field("origin", "Game.Vec2", 0x0, true),
+            ]),
+            class("Game.Vec2", Some("System.ValueType"), vec![
+                field("x", "System.Single", 0x10, false),
+                field("y", "System.Single", 0x14, false),
+            ]),
+            kind,
+        ]);
+        results
+    }
+
+    #[test]
+    fn test_header_value_types() {
+        let header = HeaderGenerator::generate(&value_type_results());
+
+        // Value type fields are defined ahead of the classes embedding them
+        let vec2 = header.find("struct Game_Vec2_Fields {").unwrap();
+        assert!(vec2 < header.find("struct Game_Body_Fields {").unwrap());
+        assert!(header.contains(
+            "struct Game_Body_Fields {\n\
+             \x20   struct Game_Vec2_Fields position;  // Offset: 0x10\n\
+             \x20   uint8_t kind;  // Offset: 0x18\n\
+             \x20   uint8_t _pad0[0x3];\n\
+             \x20   float mass;  // Offset: 0x1C\n\
+             };\n"
+        ));
+        assert!(header.contains("struct Game_Body_StaticFields {\n    struct Game_Vec2_Fields origin;\n};"));
+
+        // Unboxed value types have no object header
+        assert!(header.contains("struct Game_Vec2_o {\n    struct Game_Vec2_Fields fields;\n};"));
+    }
+
+    #[test]
+    fn test_header_layout() {
+        let header = HeaderGenerator::generate(&header_results());
+
+        // Base fields embedded ahead of the subclass' own, padded to real offsets
+        assert!(header.contains(
+            "struct Game_Player_Fields {\n\
+             \x20   int64_t id;  // Offset: 0x10\n\
+             \x20   uint8_t flags;  // Offset: 0x18\n\
+             \x20   uint8_t _pad0[0x3];\n\
+             \x20   int32_t hp;  // Offset: 0x1C\n\
+             \x20   uint8_t _pad1[0x8];\n\
+             \x20   void* name;  // Offset: 0x28\n\
+             \x20   int32_t int_;  // Offset: 0x30\n\
+             \x20   // float hpAlias;  // Offset: 0x1C (overlaps)\n\
+             \x20   bool flags_1;  // Offset: 0x34\n\
+             };\n"
+        ));
+        assert!(header.contains(
+            "struct Game_Player_StaticFields {\n\
+             \x20   void* instance;  // Offset: 0x0\n\
+             \x20   int32_t count;  // Offset: 0x8\n\
+             };\n"
+        ));
+        assert!(header.contains("struct Game_Player_o {\n    struct Game_Player_c* klass;"));
+
+        // Duplicate full names and unusable identifiers are uniquified
+        assert!(header.contains("struct Game_Player_1_o {"));
+        assert!(header.contains("struct _Module__Fields {\n    int32_t a;\n    double b;\n};"));
+        assert!(header.contains("    Game_State_None = 0,\n"));
+        assert!(header.contains("    Game_Mode_Debug = -1,\n"));
+
+        // Types without instance fields have no `fields` member
+        assert!(header.contains("struct Other_Player_o {\n    struct Other_Player_c* klass;\n    void* monitor;\n};"));
+    }
+
+    /// Compile the header with the system C compiler, checking field offsets
+    /// with `offsetof`
+    #[test]
+    #[ignore = "needs a C compiler; run with --ignored"]
+    fn test_header_compiles() {
+        let compiler = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+
+        let dir = std::env::temp_dir().join(format!("endfield_header_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("il2cpp.h"), HeaderGenerator::generate(&value_type_results())).unwrap();
+        std::fs::write(
+            dir.join("check.c"),
+            "#include <stddef.h>\n\
+             #include \"il2cpp.h\"\n\
+             _Static_assert(sizeof(void*) != 8 || offsetof(struct Game_Player_o, fields.hp) == 0x1C, \"hp\");\n\
+             _Static_assert(sizeof(void*) != 8 || offsetof(struct Game_Player_o, fields.flags_1) == 0x34, \"flags\");\n\
+             _Static_assert(sizeof(void*) != 8 || offsetof(struct Game_Player_c, static_fields) == 0xB8, \"static_fields\");\n\
+             _Static_assert(sizeof(void*) != 8 || offsetof(struct Game_Player_c, _2) == 0xC8, \"_2\");\n\
+             _Static_assert(offsetof(struct Game_Player_StaticFields, count) == 0x8, \"count\");\n\
+             _Static_assert(sizeof(void*) != 8 || offsetof(struct Game_Body_o, fields.mass) == 0x1C, \"mass\");\n\
+             _Static_assert(sizeof(struct Game_Vec2_o) == 8, \"Vec2\");\n",
+        )
+        .unwrap();
+
+        let output = std::process::Command::new(&compiler)
+            .args(["-std=c11", "-fsyntax-only", "-Wall", "-Werror"])
+            .arg(dir.join("check.c"))
+            .output()
+            .expect("C compiler");
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    /// Dump results for the C# writer: two assemblies, methods attached to types
+    fn assembly_results() -> DumpResults {
+        let mut results = header_results();
+        let send = results.methods[0].id;
+        results.types[1].methods.push(send);
+        results.types[1].interfaces.push("Other.Player".to_string());
+        results.types[2].assembly = "Other.dll".to_string();
+        results.types.reverse();
+        results
+    }
+
+    #[test]
+    fn test_dummy_assembly_is_deterministic() {
+        let results = assembly_results();
+        let mut shuffled = results.clone();
+        shuffled.types.rotate_left(3);
+        assert_eq!(
+            DummyAssemblyGenerator::generate(&results),
+            DummyAssemblyGenerator::generate(&shuffled)
+        );
+    }
+
+    #[test]
+    fn test_dummy_assembly_members() {
+        let source = DummyAssemblyGenerator::generate(&assembly_results());
+        assert!(source.contains(
+            "    [Token(Token = \"0x02000000\")]\n    \
+             public class Player : Entity, Other.Player\n    {\n"
+        ));
+        assert!(source.contains(
+            "        [FieldOffset(Offset = \"0x1C\")]\n        public int hp;\n"
+        ));
+        assert!(source.contains(
+            "        [Token(Token = \"0x06000001\")]\n        \
+             [Address(RVA = \"0x1234\")]\n        \
+             public bool Send(int cmd) { }\n"
+        ));
+        assert!(source.contains("    public enum Mode\n    {\n        None = 0,\n        Debug = -1,\n"));
+    }
+
+    #[test]
+    fn test_dummy_assembly_value_types() {
+        let mut results = value_type_results();
+        for type_def in &mut results.types {
+            type_def.is_sealed = is_value_type(type_def);
+        }
+        let source = DummyAssemblyGenerator::generate(&results);
+        assert!(source.contains("    public struct Vec2\n    {\n"));
+        assert!(source.contains("    public enum Kind\n    {\n"));
+        // No offsets were resolved for <Module>
+        assert!(source.contains("// Fields\n    public int a;\n    public double b;\n"));
+    }
+
+    #[test]
+    fn test_dummy_assembly_tree() {
+        let results = assembly_results();
+        let dir = std::env::temp_dir().join(format!("endfield_dummy_{}", std::process::id()));
+
+        let relative = |paths: Vec<PathBuf>| -> Vec<String> {
+            paths
+                .iter()
+                .map(|p| p.strip_prefix(&dir).unwrap().to_string_lossy().replace('\\', "/"))
+                .collect()
+        };
+
+        let per_type =
+            DummyAssemblyGenerator::write_tree(&results, None, &dir, CsFileLayout::PerType).unwrap();
+        let entity = std::fs::read_to_string(dir.join("Assembly-CSharp/Game/Entity.cs")).unwrap();
+        assert_eq!(
+            relative(per_type),
+            [
+                "Il2CppDummyDll.cs",
+                "Assembly-CSharp/_Global/_Module_.cs",
+                "Assembly-CSharp/Game/Entity.cs",
+                "Assembly-CSharp/Game/Mode.cs",
+                "Assembly-CSharp/Game/Player.cs",
+                "Assembly-CSharp/Game/Player_1.cs",
+                "Assembly-CSharp/Game/State.cs",
+                "Other/Other/Player.cs",
+            ]
+        );
+        assert!(entity.contains("using Il2CppDummyDll;\n\nnamespace Game\n{\n"));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let per_namespace =
+            DummyAssemblyGenerator::write_tree(&results, None, &dir, CsFileLayout::PerNamespace)
+                .unwrap();
+        assert_eq!(
+            relative(per_namespace),
+            [
+                "Il2CppDummyDll.cs",
+                "Assembly-CSharp/_Global.cs",
+                "Assembly-CSharp/Game.cs",
+                "Other/Other.cs",
+            ]
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_dummy_assembly_tree_dotted_namespaces() {
+        let mut results = sample_results();
+        results.types = vec![class("Game.Net.Client", None, Vec::new()), class("Game.Core.World", None, Vec::new())];
+        let dir = std::env::temp_dir().join(format!("endfield_dotted_{}", std::process::id()));
+
+        let written =
+            DummyAssemblyGenerator::write_tree(&results, None, &dir, CsFileLayout::PerNamespace).unwrap();
+        let names: Vec<String> =
+            written.iter().map(|p| p.file_name().unwrap().to_string_lossy().into_owned()).collect();
+        assert_eq!(names, ["Il2CppDummyDll.cs", "Game.Core.cs", "Game.Net.cs"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_function_signature() {
+        let mut results = value_type_results();
+        let parameter = |name: &str, type_name: &str, index| MethodParameter {
+            name: name.to_string(),
+            type_name: type_name.to_string(),
+            index,
+        };
+        results.methods.push(DumpedMethod {
+            id: Uuid::new_v4(),
+            name: "Move".to_string(),
+            full_name: "Game.Body$$Move".to_string(),
+            return_type: "Game.Mode".to_string(),
+            parameters: vec![
+                parameter("to", "Game.Vec2", 0),
+                parameter("kind", "Game.Kind", 1),
+                parameter("target", "Game.Player", 2),
+                parameter("method", "System.String", 3),
+            ],
+            class_name: "Body".to_string(),
+            namespace: "Game".to_string(),
+            ..results.methods[0].clone()
+        });
+
+        let signatures = HeaderGenerator::function_signatures(&results);
+        assert_eq!(
+            signatures[&results.methods[0].id],
+            "bool Game_Net_NetManager__Send(void* __this, int32_t cmd, const void* method);"
+        );
+        assert_eq!(
+            signatures[&results.methods[1].id],
+            "enum Game_Mode Game_Body__Move(struct Game_Body_o* __this, struct Game_Vec2_o to, \
+             enum Game_Kind kind, struct Game_Player_o* target, void* method, const void* method_1);"
+        );
+    }
+
+    #[test]
+    fn test_unique_function_names() {
+        let mut results = sample_results();
+        let mut overload = results.methods[0].clone();
+        overload.address = Address::new(0x2000);
+        results.methods.push(overload);
+
+        let names: Vec<String> = unique_function_names(&results)
+            .into_iter()
+            .map(|(_, name)| name)
+            .collect();
+        assert_eq!(
+            names,
+            ["Game_Net_NetManager__Send", "Game_Net_NetManager__Send_1"]
+        );
+    }
+
+    #[test]
+    fn test_frida_wrapper_types() {
+        let module = FridaGenerator::generate(&sample_results(), "libil2cpp.so");
+        assert!(module.contains("export const MODULE_NAME = \"libil2cpp.so\";"));
+        assert!(module.contains(
+            "export const Game_Net_NetManager__Send = new NativeFunction<number, \
+             [NativePointerValue, number, NativePointerValue]>(base.add(0x1234), \
+             \"bool\", [\"pointer\", \"int32\", \"pointer\"]);"
+        ));
+    }
+
+    #[test]
+    fn test_radare2_script() {
+        let script = Radare2ScriptGenerator::generate(&sample_results());
+        assert!(script.contains("afn Game_Net_NetManager__Send @ $B+0x1234\n"));
+        assert!(script.contains("f il2cpp.StringLiteral_3 @ $B+0x5000\n"));
+    }
+
+    #[test]
+    fn test_binary_ninja_loader_keys() {
+        // Every key the loader reads is one the JSON has
+        let json = serde_json::to_value(BinaryNinjaJson::from_results(&sample_results())).unwrap();
+        let read: Vec<&str> = BINARY_NINJA_LOADER
+            .split("[\"")
+            .skip(1)
+            .map(|s| s.split('"').next().unwrap())
+            .collect();
+        assert!(read.len() > 5);
+        for key in read {
+            let found = !json[key].is_null()
+                || !json["functions"][0][key].is_null()
+                || !json["strings"][0][key].is_null();
+            assert!(found, "loader reads missing key `{}`", key);
+        }
+    }
+
+    #[test]
+    fn test_py_string_escaping() {
+        assert_eq!(py_string("say \"hi\"\n\u{00e9}"), r#"u"say \"hi\"\n\u00e9""#);
+        assert_eq!(py_string("\u{1F600}"), r#"u"\U0001f600""#);
+    }
+
+    #[test]
+    fn test_scripts_embed_tables() {
+        let results = sample_results();
+        for script in [
+            IdaScriptGenerator::generate(&results),
+            GhidraScriptGenerator::generate(&results),
+        ] {
+            assert!(script.contains(GENERATOR_VERSION));
+            assert!(script.contains("(0x1234, u\"Game.Net.NetManager$$Send\""));
+            assert!(script.contains("(0x5000, 3, u\"say \\\"hi\\\"\\n\\u00e9\")"));
+        }
+    }
+}
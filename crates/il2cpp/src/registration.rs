@@ -0,0 +1,479 @@
+//! Readers for `Il2CppCodeRegistration` and `Il2CppMetadataRegistration`
+//!
+//! Both structures are arrays of pointer-sized words whose field set changes
+//! between IL2CPP versions, including revisions (24.1 to 24.5, 27.1, 29.1)
+//! that share a metadata version and can only be told apart in the binary.
+//! Fields are read in declaration order, skipping the ones a version lacks,
+//! and every count/pointer pair is checked against the binary's mapping.
+
+use crate::metadata::Metadata;
+use crate::search::SearchResult;
+use crate::types::{Il2CppCodeRegistration, Il2CppMetadataRegistration};
+use endfield_binary_parser::BinaryFile;
+use endfield_core::{Address, Architecture, Error, Result};
+use std::collections::HashMap;
+use tracing::{debug, warn};
+
+/// Registration structures read with one layout revision
+#[derive(Debug, Clone)]
+pub struct Registrations {
+    /// Layout revision that passed the sanity checks, e.g. `24.5`
+    pub version: f64,
+    pub code: Il2CppCodeRegistration,
+    pub metadata: Il2CppMetadataRegistration,
+}
+
+impl Registrations {
+    /// Read both structures found by a search, trying each layout revision of
+    /// the metadata version (newest first) and keeping the first whose counts
+    /// agree with the metadata
+    pub fn read(binary: &dyn BinaryFile, found: &SearchResult, metadata: &Metadata) -> Result<Self> {
+        let mut last_error = None;
+        for &version in layout_versions(metadata.version) {
+            let attempt = read_code_registration(binary, found.code_registration, version).and_then(|code| {
+                let registration = read_metadata_registration(binary, found.metadata_registration, version)?;
+                check_against_metadata(&code, &registration, metadata, version)?;
+                Ok(Self { version, code, metadata: registration })
+            });
+            match attempt {
+                Ok(registrations) => return Ok(registrations),
+                Err(e) => {
+                    debug!("Layout v{} rejected: {}", version, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or(Error::UnsupportedVersion(metadata.version)))
+    }
+
+    /// Entry point of every method definition, indexed like `metadata.method_definitions`
+    ///
+    /// Each image's `Il2CppCodeGenModule` is matched by name and holds one
+    /// pointer per method in token order. Methods without code (abstract,
+    /// extern, generic definitions) and modules that fail to read stay at
+    /// [`Address::ZERO`]. Layouts before 24.2 index a global table by a
+    /// `methodIndex` the metadata reader doesn't keep, so they resolve nothing.
+    pub fn method_addresses(&self, binary: &dyn BinaryFile, metadata: &Metadata) -> Vec<Address> {
+        let mut addresses = vec![Address::ZERO; metadata.method_definitions.len()];
+        if self.version < 24.2 {
+            warn!("Method pointers of the v{} layout are not resolved", self.version);
+            return addresses;
+        }
+        let modules = read_code_gen_modules(binary, &self.code);
+
+        for image in &metadata.image_definitions {
+            let Some(name) = metadata.get_string(image.name_index) else {
+                continue;
+            };
+            let Some(pointers) = modules.get(name) else {
+                debug!("No code gen module for {}", name);
+                continue;
+            };
+            let Ok(type_start) = usize::try_from(image.type_start) else {
+                continue;
+            };
+            for type_def in metadata.type_definitions.iter().skip(type_start).take(image.type_count as usize) {
+                let Ok(method_start) = usize::try_from(type_def.method_start) else {
+                    continue;
+                };
+                let methods = metadata.method_definitions.iter().zip(addresses.iter_mut());
+                for (method, address) in methods.skip(method_start).take(type_def.method_count as usize) {
+                    let rid = (method.token & 0x00FF_FFFF) as usize;
+                    if let Some(&pointer) = rid.checked_sub(1).and_then(|i| pointers.get(i)) {
+                        *address = Address::new(pointer);
+                    }
+                }
+            }
+        }
+        addresses
+    }
+
+    /// Virtual address of the slot caching each string literal, indexed like
+    /// `metadata.string_literals`
+    ///
+    /// The metadata's usage pairs name a `metadataUsages` entry for every
+    /// literal a method loads, and that entry points at the slot the method's
+    /// code reads. Only the v19 to v24.5 layouts have the table; literals of
+    /// other layouts, and entries that don't read, stay at [`Address::ZERO`].
+    pub fn string_literal_slots(&self, binary: &dyn BinaryFile, metadata: &Metadata) -> Vec<Address> {
+        let mut slots = vec![Address::ZERO; metadata.string_literals.len()];
+        if self.metadata.metadata_usages == 0 {
+            return slots;
+        }
+        let ptr_size = binary.architecture().pointer_size() as u64;
+
+        for pair in &metadata.metadata_usage_pairs {
+            let usage = pair.encoded_source_index >> 29;
+            let index = (pair.encoded_source_index & 0x1FFF_FFFF) as usize;
+            let destination = pair.destination_index as u64;
+            if usage != USAGE_STRING_LITERAL || index >= slots.len() || destination >= self.metadata.metadata_usages_count {
+                continue;
+            }
+            let entry = self.metadata.metadata_usages.wrapping_add(destination * ptr_size);
+            match FieldReader::new(binary, Address::new(entry)).word() {
+                Ok(slot) => slots[index] = Address::new(slot),
+                Err(e) => debug!("metadataUsages[{}] does not read: {}", destination, e),
+            }
+        }
+        slots
+    }
+
+    /// Offset of every field definition, indexed like `metadata.field_definitions`
+    ///
+    /// From v22 on `fieldOffsets` holds one pointer per type definition to
+    /// the `int32_t` offsets of its fields; earlier layouts index a flat
+    /// `int32_t` array by field. Instance offsets include the object header,
+    /// also for value types. Fields whose offsets don't read, and thread
+    /// statics (`-1`), stay at 0.
+    pub fn field_offsets(&self, binary: &dyn BinaryFile, metadata: &Metadata) -> Vec<u32> {
+        let mut offsets = vec![0u32; metadata.field_definitions.len()];
+        let table = self.metadata.field_offsets;
+        let count = u64::try_from(self.metadata.field_offsets_count).unwrap_or(0);
+        if table == 0 {
+            return offsets;
+        }
+        let read_offsets = |address: u64, slots: &mut [u32]| {
+            match binary.read_va(Address::new(address), slots.len() * 4) {
+                Ok(bytes) => {
+                    for (slot, word) in slots.iter_mut().zip(bytes.chunks_exact(4)) {
+                        let offset = i32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+                        *slot = u32::try_from(offset).unwrap_or(0);
+                    }
+                }
+                Err(e) => debug!("Field offsets at 0x{:X} do not read: {}", address, e),
+            }
+        };
+
+        if metadata.version <= 21 {
+            let len = offsets.len().min(count as usize);
+            read_offsets(table, &mut offsets[..len]);
+            return offsets;
+        }
+
+        let ptr_size = binary.architecture().pointer_size() as u64;
+        for (index, type_def) in metadata.type_definitions.iter().enumerate().take(count as usize) {
+            let Ok(start) = usize::try_from(type_def.field_start) else {
+                continue;
+            };
+            let end = start.saturating_add(type_def.field_count as usize).min(offsets.len());
+            if start >= end {
+                continue;
+            }
+            match FieldReader::new(binary, Address::new(table.wrapping_add(index as u64 * ptr_size))).word() {
+                Ok(0) => {}
+                Ok(pointer) => read_offsets(pointer, &mut offsets[start..end]),
+                Err(e) => debug!("fieldOffsets[{}] does not read: {}", index, e),
+            }
+        }
+        offsets
+    }
+}
+
+/// `Il2CppMetadataUsage` kind of a string literal, in the top three bits of an
+/// encoded source index
+const USAGE_STRING_LITERAL: u32 = 5;
+
+/// Method pointer tables of the `Il2CppCodeGenModule`s, by module name
+///
+/// Modules that don't read cleanly are skipped with a warning.
+fn read_code_gen_modules(binary: &dyn BinaryFile, code: &Il2CppCodeRegistration) -> HashMap<String, Vec<u64>> {
+    // Thumb entry points carry the mode in bit 0
+    let mask = if binary.architecture() == Architecture::Arm32 { !1 } else { !0 };
+    let read_module = |module: u64| -> Result<(String, Vec<u64>)> {
+        let mut r = FieldReader::new(binary, Address::new(module));
+        let name = r.word()?;
+        // uint32_t methodPointerCount, padded to a pointer
+        let count = r.word()? as u32 as u64;
+        let pointers = r.word()?;
+        r.check("CodeGenModule", "methodPointers", count, pointers, Some(r.ptr_size as u64))?;
+
+        let name = binary
+            .read_string_va(Address::new(name), 256)
+            .map_err(|e| Error::parse(e.to_string()))?;
+        let mut table = FieldReader::new(binary, Address::new(pointers));
+        let pointers = (0..count).map(|_| table.word().map(|pointer| pointer & mask)).collect::<Result<_>>()?;
+        Ok((name, pointers))
+    };
+
+    let mut modules = HashMap::new();
+    let mut array = FieldReader::new(binary, Address::new(code.code_gen_modules));
+    for index in 0..code.code_gen_modules_count {
+        match array.word().and_then(read_module) {
+            Ok((name, pointers)) => {
+                modules.insert(name, pointers);
+            }
+            Err(e) => warn!("Ignoring malformed code gen module {}: {}", index, e),
+        }
+    }
+    modules
+}
+
+/// Distinct registration layouts for a metadata version, newest first
+pub fn layout_versions(version: u32) -> &'static [f64] {
+    match version {
+        16 => &[16.0],
+        19 => &[19.0],
+        20 => &[20.0],
+        21 => &[21.0],
+        22 => &[22.0],
+        23 => &[23.0],
+        24 => &[24.5, 24.3, 24.2, 24.1],
+        27 => &[27.1, 27.0],
+        29 => &[29.1, 29.0],
+        31 => &[31.0],
+        _ => &[],
+    }
+}
+
+/// Read `Il2CppCodeRegistration` at `address` using the `version` layout
+pub fn read_code_registration(
+    binary: &dyn BinaryFile,
+    address: Address,
+    version: f64,
+) -> Result<Il2CppCodeRegistration> {
+    let mut r = FieldReader::new(binary, address);
+    let v = version;
+    let registration = Il2CppCodeRegistration {
+        method_pointers_count: r.word_if(v <= 24.1)?,
+        method_pointers: r.word_if(v <= 24.1)?,
+        delegate_wrappers_from_native_to_managed_count: r.word_if(v <= 21.0)?,
+        delegate_wrappers_from_native_to_managed: r.word_if(v <= 21.0)?,
+        reverse_pinvoke_wrapper_count: r.word_if(v >= 22.0)?,
+        reverse_pinvoke_wrappers: r.word_if(v >= 22.0)?,
+        delegate_wrappers_from_managed_to_native_count: r.word_if(v <= 22.0)?,
+        delegate_wrappers_from_managed_to_native: r.word_if(v <= 22.0)?,
+        marshaling_functions_count: r.word_if(v <= 22.0)?,
+        marshaling_functions: r.word_if(v <= 22.0)?,
+        ccw_marshaling_functions_count: r.word_if((21.0..=22.0).contains(&v))?,
+        ccw_marshaling_functions: r.word_if((21.0..=22.0).contains(&v))?,
+        generic_method_pointers_count: r.word()?,
+        generic_method_pointers: r.word()?,
+        generic_adjustor_thunks: r.word_if(v == 24.5 || v >= 27.1)?,
+        invoker_pointers_count: r.word()?,
+        invoker_pointers: r.word()?,
+        custom_attribute_count: r.word_if(v <= 24.5)?,
+        custom_attribute_generators: r.word_if(v <= 24.5)?,
+        guid_count: r.word_if((21.0..=22.0).contains(&v))?,
+        guids: r.word_if((21.0..=22.0).contains(&v))?,
+        unresolvedvirtual_call_count: r.word_if(v >= 22.0)?,
+        unresolvedvirtual_call_pointers: r.word_if(v >= 22.0)?,
+        unresolved_instance_call_pointers: r.word_if(v >= 29.1)?,
+        unresolved_static_call_pointers: r.word_if(v >= 29.1)?,
+        interop_data_count: r.word_if(v >= 23.0)?,
+        interop_data: r.word_if(v >= 23.0)?,
+        windows_runtime_factory_count: r.word_if(v >= 24.3)?,
+        windows_runtime_factory_table: r.word_if(v >= 24.3)?,
+        code_gen_modules_count: r.word_if(v >= 24.2)?,
+        code_gen_modules: r.word_if(v >= 24.2)?,
+    };
+
+    let c = &registration;
+    let p = Some(r.ptr_size as u64);
+    let checks = [
+        ("methodPointers", c.method_pointers_count, c.method_pointers, p),
+        ("reversePInvokeWrappers", c.reverse_pinvoke_wrapper_count, c.reverse_pinvoke_wrappers, p),
+        ("genericMethodPointers", c.generic_method_pointers_count, c.generic_method_pointers, p),
+        ("genericAdjustorThunks", c.generic_method_pointers_count.min(1), c.generic_adjustor_thunks, None),
+        ("invokerPointers", c.invoker_pointers_count, c.invoker_pointers, p),
+        ("customAttributeGenerators", c.custom_attribute_count, c.custom_attribute_generators, p),
+        ("unresolvedVirtualCallPointers", c.unresolvedvirtual_call_count, c.unresolvedvirtual_call_pointers, p),
+        ("interopData", c.interop_data_count, c.interop_data, None),
+        ("windowsRuntimeFactoryTable", c.windows_runtime_factory_count, c.windows_runtime_factory_table, None),
+        ("codeGenModules", c.code_gen_modules_count, c.code_gen_modules, p),
+    ];
+    for (name, count, pointer, element_size) in checks {
+        r.check("CodeRegistration", name, count, pointer, element_size)?;
+    }
+    Ok(registration)
+}
+
+/// Read `Il2CppMetadataRegistration` at `address` using the `version` layout
+pub fn read_metadata_registration(
+    binary: &dyn BinaryFile,
+    address: Address,
+    version: f64,
+) -> Result<Il2CppMetadataRegistration> {
+    let mut r = FieldReader::new(binary, address);
+    let v = version;
+    let registration = Il2CppMetadataRegistration {
+        generic_classes_count: r.count()?,
+        generic_classes: r.word()?,
+        generic_insts_count: r.count()?,
+        generic_insts: r.word()?,
+        generic_method_table_count: r.count()?,
+        generic_method_table: r.word()?,
+        types_count: r.count()?,
+        types: r.word()?,
+        method_specs_count: r.count()?,
+        method_specs: r.word()?,
+        method_references_count: if v <= 16.0 { r.count()? } else { 0 },
+        method_references: r.word_if(v <= 16.0)?,
+        field_offsets_count: r.count()?,
+        field_offsets: r.word()?,
+        type_definition_sizes_count: r.count()?,
+        type_definition_sizes: r.word()?,
+        metadata_usages_count: r.word_if((19.0..=24.5).contains(&v))?,
+        metadata_usages: r.word_if((19.0..=24.5).contains(&v))?,
+    };
+
+    let m = &registration;
+    let p = Some(r.ptr_size as u64);
+    let signed = [
+        ("genericClasses", m.generic_classes_count, m.generic_classes, p),
+        ("genericInsts", m.generic_insts_count, m.generic_insts, p),
+        ("genericMethodTable", m.generic_method_table_count, m.generic_method_table, None),
+        ("types", m.types_count, m.types, p),
+        ("methodSpecs", m.method_specs_count, m.method_specs, None),
+        ("methodReferences", m.method_references_count, m.method_references, None),
+        ("fieldOffsets", m.field_offsets_count, m.field_offsets, p),
+        ("typeDefinitionsSizes", m.type_definition_sizes_count, m.type_definition_sizes, p),
+    ];
+    for (name, count, pointer, element_size) in signed {
+        let count = u64::try_from(count)
+            .map_err(|_| Error::invalid_format(format!("MetadataRegistration.{} has count {}", name, count)))?;
+        r.check("MetadataRegistration", name, count, pointer, element_size)?;
+    }
+    r.check("MetadataRegistration", "metadataUsages", m.metadata_usages_count, m.metadata_usages, p)?;
+    Ok(registration)
+}
+
+/// Cross-check counts that the metadata also knows
+fn check_against_metadata(
+    code: &Il2CppCodeRegistration,
+    registration: &Il2CppMetadataRegistration,
+    metadata: &Metadata,
+    version: f64,
+) -> Result<()> {
+    let type_count = metadata.type_definitions.len() as i64;
+    if registration.type_definition_sizes_count != type_count {
+        return Err(Error::invalid_format(format!(
+            "MetadataRegistration.typeDefinitionsSizesCount is {}, metadata has {} types",
+            registration.type_definition_sizes_count, type_count
+        )));
+    }
+    let image_count = metadata.image_definitions.len() as u64;
+    if version >= 24.2 && code.code_gen_modules_count != image_count {
+        return Err(Error::invalid_format(format!(
+            "CodeRegistration.codeGenModulesCount is {}, metadata has {} images",
+            code.code_gen_modules_count, image_count
+        )));
+    }
+    Ok(())
+}
+
+/// Sequential reader of pointer-sized little-endian words
+struct FieldReader<'a> {
+    binary: &'a dyn BinaryFile,
+    address: u64,
+    ptr_size: usize,
+}
+
+impl<'a> FieldReader<'a> {
+    fn new(binary: &'a dyn BinaryFile, address: Address) -> Self {
+        Self {
+            binary,
+            address: address.as_u64(),
+            ptr_size: binary.architecture().pointer_size(),
+        }
+    }
+
+    fn word(&mut self) -> Result<u64> {
+        let bytes = self
+            .binary
+            .read_va(Address::new(self.address), self.ptr_size)
+            .map_err(|e| Error::parse(e.to_string()))?;
+        self.address = self.address.wrapping_add(self.ptr_size as u64);
+        Ok(match *bytes {
+            [a, b, c, d] => u32::from_le_bytes([a, b, c, d]) as u64,
+            [a, b, c, d, e, f, g, h] => u64::from_le_bytes([a, b, c, d, e, f, g, h]),
+            _ => return Err(Error::invalid_format(format!("Unsupported pointer size {}", self.ptr_size))),
+        })
+    }
+
+    fn word_if(&mut self, present: bool) -> Result<u64> {
+        if present {
+            self.word()
+        } else {
+            Ok(0)
+        }
+    }
+
+    /// Signed count, sign-extended from 32 bits on 32-bit targets
+    fn count(&mut self) -> Result<i64> {
+        let word = self.word()?;
+        Ok(if self.ptr_size == 4 { word as u32 as i32 as i64 } else { word as i64 })
+    }
+
+    /// A non-empty array must start inside the image and, when its element
+    /// size is known, fit inside it
+    fn check(&self, structure: &str, name: &str, count: u64, pointer: u64, element_size: Option<u64>) -> Result<()> {
+        if count == 0 {
+            return Ok(());
+        }
+        let invalid = |reason: &str| {
+            Err(Error::invalid_format(format!(
+                "{}.{} = {:#x} with {} entries {}",
+                structure, name, pointer, count, reason
+            )))
+        };
+        if count > self.binary.data().len() as u64 {
+            return invalid("has an impossible count");
+        }
+        if pointer == 0 || self.binary.va_to_offset(Address::new(pointer)).is_none() {
+            return invalid("is not mapped");
+        }
+        if let Some(size) = element_size {
+            let len = count.saturating_mul(size);
+            if len > usize::MAX as u64 || self.binary.read_va(Address::new(pointer), len as usize).is_err() {
+                return invalid("runs past the image");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endfield_binary_parser::test_util::TestBinary;
+    use endfield_core::Architecture;
+
+    const BASE: u64 = 0x10000;
+
+    #[test]
+    fn test_code_registration_layouts() {
+        // v27.1: reversePInvoke, genericMethodPointers, adjustor thunks, invokers,
+        // unresolved calls, interop, WinRT factories, codeGenModules
+        let array = BASE + 13 * 8;
+        let words = [1, array, 2, array, array, 2, array, 0, 0, 0, 0, 0, 0, 3, array, 0, 0];
+        let binary = TestBinary::words(BASE, &words, Architecture::Arm64);
+
+        let code = read_code_registration(&binary, Address::new(BASE), 27.1).unwrap();
+        assert_eq!(code.generic_method_pointers_count, 2);
+        assert_eq!(code.generic_adjustor_thunks, array);
+        assert_eq!(code.invoker_pointers_count, 2);
+        assert_eq!(code.code_gen_modules_count, 3);
+
+        // Read as v27.0 every field after the thunks shifts and a count becomes a pointer
+        assert!(read_code_registration(&binary, Address::new(BASE), 27.0).is_err());
+    }
+
+    #[test]
+    fn test_metadata_registration_32bit() {
+        // v24.1 on a 32-bit target: counts are sign-extended, metadataUsages present
+        let array = BASE + 18 * 4;
+        let words = [0, 0, 0, 0, 0, 0, 2, array, 0, 0, 1, array, 1, array, 2, array, 0, 0, 0, 0];
+        let binary = TestBinary::words(BASE, &words, Architecture::Arm32);
+
+        let registration = read_metadata_registration(&binary, Address::new(BASE), 24.1).unwrap();
+        assert_eq!(registration.types_count, 2);
+        assert_eq!(registration.type_definition_sizes_count, 1);
+        assert_eq!(registration.metadata_usages_count, 2);
+        assert_eq!(registration.metadata_usages, array);
+
+        let mut words = words;
+        words[6] = 0xFFFF_FFFF;
+        let binary = TestBinary::words(BASE, &words, Architecture::Arm32);
+        assert!(read_metadata_registration(&binary, Address::new(BASE), 24.1).is_err());
+    }
+}
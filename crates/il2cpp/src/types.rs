@@ -60,6 +60,11 @@ pub struct Il2CppGlobalMetadataHeader {
     pub images_size: u32,
     pub assemblies_offset: u32,
     pub assemblies_size: u32,
+    /// v19 to v24
+    pub metadata_usage_lists_offset: u32,
+    pub metadata_usage_lists_size: u32,
+    pub metadata_usage_pairs_offset: u32,
+    pub metadata_usage_pairs_size: u32,
     pub field_refs_offset: u32,
     pub field_refs_size: u32,
     pub referenced_assemblies_offset: u32,
@@ -233,6 +238,14 @@ pub struct Il2CppStringLiteral {
     pub data_index: u32,
 }
 
+/// Metadata usage: the slot `destination_index` in the binary's
+/// `metadataUsages` table receives the item encoded in `encoded_source_index`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Il2CppMetadataUsagePair {
+    pub destination_index: u32,
+    pub encoded_source_index: u32,
+}
+
 /// Field reference
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Il2CppFieldRef {
@@ -241,22 +254,54 @@ pub struct Il2CppFieldRef {
 }
 
 /// Code registration structure (found in binary)
+///
+/// Fields absent from a version's layout are left at zero; see
+/// [`crate::registration`] for which versions carry which fields.
 #[derive(Debug, Clone, Default)]
 pub struct Il2CppCodeRegistration {
+    /// v24.1 and earlier; later versions keep method pointers per code gen module
+    pub method_pointers_count: u64,
+    pub method_pointers: u64,
+    /// v21 and earlier
+    pub delegate_wrappers_from_native_to_managed_count: u64,
+    pub delegate_wrappers_from_native_to_managed: u64,
+    /// v22 and later
     pub reverse_pinvoke_wrapper_count: u64,
     pub reverse_pinvoke_wrappers: u64,
+    /// v22 and earlier
+    pub delegate_wrappers_from_managed_to_native_count: u64,
+    pub delegate_wrappers_from_managed_to_native: u64,
+    /// v22 and earlier
+    pub marshaling_functions_count: u64,
+    pub marshaling_functions: u64,
+    /// v21 to v22
+    pub ccw_marshaling_functions_count: u64,
+    pub ccw_marshaling_functions: u64,
     pub generic_method_pointers_count: u64,
     pub generic_method_pointers: u64,
+    /// v24.5 and v27.1 and later; shares `generic_method_pointers_count`
     pub generic_adjustor_thunks: u64,
+    pub invoker_pointers_count: u64,
     pub invoker_pointers: u64,
+    /// v24.5 and earlier; later versions keep generators per code gen module
     pub custom_attribute_count: u64,
     pub custom_attribute_generators: u64,
+    /// v21 to v22
+    pub guid_count: u64,
+    pub guids: u64,
+    /// v22 and later
     pub unresolvedvirtual_call_count: u64,
     pub unresolvedvirtual_call_pointers: u64,
+    /// v29.1 and later; share `unresolvedvirtual_call_count`
+    pub unresolved_instance_call_pointers: u64,
+    pub unresolved_static_call_pointers: u64,
+    /// v23 and later
     pub interop_data_count: u64,
     pub interop_data: u64,
+    /// v24.3 and later
     pub windows_runtime_factory_count: u64,
     pub windows_runtime_factory_table: u64,
+    /// v24.2 and later
     pub code_gen_modules_count: u64,
     pub code_gen_modules: u64,
 }
@@ -274,10 +319,14 @@ pub struct Il2CppMetadataRegistration {
     pub types: u64,
     pub method_specs_count: i64,
     pub method_specs: u64,
+    /// v16 only
+    pub method_references_count: i64,
+    pub method_references: u64,
     pub field_offsets_count: i64,
     pub field_offsets: u64,
     pub type_definition_sizes_count: i64,
     pub type_definition_sizes: u64,
+    /// v19 to v24.5; removed in v27
     pub metadata_usages_count: u64,
     pub metadata_usages: u64,
 }
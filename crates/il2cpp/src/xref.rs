@@ -0,0 +1,207 @@
+//! Cross-references between dumped methods, string literals and code
+//!
+//! Wraps the binary-level [`XrefIndex`] and maps every referencing site back
+//! to the dumped method containing it, so "who calls `NetManager.Send`?" or
+//! "where is this string used?" can be answered by name.
+
+use endfield_binary_parser::{BinaryFile, XrefIndex, XrefKind};
+use endfield_core::{Address, DumpResults, DumpedMethod, StringLiteral};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Instruction referencing an address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XrefSite {
+    /// Referencing instruction
+    pub address: Address,
+    /// Reference kind
+    pub kind: XrefKind,
+    /// Method containing the instruction
+    pub method_id: Option<Uuid>,
+    /// Full name of the containing method
+    pub method_name: Option<String>,
+}
+
+/// Queryable cross-reference index over a dump
+pub struct CrossReferences {
+    index: XrefIndex,
+    /// Added to the dump's relative addresses
+    image_base: u64,
+    /// start -> (id, full name, end when known)
+    methods: BTreeMap<u64, (Uuid, String, Option<u64>)>,
+}
+
+impl CrossReferences {
+    /// Sweep the binary and index references against the dumped methods
+    pub fn build(binary: &dyn BinaryFile, results: &DumpResults) -> Self {
+        Self::from_index(XrefIndex::build(binary), binary, results)
+    }
+
+    /// Map an existing index against the dumped methods
+    ///
+    /// Methods end after their dumped size, or else where the binary's
+    /// function table says the function at their address ends.
+    pub fn from_index(index: XrefIndex, binary: &dyn BinaryFile, results: &DumpResults) -> Self {
+        let image_base = binary.image_base().as_u64();
+        let methods = results
+            .methods
+            .iter()
+            .filter(|method| method.address != Address::ZERO)
+            .map(|method| {
+                let start = Address::new(image_base.wrapping_add(method.address.as_u64()));
+                let end = match method.size {
+                    Some(size) => Some(start.as_u64().saturating_add(size)),
+                    None => binary
+                        .function_at(start)
+                        .filter(|function| function.start == start)
+                        .map(|function| function.end.as_u64()),
+                };
+                (start.as_u64(), (method.id, method.full_name.clone(), end))
+            })
+            .collect();
+        Self { index, image_base, methods }
+    }
+
+    /// Virtual address of a relative address from the dump
+    fn va(&self, rva: Address) -> Address {
+        Address::new(self.image_base.wrapping_add(rva.as_u64()))
+    }
+
+    /// Underlying address index
+    pub fn index(&self) -> &XrefIndex {
+        &self.index
+    }
+
+    /// Method containing `address`, as (id, full name)
+    ///
+    /// Without a known end, a method runs up to the next method's start.
+    pub fn method_at(&self, address: Address) -> Option<(Uuid, &str)> {
+        self.methods
+            .range(..=address.as_u64())
+            .next_back()
+            .filter(|(_, (_, _, end))| end.is_none_or(|end| address.as_u64() < end))
+            .map(|(_, (id, name, _))| (*id, name.as_str()))
+    }
+
+    /// All references to the virtual address `address`
+    pub fn references_to(&self, address: Address) -> Vec<XrefSite> {
+        self.index
+            .to(address)
+            .iter()
+            .map(|xref| {
+                let method = self.method_at(xref.from);
+                XrefSite {
+                    address: xref.from,
+                    kind: xref.kind,
+                    method_id: method.map(|(id, _)| id),
+                    method_name: method.map(|(_, name)| name.to_string()),
+                }
+            })
+            .collect()
+    }
+
+    /// Call and branch sites targeting a method
+    pub fn callers_of(&self, method: &DumpedMethod) -> Vec<XrefSite> {
+        if method.address == Address::ZERO {
+            return Vec::new();
+        }
+        self.references_to(self.va(method.address))
+            .into_iter()
+            .filter(|site| site.kind != XrefKind::Load)
+            .collect()
+    }
+
+    /// Call sites of every method matching `name` (`Class.Method` or a full name)
+    pub fn callers_by_name(&self, results: &DumpResults, name: &str) -> Vec<XrefSite> {
+        find_methods(results, name)
+            .flat_map(|method| self.callers_of(method))
+            .collect()
+    }
+
+    /// Loads of a string literal's metadata-usage slot
+    ///
+    /// The dump resolves slots from the metadataUsages table, which only the
+    /// v19 to v24.5 layouts have; literals of other dumps have no uses.
+    pub fn uses_of_string(&self, literal: &StringLiteral) -> Vec<XrefSite> {
+        if literal.address == Address::ZERO {
+            return Vec::new();
+        }
+        self.references_to(self.va(literal.address))
+            .into_iter()
+            .filter(|site| site.kind == XrefKind::Load)
+            .collect()
+    }
+}
+
+/// Methods whose full name (with `$$` read as `.`) equals or ends with `.name`
+pub fn find_methods<'a>(results: &'a DumpResults, name: &'a str) -> impl Iterator<Item = &'a DumpedMethod> {
+    results.methods.iter().filter(move |method| {
+        let dotted = method.full_name.replace("$$", ".");
+        method.full_name == name || dotted == name || dotted.ends_with(&format!(".{}", name))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endfield_binary_parser::test_util::TestBinary;
+    use endfield_binary_parser::Xref;
+    use endfield_core::DumpStatistics;
+
+    fn method(full_name: &str, address: u64) -> DumpedMethod {
+        DumpedMethod {
+            id: Uuid::new_v4(),
+            name: full_name.rsplit("$$").next().unwrap().to_string(),
+            full_name: full_name.to_string(),
+            address: Address::new(address),
+            return_type: "void".to_string(),
+            parameters: Vec::new(),
+            class_name: String::new(),
+            namespace: String::new(),
+            is_static: false,
+            is_virtual: false,
+            is_abstract: false,
+            token: 0,
+            size: None,
+        }
+    }
+
+    #[test]
+    fn test_callers_and_string_uses() {
+        let results = DumpResults {
+            timestamp: chrono::Utc::now(),
+            unity_version: None,
+            il2cpp_version: 29,
+            types: Vec::new(),
+            methods: vec![
+                method("Game.NetManager$$Send", 0x1000),
+                DumpedMethod { size: Some(0x40), ..method("Game.Player$$Update", 0x2000) },
+            ],
+            string_literals: vec![StringLiteral { address: Address::new(0x9000), value: "hello".to_string(), index: 0 }],
+            statistics: DumpStatistics::default(),
+        };
+        let xref = |from, to, kind| Xref { from: Address::new(from), to: Address::new(to), kind };
+        let index = XrefIndex::from_xrefs(vec![
+            xref(0x2010, 0x1000, XrefKind::Call),
+            xref(0x2020, 0x9000, XrefKind::Load),
+            xref(0x0800, 0x1000, XrefKind::Load),
+            xref(0x2040, 0x1000, XrefKind::Load),
+        ]);
+        let xrefs = CrossReferences::from_index(index, &TestBinary::empty(), &results);
+
+        let callers = xrefs.callers_by_name(&results, "NetManager.Send");
+        assert_eq!(callers.len(), 1);
+        assert_eq!(callers[0].address, Address::new(0x2010));
+        assert_eq!(callers[0].method_name.as_deref(), Some("Game.Player$$Update"));
+
+        let uses = xrefs.uses_of_string(&results.string_literals[0]);
+        assert_eq!(uses.len(), 1);
+        assert_eq!(uses[0].method_id, Some(results.methods[1].id));
+
+        // Sites before any known method and past the end of the last one
+        let sites = xrefs.references_to(Address::new(0x1000));
+        assert_eq!(sites.iter().filter(|site| site.method_name.is_none()).count(), 2);
+        assert_eq!(xrefs.method_at(Address::new(0x203C)).map(|(_, name)| name), Some("Game.Player$$Update"));
+        assert_eq!(xrefs.method_at(Address::new(0x2040)), None);
+    }
+}